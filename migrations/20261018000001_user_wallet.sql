@@ -0,0 +1,3 @@
+-- 钱包登录用户：在 cook_jcc_user 上记录绑定的钱包地址
+ALTER TABLE cook_jcc_user ADD COLUMN wallet VARCHAR(64) NULL DEFAULT NULL AFTER email;
+CREATE UNIQUE INDEX idx_cook_jcc_user_wallet ON cook_jcc_user (wallet);
@@ -0,0 +1,2 @@
+-- Argon2id PHC 格式的密码哈希比 SHA256 十六进制串更长
+ALTER TABLE cook_jcc_user MODIFY COLUMN password VARCHAR(255) NOT NULL DEFAULT '';
@@ -0,0 +1,18 @@
+-- 一个账户可绑定多个 Solana / EVM 钱包，任一已绑定钱包均可登录同一用户
+CREATE TABLE IF NOT EXISTS cook_jcc_user_wallets (
+    id INT UNSIGNED NOT NULL AUTO_INCREMENT,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+    user_id INT UNSIGNED NOT NULL,
+    wallet VARCHAR(64) NOT NULL,
+    network VARCHAR(16) NOT NULL, -- sol / evm
+    PRIMARY KEY (id),
+    UNIQUE KEY idx_cook_jcc_user_wallets_wallet (wallet),
+    KEY idx_cook_jcc_user_wallets_user_id (user_id)
+);
+
+-- 迁移已有的钱包登录用户
+INSERT INTO cook_jcc_user_wallets (user_id, wallet, network, created_at, updated_at)
+SELECT id, wallet, IF(wallet LIKE '0x%', 'evm', 'sol'), created_at, updated_at
+FROM cook_jcc_user
+WHERE wallet IS NOT NULL AND deleted_at IS NULL;
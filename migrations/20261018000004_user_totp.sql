@@ -0,0 +1,10 @@
+-- 邮箱账户的TOTP双重认证，secret 为 AES-GCM 加密后的密钥，recovery_codes 为恢复码哈希的JSON数组
+CREATE TABLE IF NOT EXISTS cook_jcc_user_totp (
+    user_id INT UNSIGNED NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+    secret VARCHAR(255) NOT NULL,
+    enabled TINYINT(1) NOT NULL DEFAULT 0,
+    recovery_codes TEXT NOT NULL,
+    PRIMARY KEY (user_id)
+);
@@ -0,0 +1,17 @@
+-- 用户API密钥，仅保存密钥的SHA256哈希；scopes 为逗号分隔的权限，ip_allowlist 为逗号分隔的IP或CIDR
+CREATE TABLE IF NOT EXISTS cook_jcc_user_api_keys (
+    id INT UNSIGNED NOT NULL AUTO_INCREMENT,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+    user_id INT UNSIGNED NOT NULL,
+    name VARCHAR(64) NOT NULL,
+    prefix VARCHAR(16) NOT NULL,
+    key_hash CHAR(64) NOT NULL,
+    scopes VARCHAR(255) NOT NULL,
+    ip_allowlist TEXT NULL,
+    last_used_at DATETIME NULL DEFAULT NULL,
+    revoked_at DATETIME NULL DEFAULT NULL,
+    PRIMARY KEY (id),
+    UNIQUE KEY idx_cook_jcc_user_api_keys_hash (key_hash),
+    KEY idx_cook_jcc_user_api_keys_user_id (user_id)
+);
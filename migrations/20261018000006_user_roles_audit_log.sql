@@ -0,0 +1,16 @@
+-- 用户角色：user、operator、admin
+ALTER TABLE cook_jcc_user ADD COLUMN role VARCHAR(16) NOT NULL DEFAULT 'user' AFTER parent;
+
+-- 审计日志，记录管理操作等敏感行为；target 为操作对象，detail 为JSON格式的补充信息
+CREATE TABLE IF NOT EXISTS cook_jcc_audit_log (
+    id BIGINT UNSIGNED NOT NULL AUTO_INCREMENT,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    user_id INT UNSIGNED NULL,
+    event VARCHAR(64) NOT NULL,
+    target VARCHAR(128) NULL,
+    detail TEXT NULL,
+    ip VARCHAR(45) NULL,
+    PRIMARY KEY (id),
+    KEY idx_cook_jcc_audit_log_user_id (user_id, created_at),
+    KEY idx_cook_jcc_audit_log_event (event, created_at)
+);
@@ -0,0 +1,6 @@
+-- 审计日志记录认证事件：钱包地址、User-Agent、由User-Agent识别的钱包类型以及结果（success、failure、locked）
+ALTER TABLE cook_jcc_audit_log
+    ADD COLUMN wallet VARCHAR(64) NULL AFTER target,
+    ADD COLUMN user_agent VARCHAR(255) NULL AFTER ip,
+    ADD COLUMN wallet_type VARCHAR(32) NULL AFTER user_agent,
+    ADD COLUMN outcome VARCHAR(16) NOT NULL DEFAULT 'success' AFTER wallet_type;
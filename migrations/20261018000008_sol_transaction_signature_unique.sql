@@ -0,0 +1,3 @@
+-- 交易按签名幂等写入，回填和实时监听可以重复处理同一笔交易
+ALTER TABLE cook_wm_sol_transaction
+    ADD UNIQUE INDEX uk_signature (signature);
@@ -0,0 +1,8 @@
+-- 交易的确认级别：监听在confirmed级别写入，所在slot最终确认后改为finalized，slot被跳过时撤销
+ALTER TABLE cook_wm_sol_transaction
+    ADD COLUMN commitment VARCHAR(16) NOT NULL DEFAULT 'finalized' AFTER slot,
+    ADD INDEX idx_commitment_slot (commitment, slot);
+
+-- 统计按代币和小时汇总，交易写入和撤销时增量更新
+ALTER TABLE cook_wm_sol_stat
+    ADD UNIQUE INDEX uk_mint_create_time (mint, create_time);
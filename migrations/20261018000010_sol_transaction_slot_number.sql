@@ -0,0 +1,8 @@
+-- 结算按数值slot查找尚未最终确认的交易，slot列为字符串，比较需要CAST而无法使用索引
+-- 只有监听写入的confirmed交易需要结算，其他来源写入的交易该列为空
+ALTER TABLE cook_wm_sol_transaction
+    ADD COLUMN slot_number BIGINT UNSIGNED NULL AFTER commitment,
+    DROP INDEX idx_commitment_slot,
+    ADD INDEX idx_commitment_slot_number (commitment, slot_number);
+
+UPDATE cook_wm_sol_transaction SET slot_number = CAST(slot AS UNSIGNED) WHERE commitment = 'confirmed';
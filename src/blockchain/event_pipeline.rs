@@ -0,0 +1,499 @@
+//! 交易事件管道
+//!
+//! 监听产生的事件先进入有界队列，队列满时按配置等待或丢弃。分发任务把事件复制到每个输出端各自的队列，
+//! 输出端并发投递，失败的事件进入该输出端的重试队列按退避重试，多次失败或队列已满时写入Redis死信。
+//! 分发不等待输出端，单个输出端变慢只会让它自己的事件进入死信，不影响监听和其他输出端。
+//! 死信可以通过管理接口重新放入输出端的投递队列。
+//! 停止服务时先关闭管道，等待各输出端处理完已入队的事件，避免重启丢失已越过监听进度的事件。
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use serde::Serialize;
+use serde_json::json;
+use tokio::sync::Notify;
+use tokio::task::JoinHandle;
+use tokio::time::{sleep_until, timeout, Instant};
+use crate::blockchain::{EventSink, TransactionEvent};
+use crate::config::{EventPipelineConfig, OverflowPolicy};
+use crate::repositories::RedisRepository;
+use crate::utils::{AppError, AppResult};
+use tracing::{error, info, warn};
+
+/// 单次投递的超时时间
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(30);
+/// 重试等待的上限
+const MAX_RETRY_BACKOFF: Duration = Duration::from_secs(60);
+
+/// 有界事件队列
+struct EventQueue {
+    capacity: usize,
+    events: Mutex<VecDeque<TransactionEvent>>,
+    not_empty: Notify,
+    not_full: Notify,
+    closed: AtomicBool,
+}
+
+impl EventQueue {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            events: Mutex::new(VecDeque::new()),
+            not_empty: Notify::new(),
+            not_full: Notify::new(),
+            closed: AtomicBool::new(false),
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.events.lock().unwrap().len()
+    }
+
+    /// 不等待地复制事件入队，队列已满或已关闭时返回`false`
+    fn try_push(&self, event: &TransactionEvent) -> bool {
+        let mut events = self.events.lock().unwrap();
+        if events.len() >= self.capacity || self.closed.load(Ordering::Acquire) {
+            return false;
+        }
+        events.push_back(event.clone());
+        drop(events);
+        self.not_empty.notify_one();
+        true
+    }
+
+    /// 按策略入队，返回被丢弃的事件；队列已关闭时返回`Err`
+    async fn push(&self, event: TransactionEvent, policy: OverflowPolicy) -> Result<Option<TransactionEvent>, TransactionEvent> {
+        loop {
+            let not_full = self.not_full.notified();
+            {
+                let mut events = self.events.lock().unwrap();
+                if self.closed.load(Ordering::Acquire) {
+                    return Err(event);
+                }
+                let dropped = if events.len() < self.capacity {
+                    None
+                } else {
+                    match policy {
+                        OverflowPolicy::Block => None,
+                        OverflowPolicy::DropNewest => return Ok(Some(event)),
+                        OverflowPolicy::DropOldest => events.pop_front(),
+                    }
+                };
+                if events.len() < self.capacity {
+                    events.push_back(event);
+                    drop(events);
+                    self.not_empty.notify_one();
+                    return Ok(dropped);
+                }
+            }
+            // `notified`在检查前创建，检查后到来的通知不会丢失
+            not_full.await;
+        }
+    }
+
+    /// 出队，队列关闭且已取空时返回`None`
+    async fn pop(&self) -> Option<TransactionEvent> {
+        loop {
+            let not_empty = self.not_empty.notified();
+            {
+                let mut events = self.events.lock().unwrap();
+                if let Some(event) = events.pop_front() {
+                    drop(events);
+                    self.not_full.notify_one();
+                    return Some(event);
+                }
+                if self.closed.load(Ordering::Acquire) {
+                    return None;
+                }
+            }
+            not_empty.await;
+        }
+    }
+
+    fn close(&self) {
+        self.closed.store(true, Ordering::Release);
+        self.not_empty.notify_waiters();
+        self.not_full.notify_waiters();
+    }
+}
+
+/// 等待重试的事件
+struct PendingRetry {
+    event: TransactionEvent,
+    attempts: u32,
+    ready_at: Instant,
+}
+
+/// 输出端状态
+#[derive(Debug, Clone, Serialize)]
+pub struct EventSinkStatus {
+    pub name: String,
+    /// 待投递的事件数
+    pub queued: usize,
+    /// 等待重试的事件数
+    pub retrying: usize,
+    pub delivered: u64,
+    pub retried: u64,
+    pub dead_lettered: u64,
+    /// Redis中保存的死信数
+    pub dead_letters_stored: Option<u64>,
+}
+
+/// 事件管道状态
+#[derive(Debug, Clone, Serialize)]
+pub struct EventPipelineStatus {
+    pub capacity: usize,
+    pub overflow: OverflowPolicy,
+    pub queued: usize,
+    pub accepted: u64,
+    pub dropped: u64,
+    pub sinks: Vec<EventSinkStatus>,
+}
+
+/// 死信重放结果
+#[derive(Debug, Clone, Serialize)]
+pub struct DeadLetterReplay {
+    pub sink: String,
+    /// 重新放入投递队列的事件数
+    pub requeued: usize,
+    /// 无法解析的死信数，原样保留在Redis中
+    pub invalid: usize,
+    /// Redis中剩余的死信数
+    pub remaining: Option<u64>,
+}
+
+/// 单个输出端的投递任务
+struct SinkWorker {
+    sink: Arc<dyn EventSink>,
+    queue: EventQueue,
+    redis: RedisRepository,
+    max_attempts: u32,
+    retry_backoff: Duration,
+    dead_letter_max_len: usize,
+    retrying: AtomicUsize,
+    delivered: AtomicU64,
+    retried: AtomicU64,
+    dead_lettered: AtomicU64,
+}
+
+impl SinkWorker {
+    /// 第`attempts`次失败后的重试等待时间
+    fn backoff(&self, attempts: u32) -> Duration {
+        self.retry_backoff
+            .saturating_mul(2u32.saturating_pow(attempts.saturating_sub(1)))
+            .min(MAX_RETRY_BACKOFF)
+    }
+
+    /// 持续投递，队列关闭后处理完剩余事件，仍在等待重试的事件写入死信
+    async fn run(self: Arc<Self>) {
+        let mut retries: Vec<PendingRetry> = Vec::new();
+        loop {
+            let next_retry = retries.iter().map(|retry| retry.ready_at).min();
+            tokio::select! {
+                event = self.queue.pop() => match event {
+                    Some(event) => self.deliver(event, 0, &mut retries).await,
+                    None => break,
+                },
+                _ = sleep_until(next_retry.unwrap_or_else(Instant::now)), if next_retry.is_some() => {
+                    let now = Instant::now();
+                    let (ready, waiting): (Vec<_>, Vec<_>) = retries.drain(..).partition(|retry| retry.ready_at <= now);
+                    retries = waiting;
+                    for retry in ready {
+                        self.retried.fetch_add(1, Ordering::Relaxed);
+                        self.deliver(retry.event, retry.attempts, &mut retries).await;
+                    }
+                }
+            }
+            self.retrying.store(retries.len(), Ordering::Relaxed);
+        }
+
+        for retry in retries {
+            self.dead_letter(&retry.event, "pipeline stopped").await;
+        }
+        self.retrying.store(0, Ordering::Relaxed);
+        info!("事件输出端 {} 已停止", self.sink.name());
+    }
+
+    /// 投递事件，`attempts`为此前已失败的次数
+    async fn deliver(&self, event: TransactionEvent, attempts: u32, retries: &mut Vec<PendingRetry>) {
+        let result = match timeout(DELIVERY_TIMEOUT, self.sink.deliver(&event)).await {
+            Ok(result) => result,
+            Err(_) => Err(AppError::internal("投递超时")),
+        };
+        let Err(e) = result else {
+            self.delivered.fetch_add(1, Ordering::Relaxed);
+            return;
+        };
+
+        let attempts = attempts + 1;
+        if attempts >= self.max_attempts || retries.len() >= self.queue.capacity {
+            warn!("事件输出端 {} 投递失败 {} 次: {}", self.sink.name(), attempts, e);
+            self.dead_letter(&event, &e.to_string()).await;
+        } else {
+            retries.push(PendingRetry {
+                event,
+                attempts,
+                ready_at: Instant::now() + self.backoff(attempts),
+            });
+        }
+    }
+
+    /// 写入死信，Redis不可用时记录到日志
+    async fn dead_letter(&self, event: &TransactionEvent, reason: &str) {
+        self.dead_lettered.fetch_add(1, Ordering::Relaxed);
+        let entry = json!({
+            "sink": self.sink.name(),
+            "reason": reason,
+            "failed_at": chrono::Utc::now(),
+            "event": event,
+        })
+        .to_string();
+        if let Err(e) = self.redis.push_dead_letter(self.sink.name(), &entry, self.dead_letter_max_len).await {
+            error!("写入死信失败: {} - {}", e, entry);
+        }
+    }
+
+    async fn status(&self) -> EventSinkStatus {
+        EventSinkStatus {
+            name: self.sink.name().to_string(),
+            queued: self.queue.len(),
+            retrying: self.retrying.load(Ordering::Relaxed),
+            delivered: self.delivered.load(Ordering::Relaxed),
+            retried: self.retried.load(Ordering::Relaxed),
+            dead_lettered: self.dead_lettered.load(Ordering::Relaxed),
+            dead_letters_stored: self.redis.dead_letter_count(self.sink.name()).await.ok(),
+        }
+    }
+}
+
+/// 交易事件管道
+pub struct EventPipeline {
+    queue: EventQueue,
+    overflow: OverflowPolicy,
+    workers: Vec<Arc<SinkWorker>>,
+    /// 分发任务和各输出端投递任务，停止时等待其处理完剩余事件
+    handles: Mutex<Vec<JoinHandle<()>>>,
+    started: AtomicBool,
+    accepted: AtomicU64,
+    dropped: AtomicU64,
+}
+
+impl EventPipeline {
+    pub fn new(config: &EventPipelineConfig, sinks: Vec<Arc<dyn EventSink>>, redis: RedisRepository) -> Self {
+        let workers = sinks
+            .into_iter()
+            .map(|sink| {
+                Arc::new(SinkWorker {
+                    sink,
+                    queue: EventQueue::new(config.sink_queue_capacity),
+                    redis: redis.clone(),
+                    max_attempts: config.max_attempts.max(1),
+                    retry_backoff: Duration::from_millis(config.retry_backoff_ms),
+                    dead_letter_max_len: config.dead_letter_max_len.max(1),
+                    retrying: AtomicUsize::new(0),
+                    delivered: AtomicU64::new(0),
+                    retried: AtomicU64::new(0),
+                    dead_lettered: AtomicU64::new(0),
+                })
+            })
+            .collect();
+
+        Self {
+            queue: EventQueue::new(config.channel_capacity),
+            overflow: config.overflow,
+            workers,
+            handles: Mutex::new(Vec::new()),
+            started: AtomicBool::new(false),
+            accepted: AtomicU64::new(0),
+            dropped: AtomicU64::new(0),
+        }
+    }
+
+    /// 启动分发任务和各输出端的投递任务，重复调用无效
+    pub fn start(self: &Arc<Self>) {
+        if self.started.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        let mut handles = self.handles.lock().unwrap();
+        for worker in &self.workers {
+            info!("启动事件输出端: {}", worker.sink.name());
+            handles.push(tokio::spawn(worker.clone().run()));
+        }
+
+        let pipeline = self.clone();
+        handles.push(tokio::spawn(async move {
+            while let Some(event) = pipeline.queue.pop().await {
+                pipeline.dispatch(event).await;
+            }
+            for worker in &pipeline.workers {
+                worker.queue.close();
+            }
+        }));
+    }
+
+    /// 复制事件到各输出端的队列，队列已满的输出端直接写入死信
+    ///
+    /// 死信写完才分发下一个事件，停止时不会有未落盘的死信。
+    async fn dispatch(&self, event: TransactionEvent) {
+        for worker in &self.workers {
+            if !worker.queue.try_push(&event) {
+                worker.dead_letter(&event, "sink queue full").await;
+            }
+        }
+    }
+
+    /// 发布事件，队列满时按溢出策略等待或丢弃
+    pub async fn publish(&self, event: TransactionEvent) -> AppResult<()> {
+        match self.queue.push(event, self.overflow).await {
+            Ok(None) => {
+                self.accepted.fetch_add(1, Ordering::Relaxed);
+                Ok(())
+            }
+            Ok(Some(dropped)) => {
+                // 丢弃最早事件时新事件已入队
+                if self.overflow == OverflowPolicy::DropOldest {
+                    self.accepted.fetch_add(1, Ordering::Relaxed);
+                }
+                let dropped_total = self.dropped.fetch_add(1, Ordering::Relaxed) + 1;
+                if dropped_total.is_power_of_two() {
+                    warn!("事件队列已满，累计丢弃 {} 个事件，最近丢弃: {:?}", dropped_total, dropped);
+                }
+                Ok(())
+            }
+            Err(_) => Err(AppError::internal("事件管道已关闭")),
+        }
+    }
+
+    /// 关闭管道，已入队的事件投递完后各输出端停止
+    pub fn close(&self) {
+        self.queue.close();
+    }
+
+    /// 关闭管道并等待分发任务和各输出端处理完已入队的事件，超时返回`false`
+    pub async fn shutdown(&self, wait: Duration) -> bool {
+        self.close();
+        let handles = std::mem::take(&mut *self.handles.lock().unwrap());
+        timeout(wait, futures::future::join_all(handles)).await.is_ok()
+    }
+
+    /// 把输出端最早的`limit`条死信重新放入其投递队列，按正常流程投递、重试
+    ///
+    /// 死信先读取、入队成功后才从Redis删除，投递队列放不下的和无法解析的死信原样保留，可以再次重放。
+    pub async fn replay_dead_letters(&self, sink: &str, limit: usize) -> AppResult<DeadLetterReplay> {
+        if !self.started.load(Ordering::Acquire) {
+            return Err(AppError::business("Event pipeline is not running"));
+        }
+        let worker = self
+            .workers
+            .iter()
+            .find(|worker| worker.sink.name() == sink)
+            .ok_or_else(|| AppError::validation(format!("Unknown event sink: {}", sink)))?;
+
+        let entries = worker.redis.peek_dead_letters(sink, limit).await?;
+        let mut requeued = Vec::new();
+        let mut invalid = 0;
+        for entry in &entries {
+            let event = serde_json::from_str::<serde_json::Value>(entry)
+                .and_then(|mut entry| serde_json::from_value::<TransactionEvent>(entry["event"].take()));
+            let event = match event {
+                Ok(event) => event,
+                Err(e) => {
+                    error!("无法解析的死信: {} - {}", e, entry);
+                    invalid += 1;
+                    continue;
+                }
+            };
+            if !worker.queue.try_push(&event) {
+                break;
+            }
+            requeued.push(entry.clone());
+        }
+
+        // 事件已入队，删除失败只会导致下次重复重放
+        if let Err(e) = worker.redis.remove_dead_letters(sink, &requeued).await {
+            error!("删除已重放的死信失败: {} - {}", sink, e);
+        }
+        info!("事件输出端 {} 重放死信 {} 条", sink, requeued.len());
+        Ok(DeadLetterReplay {
+            sink: sink.to_string(),
+            requeued: requeued.len(),
+            invalid,
+            remaining: worker.redis.dead_letter_count(sink).await.ok(),
+        })
+    }
+
+    pub async fn status(&self) -> EventPipelineStatus {
+        let mut sinks = Vec::with_capacity(self.workers.len());
+        for worker in &self.workers {
+            sinks.push(worker.status().await);
+        }
+        EventPipelineStatus {
+            capacity: self.queue.capacity,
+            overflow: self.overflow,
+            queued: self.queue.len(),
+            accepted: self.accepted.load(Ordering::Relaxed),
+            dropped: self.dropped.load(Ordering::Relaxed),
+            sinks,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blockchain::Commitment;
+
+    fn event(slot: u64) -> TransactionEvent {
+        TransactionEvent::PoolUpdate {
+            pool: "pool".to_string(),
+            lamports: 0,
+            data: Vec::new(),
+            slot,
+            commitment: Commitment::Processed,
+            timestamp: chrono::Utc::now(),
+        }
+    }
+
+    fn slot_of(event: &TransactionEvent) -> u64 {
+        match event {
+            TransactionEvent::PoolUpdate { slot, .. } => *slot,
+            _ => unreachable!(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_overflow_drop_policies() {
+        let queue = EventQueue::new(2);
+        assert!(queue.push(event(1), OverflowPolicy::DropNewest).await.unwrap().is_none());
+        assert!(queue.push(event(2), OverflowPolicy::DropNewest).await.unwrap().is_none());
+
+        let dropped = queue.push(event(3), OverflowPolicy::DropNewest).await.unwrap().unwrap();
+        assert_eq!(slot_of(&dropped), 3);
+        let dropped = queue.push(event(4), OverflowPolicy::DropOldest).await.unwrap().unwrap();
+        assert_eq!(slot_of(&dropped), 1);
+
+        assert_eq!(slot_of(&queue.pop().await.unwrap()), 2);
+        assert_eq!(slot_of(&queue.pop().await.unwrap()), 4);
+        assert!(queue.try_push(&event(5)));
+    }
+
+    #[tokio::test]
+    async fn test_block_policy_waits_for_space() {
+        let queue = Arc::new(EventQueue::new(1));
+        queue.push(event(1), OverflowPolicy::Block).await.unwrap();
+        assert!(!queue.try_push(&event(2)));
+
+        let producer = queue.clone();
+        let pushed = tokio::spawn(async move { producer.push(event(2), OverflowPolicy::Block).await.is_ok() });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!pushed.is_finished());
+
+        assert_eq!(slot_of(&queue.pop().await.unwrap()), 1);
+        assert!(timeout(Duration::from_secs(1), pushed).await.unwrap().unwrap());
+        assert_eq!(slot_of(&queue.pop().await.unwrap()), 2);
+
+        queue.close();
+        assert!(queue.pop().await.is_none());
+    }
+}
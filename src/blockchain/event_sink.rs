@@ -0,0 +1,226 @@
+//! 交易事件输出端
+//!
+//! 事件管道将每个事件分发给所有启用的输出端：MySQL（交易、持仓和统计）、Redis Stream、Webhook推送和日志。
+
+use std::sync::Arc;
+use std::time::Duration;
+use async_trait::async_trait;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use crate::blockchain::{TradeRecorder, TransactionEvent};
+use crate::config::EventPipelineConfig;
+use crate::repositories::RedisRepository;
+use crate::utils::{AppError, AppResult};
+use tracing::{debug, info, warn};
+
+/// Webhook请求超时
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 事件输出端
+///
+/// 投递失败时由事件管道按退避重试，多次失败后写入死信，实现需要保证重复投递是安全的。
+#[async_trait]
+pub trait EventSink: Send + Sync {
+    /// 输出端名称，用于日志、统计和死信
+    fn name(&self) -> &str;
+
+    /// 投递单个事件
+    async fn deliver(&self, event: &TransactionEvent) -> AppResult<()>;
+}
+
+/// 按配置创建启用的输出端
+pub fn configured_sinks(
+    config: &EventPipelineConfig,
+    redis: RedisRepository,
+    trade_recorder: Arc<TradeRecorder>,
+) -> AppResult<Vec<Arc<dyn EventSink>>> {
+    let mut sinks: Vec<Arc<dyn EventSink>> = Vec::new();
+    if config.log_sink {
+        sinks.push(Arc::new(LogSink));
+    }
+    if config.mysql_sink {
+        sinks.push(Arc::new(MysqlSink::new(trade_recorder)));
+    }
+    if let Some(stream) = config.redis_stream.as_ref().filter(|stream| !stream.is_empty()) {
+        sinks.push(Arc::new(RedisStreamSink::new(redis, stream.clone(), config.redis_stream_max_len)));
+    }
+    if let Some(url) = config.webhook_url.as_ref().filter(|url| !url.is_empty()) {
+        sinks.push(Arc::new(WebhookSink::new(url.clone(), config.webhook_secret.clone())?));
+    }
+    Ok(sinks)
+}
+
+/// 日志输出端
+pub struct LogSink;
+
+#[async_trait]
+impl EventSink for LogSink {
+    fn name(&self) -> &str {
+        "log"
+    }
+
+    async fn deliver(&self, event: &TransactionEvent) -> AppResult<()> {
+        match event {
+            TransactionEvent::TokenTransfer { signature, from, to, mint, amount, .. } => {
+                info!("代币转账: {} -> {}, 代币: {}, 数量: {}, 交易: {}",
+                      from, to, mint, amount, signature);
+            }
+            TransactionEvent::TokenSwap { signature, user, token_in, token_out, amount_in, amount_out, .. } => {
+                info!("代币交换: 用户: {}, {} {} -> {} {}, 交易: {}",
+                      user, amount_in, token_in, amount_out, token_out, signature);
+            }
+            TransactionEvent::TokenMint { signature, mint, authority, supply, decimals, .. } => {
+                info!("新代币创建: {}, 发行方: {}, 供应量: {}, 精度: {}, 交易: {}",
+                      mint, authority, supply, decimals, signature);
+            }
+            TransactionEvent::PoolUpdate { pool, lamports, data, slot, .. } => {
+                debug!("池子账户变化: {}, 余额: {}, 数据长度: {}, 区块: {}",
+                       pool, lamports, data.len(), slot);
+            }
+            TransactionEvent::TradeReverted { signature, mint, signer, slot, .. } => {
+                warn!("交易已撤销: {}, 代币: {}, 用户: {}, 区块: {}", signature, mint, signer, slot);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// MySQL输出端：confirmed级别的交换写入交易表并更新持仓和统计，按签名幂等
+pub struct MysqlSink {
+    trade_recorder: Arc<TradeRecorder>,
+}
+
+impl MysqlSink {
+    pub fn new(trade_recorder: Arc<TradeRecorder>) -> Self {
+        Self { trade_recorder }
+    }
+}
+
+#[async_trait]
+impl EventSink for MysqlSink {
+    fn name(&self) -> &str {
+        "mysql"
+    }
+
+    async fn deliver(&self, event: &TransactionEvent) -> AppResult<()> {
+        self.trade_recorder.record(event).await?;
+        Ok(())
+    }
+}
+
+/// Redis Stream输出端：每个事件一条消息，`payload`字段为事件JSON
+pub struct RedisStreamSink {
+    redis: RedisRepository,
+    stream: String,
+    max_len: usize,
+}
+
+impl RedisStreamSink {
+    pub fn new(redis: RedisRepository, stream: String, max_len: usize) -> Self {
+        Self { redis, stream, max_len }
+    }
+}
+
+#[async_trait]
+impl EventSink for RedisStreamSink {
+    fn name(&self) -> &str {
+        "redis_stream"
+    }
+
+    async fn deliver(&self, event: &TransactionEvent) -> AppResult<()> {
+        let payload = serde_json::to_string(event)?;
+        self.redis.xadd(&self.stream, &payload, self.max_len).await?;
+        Ok(())
+    }
+}
+
+/// Webhook输出端：以JSON POST事件，非2xx响应视为失败
+pub struct WebhookSink {
+    http_client: reqwest::Client,
+    url: String,
+    secret: Option<String>,
+}
+
+impl WebhookSink {
+    pub fn new(url: String, secret: Option<String>) -> AppResult<Self> {
+        let http_client = reqwest::Client::builder()
+            .timeout(WEBHOOK_TIMEOUT)
+            .build()
+            .map_err(|e| AppError::internal(format!("创建Webhook客户端失败: {}", e)))?;
+        Ok(Self {
+            http_client,
+            url,
+            secret: secret.filter(|secret| !secret.is_empty()),
+        })
+    }
+
+    /// 请求体的HMAC-SHA256签名（十六进制）
+    fn signature(secret: &str, body: &[u8]) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+        mac.update(body);
+        hex::encode(mac.finalize().into_bytes())
+    }
+}
+
+#[async_trait]
+impl EventSink for WebhookSink {
+    fn name(&self) -> &str {
+        "webhook"
+    }
+
+    async fn deliver(&self, event: &TransactionEvent) -> AppResult<()> {
+        let body = serde_json::to_vec(event)?;
+        let mut request = self
+            .http_client
+            .post(&self.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json");
+        if let Some(secret) = &self.secret {
+            request = request.header("X-Signature", Self::signature(secret, &body));
+        }
+
+        let response = request
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| AppError::internal(format!("Webhook请求失败: {}", e)))?;
+        if !response.status().is_success() {
+            return Err(AppError::internal(format!("Webhook返回状态码: {}", response.status())));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blockchain::Commitment;
+
+    #[test]
+    fn test_webhook_signature() {
+        // RFC 4231 测试用例2
+        assert_eq!(
+            WebhookSink::signature("Jefe", b"what do ya want for nothing?"),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn test_event_json_is_tagged() {
+        let event = TransactionEvent::PoolUpdate {
+            pool: "pool".to_string(),
+            lamports: 1,
+            data: vec![1, 2, 3],
+            slot: 42,
+            commitment: Commitment::Processed,
+            timestamp: chrono::Utc::now(),
+        };
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["type"], "pool_update");
+        assert_eq!(json["commitment"], "processed");
+        assert_eq!(json["data"], "AQID");
+
+        // 死信重放时从JSON还原事件
+        let decoded: TransactionEvent = serde_json::from_value(json).unwrap();
+        assert!(matches!(decoded, TransactionEvent::PoolUpdate { data, slot: 42, .. } if data == [1, 2, 3]));
+    }
+}
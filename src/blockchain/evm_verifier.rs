@@ -0,0 +1,734 @@
+//! EVM钱包签名验证服务
+//!
+//! 基于secp256k1公钥恢复验证以太坊系钱包（MetaMask等）的签名，支持：
+//! - EIP-191 `personal_sign`
+//! - EIP-712 结构化数据签名（`eth_signTypedData_v4`）
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Arc;
+use chrono::{DateTime, SecondsFormat, Utc};
+use k256::ecdsa::{RecoveryId, Signature, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha3::{Digest, Keccak256};
+use crate::blockchain::wallet_verifier::{
+    ChallengeError, LoginChallenge, SignatureVerificationResult, SiwsError, CHALLENGE_TTL_SECONDS,
+};
+use crate::config::{Config, SiwsConfig};
+use crate::repositories::RedisRepository;
+use crate::utils::{AppError, AppResult};
+use tracing::{debug, info, warn};
+
+const EVM_HEADER_SUFFIX: &str = " wants you to sign in with your Ethereum account:";
+const LOGIN_TYPE: &str = "Login";
+const DOMAIN_TYPE: &str = "EIP712Domain";
+
+/// 支持登录的EVM链
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EvmChain {
+    Ethereum,
+    Base,
+}
+
+impl EvmChain {
+    /// 根据登录请求中的network解析链
+    pub fn from_network(network: &str) -> Option<Self> {
+        match network.to_lowercase().as_str() {
+            "eth" | "ethereum" => Some(Self::Ethereum),
+            "base" => Some(Self::Base),
+            _ => None,
+        }
+    }
+
+    pub fn chain_id(&self) -> u64 {
+        match self {
+            Self::Ethereum => 1,
+            Self::Base => 8453,
+        }
+    }
+}
+
+/// EIP-712 类型字段
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Eip712Field {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub field_type: String,
+}
+
+impl Eip712Field {
+    fn new(name: &str, field_type: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            field_type: field_type.to_string(),
+        }
+    }
+}
+
+/// EIP-712 结构化数据，与`eth_signTypedData_v4`的参数格式一致
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TypedData {
+    pub types: BTreeMap<String, Vec<Eip712Field>>,
+    pub primary_type: String,
+    pub domain: Value,
+    pub message: Value,
+}
+
+impl TypedData {
+    /// 计算待签名摘要：`keccak256(0x1901 ‖ domainSeparator ‖ hashStruct(message))`
+    pub fn signing_hash(&self) -> AppResult<[u8; 32]> {
+        let mut data = vec![0x19, 0x01];
+        data.extend_from_slice(&self.hash_struct(DOMAIN_TYPE, &self.domain)?);
+        data.extend_from_slice(&self.hash_struct(&self.primary_type, &self.message)?);
+        Ok(keccak256(&data))
+    }
+
+    fn fields(&self, type_name: &str) -> AppResult<&Vec<Eip712Field>> {
+        self.types
+            .get(type_name)
+            .ok_or_else(|| AppError::wallet_verification_error(format!("EIP-712类型未定义: {}", type_name)))
+    }
+
+    fn collect_dependencies<'a>(&'a self, type_name: &'a str, found: &mut BTreeSet<&'a str>) -> AppResult<()> {
+        if !found.insert(type_name) {
+            return Ok(());
+        }
+        for field in self.fields(type_name)? {
+            let base = base_type(&field.field_type);
+            if self.types.contains_key(base) {
+                self.collect_dependencies(base, found)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// 编码类型签名，依赖类型按名称排序附加在主类型之后
+    fn encode_type(&self, type_name: &str) -> AppResult<String> {
+        let mut dependencies = BTreeSet::new();
+        self.collect_dependencies(type_name, &mut dependencies)?;
+        dependencies.remove(type_name);
+
+        let mut encoded = String::new();
+        for name in std::iter::once(type_name).chain(dependencies) {
+            let fields = self
+                .fields(name)?
+                .iter()
+                .map(|field| format!("{} {}", field.field_type, field.name))
+                .collect::<Vec<_>>()
+                .join(",");
+            encoded.push_str(&format!("{}({})", name, fields));
+        }
+        Ok(encoded)
+    }
+
+    fn hash_struct(&self, type_name: &str, value: &Value) -> AppResult<[u8; 32]> {
+        let mut data = keccak256(self.encode_type(type_name)?.as_bytes()).to_vec();
+        for field in self.fields(type_name)? {
+            let field_value = value.get(&field.name).unwrap_or(&Value::Null);
+            data.extend_from_slice(&self.encode_value(&field.field_type, field_value)?);
+        }
+        Ok(keccak256(&data))
+    }
+
+    fn encode_value(&self, field_type: &str, value: &Value) -> AppResult<[u8; 32]> {
+        let invalid = || AppError::wallet_verification_error(format!("EIP-712字段值无效: {} = {}", field_type, value));
+
+        if let Some(item_type) = field_type.strip_suffix(']').and_then(|t| t.rsplit_once('[')).map(|(item, _)| item) {
+            let items = value.as_array().ok_or_else(invalid)?;
+            let mut data = Vec::with_capacity(items.len() * 32);
+            for item in items {
+                data.extend_from_slice(&self.encode_value(item_type, item)?);
+            }
+            return Ok(keccak256(&data));
+        }
+        if self.types.contains_key(field_type) {
+            return self.hash_struct(field_type, value);
+        }
+
+        let mut word = [0u8; 32];
+        match field_type {
+            "string" => Ok(keccak256(value.as_str().ok_or_else(invalid)?.as_bytes())),
+            "bytes" => {
+                let bytes = decode_hex(value.as_str().ok_or_else(invalid)?).map_err(|_| invalid())?;
+                Ok(keccak256(&bytes))
+            }
+            "bool" => {
+                word[31] = value.as_bool().ok_or_else(invalid)? as u8;
+                Ok(word)
+            }
+            "address" => {
+                let address = parse_address(value.as_str().ok_or_else(invalid)?).map_err(|_| invalid())?;
+                word[12..].copy_from_slice(&address);
+                Ok(word)
+            }
+            t if t.starts_with("bytes") => {
+                let size: usize = t["bytes".len()..].parse().map_err(|_| invalid())?;
+                let bytes = decode_hex(value.as_str().ok_or_else(invalid)?).map_err(|_| invalid())?;
+                if size == 0 || size > 32 || bytes.len() > size {
+                    return Err(invalid());
+                }
+                word[..bytes.len()].copy_from_slice(&bytes);
+                Ok(word)
+            }
+            t if t.starts_with("uint") || t.starts_with("int") => {
+                encode_integer(value, t.starts_with("int")).ok_or_else(invalid)
+            }
+            _ => Err(invalid()),
+        }
+    }
+}
+
+/// EVM登录挑战，前端可任选`personal_sign`文本或EIP-712结构化数据签名
+#[derive(Debug, Clone, Serialize)]
+pub struct EvmLoginChallenge {
+    pub message: String,
+    pub typed_data: TypedData,
+}
+
+/// 按固定的登录结构构造EIP-712数据，签发挑战和验证签名共用同一份类型定义
+fn login_typed_data(
+    siws: &SiwsConfig,
+    address: &str,
+    chain_id: u64,
+    nonce: &str,
+    issued_at: &str,
+    expiration_time: &str,
+) -> TypedData {
+    let mut types = BTreeMap::new();
+    types.insert(DOMAIN_TYPE.to_string(), vec![
+        Eip712Field::new("name", "string"),
+        Eip712Field::new("version", "string"),
+        Eip712Field::new("chainId", "uint256"),
+    ]);
+    types.insert(LOGIN_TYPE.to_string(), vec![
+        Eip712Field::new("address", "address"),
+        Eip712Field::new("statement", "string"),
+        Eip712Field::new("uri", "string"),
+        Eip712Field::new("nonce", "string"),
+        Eip712Field::new("issuedAt", "string"),
+        Eip712Field::new("expirationTime", "string"),
+    ]);
+    TypedData {
+        types,
+        primary_type: LOGIN_TYPE.to_string(),
+        domain: serde_json::json!({
+            "name": siws.domain,
+            "version": "1",
+            "chainId": chain_id,
+        }),
+        message: serde_json::json!({
+            "address": address,
+            "statement": siws.statement.clone().unwrap_or_default(),
+            "uri": siws.uri,
+            "nonce": nonce,
+            "issuedAt": issued_at,
+            "expirationTime": expiration_time,
+        }),
+    }
+}
+
+/// 从挑战中解析出的登录字段
+#[derive(Debug, Clone, PartialEq)]
+struct EvmLoginFields {
+    domain: String,
+    address: String,
+    uri: String,
+    chain_id: u64,
+    nonce: String,
+    issued_at: DateTime<Utc>,
+    expiration_time: Option<DateTime<Utc>>,
+}
+
+impl EvmLoginFields {
+    /// 解析`personal_sign`文本挑战
+    fn from_text(text: &str) -> Result<Self, SiwsError> {
+        let missing = |field| SiwsError::MissingField(field);
+        let mut lines = text.lines();
+        let domain = lines
+            .next()
+            .and_then(|line| line.strip_suffix(EVM_HEADER_SUFFIX))
+            .ok_or_else(|| SiwsError::Parse("missing header".to_string()))?;
+        let address = lines.next().ok_or_else(|| SiwsError::Parse("missing address".to_string()))?;
+
+        let (mut uri, mut chain_id, mut nonce, mut issued_at, mut expiration_time) = (None, None, None, None, None);
+        for line in lines {
+            match line.split_once(": ") {
+                Some(("URI", value)) => uri = Some(value.to_string()),
+                Some(("Chain ID", value)) => {
+                    chain_id = Some(value.parse().map_err(|_| SiwsError::Parse(format!("invalid chain id: {}", value)))?);
+                }
+                Some(("Nonce", value)) => nonce = Some(value.to_string()),
+                Some(("Issued At", value)) => issued_at = Some(parse_time(value)?),
+                Some(("Expiration Time", value)) => expiration_time = Some(parse_time(value)?),
+                _ => {}
+            }
+        }
+
+        Ok(Self {
+            domain: domain.to_string(),
+            address: address.to_string(),
+            uri: uri.ok_or_else(|| missing("uri"))?,
+            chain_id: chain_id.ok_or_else(|| missing("chainId"))?,
+            nonce: nonce.ok_or_else(|| missing("nonce"))?,
+            issued_at: issued_at.ok_or_else(|| missing("issuedAt"))?,
+            expiration_time,
+        })
+    }
+
+    /// 解析EIP-712登录挑战
+    fn from_typed_data(typed_data: &TypedData) -> Result<Self, SiwsError> {
+        if typed_data.primary_type != LOGIN_TYPE {
+            return Err(SiwsError::Parse(format!("unexpected primary type: {}", typed_data.primary_type)));
+        }
+        let text = |value: &Value, field: &'static str| {
+            value.get(field).and_then(Value::as_str).map(str::to_string).ok_or(SiwsError::MissingField(field))
+        };
+        let domain = &typed_data.domain;
+        let message = &typed_data.message;
+        let chain_id = match domain.get("chainId") {
+            Some(Value::Number(n)) => n.as_u64(),
+            Some(Value::String(s)) => s.parse().ok(),
+            _ => None,
+        }
+        .ok_or(SiwsError::MissingField("chainId"))?;
+        let expiration_time = match message.get("expirationTime").and_then(Value::as_str) {
+            Some(value) => Some(parse_time(value)?),
+            None => None,
+        };
+
+        Ok(Self {
+            domain: text(domain, "name")?,
+            address: text(message, "address")?,
+            uri: text(message, "uri")?,
+            chain_id,
+            nonce: text(message, "nonce")?,
+            issued_at: parse_time(&text(message, "issuedAt")?)?,
+            expiration_time,
+        })
+    }
+}
+
+/// EVM钱包验证器
+pub struct EvmVerifier {
+    config: Arc<Config>,
+    redis: RedisRepository,
+}
+
+impl EvmVerifier {
+    /// 创建新的EVM钱包验证器
+    pub fn new(config: Arc<Config>, redis: RedisRepository) -> AppResult<Self> {
+        Ok(Self { config, redis })
+    }
+
+    /// 将地址规范化为EIP-55校验和格式
+    pub fn checksum_address(address: &str) -> AppResult<String> {
+        let bytes = parse_address(address)
+            .map_err(|_| AppError::validation(format!("Invalid EVM address: {}", address)))?;
+        Ok(to_checksum_address(&bytes))
+    }
+
+    /// 从EIP-191 `personal_sign`签名中恢复签名者地址
+    pub fn recover_personal_sign(message: &[u8], signature: &str) -> AppResult<String> {
+        let mut data = format!("\x19Ethereum Signed Message:\n{}", message.len()).into_bytes();
+        data.extend_from_slice(message);
+        recover_address(&keccak256(&data), signature)
+    }
+
+    /// 从EIP-712签名中恢复签名者地址
+    pub fn recover_typed_data(typed_data: &TypedData, signature: &str) -> AppResult<String> {
+        recover_address(&typed_data.signing_hash()?, signature)
+    }
+
+    /// 用客户端提交的挑战取值按服务端的固定结构重建EIP-712数据
+    fn rebuild_typed_data(
+        siws: &SiwsConfig,
+        address: &str,
+        chain: EvmChain,
+        typed_data: &TypedData,
+    ) -> AppResult<TypedData> {
+        let text = |field: &'static str| {
+            typed_data
+                .message
+                .get(field)
+                .and_then(Value::as_str)
+                .ok_or_else(|| AppError::from(ChallengeError::from(SiwsError::MissingField(field))))
+        };
+        Ok(login_typed_data(
+            siws,
+            address,
+            chain.chain_id(),
+            text("nonce")?,
+            text("issuedAt")?,
+            text("expirationTime")?,
+        ))
+    }
+
+    /// 生成EVM登录挑战，并在Redis中记录随机数
+    pub async fn generate_login_challenge(&self, address: &str, chain: EvmChain) -> AppResult<EvmLoginChallenge> {
+        let address = Self::checksum_address(address)?;
+        let siws = &self.config.siws;
+        let statement = siws.statement.clone().unwrap_or_default();
+        let nonce = uuid::Uuid::new_v4().simple().to_string();
+        let now = Utc::now();
+        let issued_at = now.to_rfc3339_opts(SecondsFormat::Millis, true);
+        let expiration_time = (now + chrono::Duration::seconds(CHALLENGE_TTL_SECONDS as i64))
+            .to_rfc3339_opts(SecondsFormat::Millis, true);
+
+        let message = format!(
+            "{}{}\n{}\n\n{}\n\nURI: {}\nVersion: 1\nChain ID: {}\nNonce: {}\nIssued At: {}\nExpiration Time: {}",
+            siws.domain, EVM_HEADER_SUFFIX, address, statement, siws.uri,
+            chain.chain_id(), nonce, issued_at, expiration_time,
+        );
+
+        let typed_data = login_typed_data(siws, &address, chain.chain_id(), &nonce, &issued_at, &expiration_time);
+
+        let record = LoginChallenge {
+            nonce,
+            message: message.clone(),
+            issued_at: now.timestamp(),
+        };
+        self.redis
+            .store_login_challenge(&address.to_lowercase(), &record.nonce, &record, CHALLENGE_TTL_SECONDS)
+            .await?;
+
+        debug!("生成EVM登录挑战: {}", address);
+        Ok(EvmLoginChallenge { message, typed_data })
+    }
+
+    /// 验证EVM登录挑战
+    ///
+    /// `challenge`为JSON时按EIP-712签名验证：服务端按固定的登录结构重建结构化数据再恢复签名者，
+    /// 客户端提交的类型定义不参与验签；否则按`personal_sign`文本验证。两种方式的字段都须与服务端签发的挑战一致。
+    pub async fn verify_login_challenge(
+        &self,
+        address: &str,
+        chain: EvmChain,
+        challenge: &str,
+        signature: &str,
+    ) -> AppResult<SignatureVerificationResult> {
+        let address = Self::checksum_address(address)?;
+
+        let typed_data = if challenge.trim_start().starts_with('{') {
+            Some(serde_json::from_str::<TypedData>(challenge).map_err(|_| ChallengeError::Malformed)?)
+        } else {
+            None
+        };
+        let fields = match &typed_data {
+            Some(typed_data) => EvmLoginFields::from_typed_data(typed_data),
+            None => EvmLoginFields::from_text(challenge),
+        }
+        .map_err(ChallengeError::from)?;
+
+        if self.redis.is_challenge_used(&fields.nonce).await? {
+            return Err(ChallengeError::Reused.into());
+        }
+
+        // 检查域名、链和时效性
+        let now = Utc::now();
+        if fields.domain != self.config.siws.domain {
+            return Err(ChallengeError::from(SiwsError::DomainMismatch(fields.domain)).into());
+        }
+        if fields.uri != self.config.siws.uri {
+            return Err(ChallengeError::from(SiwsError::UriMismatch(fields.uri)).into());
+        }
+        if !fields.address.eq_ignore_ascii_case(&address) {
+            return Err(ChallengeError::from(SiwsError::AddressMismatch(fields.address)).into());
+        }
+        if fields.chain_id != chain.chain_id() {
+            return Err(ChallengeError::from(SiwsError::ChainMismatch(fields.chain_id.to_string())).into());
+        }
+        let expired = matches!(fields.expiration_time, Some(expiration) if now >= expiration);
+        if expired || (now - fields.issued_at).num_seconds() > CHALLENGE_TTL_SECONDS as i64 {
+            return Err(ChallengeError::Expired.into());
+        }
+
+        // 恢复签名者
+        let recovered = match &typed_data {
+            Some(typed_data) => Self::rebuild_typed_data(&self.config.siws, &address, chain, typed_data)
+                .and_then(|rebuilt| Self::recover_typed_data(&rebuilt, signature)),
+            None => Self::recover_personal_sign(challenge.as_bytes(), signature),
+        };
+        let mut result = SignatureVerificationResult {
+            is_valid: false,
+            wallet_address: address.clone(),
+            message: challenge.to_string(),
+            error: None,
+        };
+        match recovered {
+            Ok(signer) if signer == address => result.is_valid = true,
+            Ok(signer) => {
+                warn!("EVM签名者不匹配: 期望 {}, 实际 {}", address, signer);
+                result.error = Some("签名者与钱包地址不匹配".to_string());
+                return Ok(result);
+            }
+            Err(e) => {
+                warn!("EVM签名验证失败: {}", e);
+                result.error = Some(e.to_string());
+                return Ok(result);
+            }
+        }
+
+        // 原子地消费服务端记录
+        let record: Option<LoginChallenge> = self.redis.take_login_challenge(&address.to_lowercase(), &fields.nonce).await?;
+        let record = match record {
+            Some(record) => record,
+            None if self.redis.is_challenge_used(&fields.nonce).await? => {
+                return Err(ChallengeError::Reused.into());
+            }
+            None => return Err(ChallengeError::Unknown.into()),
+        };
+        let issued = EvmLoginFields::from_text(&record.message).map_err(|_| ChallengeError::Unknown)?;
+        if record.nonce != fields.nonce || issued != fields {
+            return Err(ChallengeError::Unknown.into());
+        }
+
+        self.redis.mark_challenge_used(&fields.nonce, CHALLENGE_TTL_SECONDS).await?;
+
+        info!("EVM钱包签名验证成功: {}", address);
+        Ok(result)
+    }
+}
+
+fn keccak256(data: &[u8]) -> [u8; 32] {
+    Keccak256::digest(data).into()
+}
+
+fn decode_hex(value: &str) -> Result<Vec<u8>, hex::FromHexError> {
+    hex::decode(value.strip_prefix("0x").unwrap_or(value))
+}
+
+fn parse_address(value: &str) -> Result<[u8; 20], hex::FromHexError> {
+    let bytes = decode_hex(value)?;
+    bytes.try_into().map_err(|_| hex::FromHexError::InvalidStringLength)
+}
+
+fn parse_time(value: &str) -> Result<DateTime<Utc>, SiwsError> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|time| time.with_timezone(&Utc))
+        .map_err(|e| SiwsError::Parse(format!("invalid time {}: {}", value, e)))
+}
+
+/// EIP-55 校验和地址
+fn to_checksum_address(address: &[u8; 20]) -> String {
+    let lower = hex::encode(address);
+    let hash = keccak256(lower.as_bytes());
+    let checksummed: String = lower
+        .chars()
+        .enumerate()
+        .map(|(i, c)| {
+            let nibble = (hash[i / 2] >> (if i % 2 == 0 { 4 } else { 0 })) & 0x0f;
+            if nibble >= 8 { c.to_ascii_uppercase() } else { c }
+        })
+        .collect();
+    format!("0x{}", checksummed)
+}
+
+/// 按EIP-712规则截取基础类型名，如`Person[]`取`Person`
+fn base_type(field_type: &str) -> &str {
+    field_type.split('[').next().unwrap_or(field_type)
+}
+
+/// 将十进制/十六进制整数编码为256位大端补码
+fn encode_integer(value: &Value, signed: bool) -> Option<[u8; 32]> {
+    let text = match value {
+        Value::Number(n) => n.to_string(),
+        Value::String(s) => s.clone(),
+        _ => return None,
+    };
+    let (negative, digits) = match text.strip_prefix('-') {
+        Some(digits) if signed => (true, digits),
+        Some(_) => return None,
+        None => (false, text.as_str()),
+    };
+    if digits.is_empty() {
+        return None;
+    }
+
+    let mut word = [0u8; 32];
+    if let Some(hex_digits) = digits.strip_prefix("0x") {
+        let padded = if hex_digits.len() % 2 == 1 { format!("0{}", hex_digits) } else { hex_digits.to_string() };
+        let bytes = hex::decode(padded).ok()?;
+        if bytes.len() > 32 {
+            return None;
+        }
+        word[32 - bytes.len()..].copy_from_slice(&bytes);
+    } else {
+        for c in digits.chars() {
+            let mut carry = c.to_digit(10)?;
+            for byte in word.iter_mut().rev() {
+                let v = *byte as u32 * 10 + carry;
+                *byte = v as u8;
+                carry = v >> 8;
+            }
+            if carry != 0 {
+                return None;
+            }
+        }
+    }
+
+    if negative {
+        let mut carry = true;
+        for byte in word.iter_mut().rev() {
+            let (v, overflow) = (!*byte).overflowing_add(carry as u8);
+            *byte = v;
+            carry = overflow;
+        }
+    }
+    Some(word)
+}
+
+/// 从65字节`r ‖ s ‖ v`签名中恢复地址
+fn recover_address(prehash: &[u8; 32], signature: &str) -> AppResult<String> {
+    let invalid = |message: String| AppError::wallet_verification_error(message);
+    let bytes = decode_hex(signature).map_err(|e| invalid(format!("无效的签名格式: {}", e)))?;
+    if bytes.len() != 65 {
+        return Err(invalid("签名长度不正确".to_string()));
+    }
+
+    let v = match bytes[64] {
+        0 | 27 => 0,
+        1 | 28 => 1,
+        other => return Err(invalid(format!("无效的签名恢复位: {}", other))),
+    };
+    let signature = Signature::from_slice(&bytes[..64]).map_err(|e| invalid(format!("创建签名对象失败: {}", e)))?;
+    let recovery_id = RecoveryId::from_byte(v).ok_or_else(|| invalid("无效的签名恢复位".to_string()))?;
+    let key = VerifyingKey::recover_from_prehash(prehash, &signature, recovery_id)
+        .map_err(|e| invalid(format!("恢复公钥失败: {}", e)))?;
+
+    let point = key.to_encoded_point(false);
+    let hash = keccak256(&point.as_bytes()[1..]);
+    let mut address = [0u8; 20];
+    address.copy_from_slice(&hash[12..]);
+    Ok(to_checksum_address(&address))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use k256::ecdsa::SigningKey;
+
+    const PRIVATE_KEY: &str = "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318";
+    const ADDRESS: &str = "0x2c7536E3605D9C16a7a3D7b1898e529396a65c23";
+
+    fn sign(prehash: &[u8; 32]) -> String {
+        let key = SigningKey::from_slice(&hex::decode(PRIVATE_KEY).unwrap()).unwrap();
+        let (signature, recovery_id) = key.sign_prehash_recoverable(prehash).unwrap();
+        let mut bytes = signature.to_bytes().to_vec();
+        bytes.push(recovery_id.to_byte() + 27);
+        format!("0x{}", hex::encode(bytes))
+    }
+
+    #[test]
+    fn test_checksum_address() {
+        assert_eq!(EvmVerifier::checksum_address(&ADDRESS.to_lowercase()).unwrap(), ADDRESS);
+        assert!(EvmVerifier::checksum_address("0x1234").is_err());
+    }
+
+    #[test]
+    fn test_personal_sign_recovery() {
+        // web3.js `eth.accounts.sign("Some data", key)` 的已知结果
+        let signature = "0xb91467e570a6466aa9e9876cbcd013baba02900b8979d43fe208a4a4f339f5fd6007e74cd82e037b800186422fc2da167c747ef045e5d18a5f5d4300f8e1a0291c";
+        assert_eq!(EvmVerifier::recover_personal_sign(b"Some data", signature).unwrap(), ADDRESS);
+        assert_ne!(EvmVerifier::recover_personal_sign(b"Other data", signature).unwrap(), ADDRESS);
+    }
+
+    #[test]
+    fn test_eip712_mail_example() {
+        // EIP-712 规范中的 Mail 示例
+        let typed_data: TypedData = serde_json::from_value(serde_json::json!({
+            "types": {
+                "EIP712Domain": [
+                    {"name": "name", "type": "string"},
+                    {"name": "version", "type": "string"},
+                    {"name": "chainId", "type": "uint256"},
+                    {"name": "verifyingContract", "type": "address"}
+                ],
+                "Person": [
+                    {"name": "name", "type": "string"},
+                    {"name": "wallet", "type": "address"}
+                ],
+                "Mail": [
+                    {"name": "from", "type": "Person"},
+                    {"name": "to", "type": "Person"},
+                    {"name": "contents", "type": "string"}
+                ]
+            },
+            "primaryType": "Mail",
+            "domain": {
+                "name": "Ether Mail",
+                "version": "1",
+                "chainId": 1,
+                "verifyingContract": "0xCcCCccccCCCCcCCCCCCcCcCccCcCCCcCcccccccC"
+            },
+            "message": {
+                "from": {"name": "Cow", "wallet": "0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826"},
+                "to": {"name": "Bob", "wallet": "0xbBbBBBBbbBBBbbbBbbBbbbbBBbBbbbbBbBbbBBbB"},
+                "contents": "Hello, Bob!"
+            }
+        })).unwrap();
+
+        assert_eq!(typed_data.encode_type("Mail").unwrap(), "Mail(Person from,Person to,string contents)Person(string name,address wallet)");
+        let hash = typed_data.signing_hash().unwrap();
+        assert_eq!(hex::encode(hash), "be609aee343fb3c4b28e1df9e632fca64fcfaede20f02e86244efddf30957bd2");
+        assert_eq!(EvmVerifier::recover_typed_data(&typed_data, &sign(&hash)).unwrap(), ADDRESS);
+    }
+
+    #[test]
+    fn test_parse_text_challenge() {
+        let text = format!(
+            "watermelo.io{}\n{}\n\nSign in to watermelo.io\n\nURI: https://watermelo.io\nVersion: 1\nChain ID: 8453\nNonce: abc123\nIssued At: 2024-01-01T00:00:00.000Z",
+            EVM_HEADER_SUFFIX, ADDRESS
+        );
+        let fields = EvmLoginFields::from_text(&text).unwrap();
+        assert_eq!(fields.domain, "watermelo.io");
+        assert_eq!(fields.address, ADDRESS);
+        assert_eq!(fields.chain_id, EvmChain::Base.chain_id());
+        assert_eq!(fields.nonce, "abc123");
+        assert!(EvmLoginFields::from_text("hello").is_err());
+    }
+
+    #[test]
+    fn test_challenges_for_same_wallet_are_kept_apart() {
+        let challenge = |nonce: &str| format!(
+            "watermelo.io{}\n{}\n\nURI: https://watermelo.io\nVersion: 1\nChain ID: 1\nNonce: {}\nIssued At: 2024-01-01T00:00:00.000Z",
+            EVM_HEADER_SUFFIX, ADDRESS, nonce
+        );
+        let first = EvmLoginFields::from_text(&challenge("first")).unwrap();
+        let second = EvmLoginFields::from_text(&challenge("second")).unwrap();
+
+        // 第二次申请挑战不能覆盖第一次签发的记录
+        let wallet = ADDRESS.to_lowercase();
+        assert_ne!(
+            RedisRepository::login_challenge_key(&wallet, &first.nonce),
+            RedisRepository::login_challenge_key(&wallet, &second.nonce)
+        );
+    }
+
+    #[test]
+    fn test_typed_data_is_verified_against_fixed_schema() {
+        let siws = SiwsConfig::default();
+        let issued = login_typed_data(&siws, ADDRESS, 1, "abc123", "2024-01-01T00:00:00.000Z", "2024-01-01T00:05:00.000Z");
+        let signature = sign(&issued.signing_hash().unwrap());
+        let rebuilt = EvmVerifier::rebuild_typed_data(&siws, ADDRESS, EvmChain::Ethereum, &issued).unwrap();
+        assert_eq!(EvmVerifier::recover_typed_data(&rebuilt, &signature).unwrap(), ADDRESS);
+
+        // 客户端自定义的类型去掉了nonce或域名，签名不再覆盖这些字段，必须拒绝
+        for (type_name, field) in [(LOGIN_TYPE, "nonce"), (DOMAIN_TYPE, "name")] {
+            let mut forged = issued.clone();
+            forged.types.get_mut(type_name).unwrap().retain(|f| f.name != field);
+            let signature = sign(&forged.signing_hash().unwrap());
+            let rebuilt = EvmVerifier::rebuild_typed_data(&siws, ADDRESS, EvmChain::Ethereum, &forged).unwrap();
+            assert_ne!(EvmVerifier::recover_typed_data(&rebuilt, &signature).unwrap(), ADDRESS);
+        }
+    }
+
+    #[test]
+    fn test_encode_integer() {
+        let one = encode_integer(&serde_json::json!(1), false).unwrap();
+        assert_eq!(one[31], 1);
+        assert_eq!(encode_integer(&serde_json::json!("0x0100"), false).unwrap()[30], 1);
+        assert_eq!(encode_integer(&serde_json::json!("-1"), true).unwrap(), [0xff; 32]);
+        assert!(encode_integer(&serde_json::json!("-1"), false).is_none());
+    }
+}
@@ -1,46 +1,86 @@
 //! 区块链集成模块
 //! 
 //! 提供Solana区块链的完整集成功能，包括：
-//! - RPC客户端
-//! - 交易监听
+//! - RPC客户端（多节点故障切换）
+//! - 交易监听（WebSocket订阅）
+//! - 交易记录（按确认级别写入和撤销）
+//! - 事件管道（有界队列，多输出端并发投递）
 //! - 代币价格获取
-//! - 钱包签名验证
+//! - 钱包签名验证（Solana / EVM）
 
+pub mod rpc_limiter;
+pub mod rpc_pool;
 pub mod solana_client;
 pub mod price_service;
 pub mod transaction_listener;
+pub mod trade_recorder;
+pub mod event_pipeline;
+pub mod event_sink;
 pub mod wallet_verifier;
+pub mod evm_verifier;
+pub mod ws_client;
 
+pub use rpc_limiter::*;
+pub use rpc_pool::*;
 pub use solana_client::*;
 pub use price_service::*;
 pub use transaction_listener::*;
+pub use trade_recorder::*;
+pub use event_pipeline::*;
+pub use event_sink::*;
 pub use wallet_verifier::*;
+pub use evm_verifier::*;
+pub use ws_client::*;
 
 use std::sync::Arc;
+use std::time::Duration;
 use crate::config::Config;
+use crate::repositories::{RedisRepository, SolanaRepository};
 use crate::utils::AppResult;
 
+/// 停止时等待事件管道处理完剩余事件的最长时间
+const PIPELINE_DRAIN_TIMEOUT: Duration = Duration::from_secs(30);
+
 /// 区块链服务集合
 #[derive(Clone)]
 pub struct BlockchainServices {
     pub solana_client: Arc<SolanaClientService>,
+    pub ws_client: Arc<SolanaWsClient>,
     pub price_service: Arc<PriceService>,
     pub transaction_listener: Arc<TransactionListener>,
+    pub event_pipeline: Arc<EventPipeline>,
     pub wallet_verifier: Arc<WalletVerifier>,
 }
 
 impl BlockchainServices {
     /// 创建新的区块链服务实例
-    pub async fn new(config: Arc<Config>) -> AppResult<Self> {
+    pub async fn new(config: Arc<Config>, redis: RedisRepository, solana_repository: SolanaRepository) -> AppResult<Self> {
         let solana_client = Arc::new(SolanaClientService::new(config.clone()).await?);
         let price_service = Arc::new(PriceService::new(config.clone()).await?);
-        let transaction_listener = Arc::new(TransactionListener::new(config.clone()).await?);
-        let wallet_verifier = Arc::new(WalletVerifier::new(config.clone())?);
+        let ws_client = Arc::new(SolanaWsClient::from_config(&config));
+        let trade_recorder = Arc::new(TradeRecorder::new(solana_client.clone(), solana_repository));
+        let pipeline_config = &config.solana.monitoring.event_pipeline;
+        let sinks = configured_sinks(pipeline_config, redis.clone(), trade_recorder.clone())?;
+        let event_pipeline = Arc::new(EventPipeline::new(pipeline_config, sinks, redis.clone()));
+        let transaction_listener = Arc::new(
+            TransactionListener::new(
+                config.clone(),
+                solana_client.clone(),
+                ws_client.clone(),
+                redis.clone(),
+                trade_recorder,
+                event_pipeline.clone(),
+            )
+            .await?,
+        );
+        let wallet_verifier = Arc::new(WalletVerifier::new(config.clone(), redis)?);
 
         Ok(Self {
             solana_client,
+            ws_client,
             price_service,
             transaction_listener,
+            event_pipeline,
             wallet_verifier,
         })
     }
@@ -50,16 +90,20 @@ impl BlockchainServices {
         // 启动价格更新服务
         self.price_service.start_price_updates().await?;
         
-        // 启动交易监听服务
+        // 启动事件管道和交易监听服务
+        self.event_pipeline.start();
         self.transaction_listener.start_listening().await?;
         
         Ok(())
     }
 
-    /// 停止所有后台服务
+    /// 停止所有后台服务：先停止监听，再关闭事件管道并等待已入队的事件处理完
     pub async fn stop_background_services(&self) -> AppResult<()> {
         self.price_service.stop_price_updates().await?;
         self.transaction_listener.stop_listening().await?;
+        if !self.event_pipeline.shutdown(PIPELINE_DRAIN_TIMEOUT).await {
+            tracing::warn!("事件管道 {} 秒内未处理完剩余事件", PIPELINE_DRAIN_TIMEOUT.as_secs());
+        }
         Ok(())
     }
 }
@@ -240,6 +240,20 @@ impl PriceService {
         Ok(result)
     }
 
+    /// 强制从API刷新价格，忽略缓存中未过期的数据
+    pub async fn refresh_prices(&self, mints: &[String]) -> AppResult<HashMap<String, PriceData>> {
+        let prices = self.fetch_jupiter_prices(mints).await?;
+
+        {
+            let mut cache = self.price_cache.write().await;
+            for (mint, price_data) in &prices {
+                cache.insert(mint.clone(), price_data.clone());
+            }
+        }
+
+        Ok(prices)
+    }
+
     /// 启动价格更新服务
     pub async fn start_price_updates(&self) -> AppResult<()> {
         info!("启动价格更新服务");
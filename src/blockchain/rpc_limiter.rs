@@ -0,0 +1,217 @@
+//! Solana RPC客户端限流
+//!
+//! 每个RPC节点一个令牌桶，容量和每秒补充量均为`sol_endpoint.rpc_limit`。
+//! 不同方法按权重消耗令牌；令牌不足时按预约顺序排队等待，超过截止时间才拒绝。
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use serde::Serialize;
+use serde_json::Value;
+
+/// 未单独配置的方法权重
+const DEFAULT_METHOD_WEIGHT: u32 = 1;
+
+/// 内置的方法权重，可被`sol_endpoint.method_weights`覆盖
+const BUILTIN_METHOD_WEIGHTS: &[(&str, u32)] = &[
+    ("getProgramAccounts", 10),
+    ("getBlock", 5),
+    ("getSignaturesForAddress", 3),
+    ("getTokenAccountsByOwner", 3),
+    ("getMultipleAccounts", 3),
+    ("getTransaction", 2),
+];
+
+/// 方法权重表
+#[derive(Debug, Clone)]
+pub struct MethodWeights {
+    weights: HashMap<String, u32>,
+}
+
+impl MethodWeights {
+    /// 内置权重合并配置中的覆盖值
+    pub fn new(overrides: &HashMap<String, u32>) -> Self {
+        let mut weights: HashMap<String, u32> = BUILTIN_METHOD_WEIGHTS
+            .iter()
+            .map(|(method, weight)| (method.to_string(), *weight))
+            .collect();
+        weights.extend(overrides.iter().map(|(method, weight)| (method.clone(), *weight)));
+        Self { weights }
+    }
+
+    /// 单个方法的权重
+    pub fn weight(&self, method: &str) -> u32 {
+        self.weights.get(method).copied().unwrap_or(DEFAULT_METHOD_WEIGHT)
+    }
+
+    /// JSON-RPC请求体的总权重，批量请求为各子请求权重之和
+    pub fn weight_of(&self, body: &Value) -> u32 {
+        let method_weight = |request: &Value| request["method"].as_str().map_or(DEFAULT_METHOD_WEIGHT, |method| self.weight(method));
+        match body {
+            Value::Array(requests) => requests.iter().map(method_weight).sum(),
+            request => method_weight(request),
+        }
+    }
+}
+
+impl Default for MethodWeights {
+    fn default() -> Self {
+        Self::new(&HashMap::new())
+    }
+}
+
+/// 限流统计快照
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RpcLimiterMetrics {
+    /// 未等待直接放行的请求数
+    pub granted: u64,
+    /// 排队后放行的请求数
+    pub queued: u64,
+    /// 排队超过截止时间被拒绝的请求数
+    pub rejected: u64,
+    /// 累计排队时间（毫秒）
+    pub queue_wait_ms: u64,
+}
+
+/// 令牌桶状态，令牌数可以为负，表示已被排队请求预约
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+/// 单个RPC节点的令牌桶限流器
+#[derive(Debug)]
+pub struct RpcLimiter {
+    /// 每秒请求数，0表示不限流
+    rate: f64,
+    bucket: Mutex<Bucket>,
+    granted: AtomicU64,
+    queued: AtomicU64,
+    rejected: AtomicU64,
+    queue_wait_ms: AtomicU64,
+}
+
+impl RpcLimiter {
+    /// 创建限流器，`rate_per_second`为0时不限流
+    pub fn new(rate_per_second: u32) -> Self {
+        Self {
+            rate: rate_per_second as f64,
+            bucket: Mutex::new(Bucket {
+                tokens: rate_per_second as f64,
+                updated_at: Instant::now(),
+            }),
+            granted: AtomicU64::new(0),
+            queued: AtomicU64::new(0),
+            rejected: AtomicU64::new(0),
+            queue_wait_ms: AtomicU64::new(0),
+        }
+    }
+
+    /// 预约令牌，返回需要等待的时间
+    ///
+    /// 需要等待超过`max_wait`时不预约并返回`None`。权重超过桶容量时按容量计算，避免永远无法放行。
+    fn reserve(&self, weight: u32, max_wait: Duration) -> Option<Duration> {
+        if self.rate <= 0.0 {
+            return Some(Duration::ZERO);
+        }
+
+        let weight = (weight as f64).min(self.rate);
+        let mut bucket = self.bucket.lock().unwrap();
+        let now = Instant::now();
+        let elapsed = now.duration_since(bucket.updated_at).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.rate).min(self.rate);
+        bucket.updated_at = now;
+
+        let wait = if bucket.tokens >= weight {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64((weight - bucket.tokens) / self.rate)
+        };
+        if wait > max_wait {
+            return None;
+        }
+        bucket.tokens -= weight;
+        Some(wait)
+    }
+
+    /// 不等待地获取令牌
+    pub fn try_acquire(&self, weight: u32) -> bool {
+        let acquired = self.reserve(weight, Duration::ZERO).is_some();
+        if acquired {
+            self.granted.fetch_add(1, Ordering::Relaxed);
+        }
+        acquired
+    }
+
+    /// 获取令牌，必要时排队等待到`deadline`，超时返回`false`
+    pub async fn acquire(&self, weight: u32, deadline: Instant) -> bool {
+        let max_wait = deadline.saturating_duration_since(Instant::now());
+        match self.reserve(weight, max_wait) {
+            None => {
+                self.rejected.fetch_add(1, Ordering::Relaxed);
+                false
+            }
+            Some(wait) if wait.is_zero() => {
+                self.granted.fetch_add(1, Ordering::Relaxed);
+                true
+            }
+            Some(wait) => {
+                self.queued.fetch_add(1, Ordering::Relaxed);
+                self.queue_wait_ms.fetch_add(wait.as_millis() as u64, Ordering::Relaxed);
+                tokio::time::sleep(wait).await;
+                true
+            }
+        }
+    }
+
+    /// 当前统计
+    pub fn metrics(&self) -> RpcLimiterMetrics {
+        RpcLimiterMetrics {
+            granted: self.granted.load(Ordering::Relaxed),
+            queued: self.queued.load(Ordering::Relaxed),
+            rejected: self.rejected.load(Ordering::Relaxed),
+            queue_wait_ms: self.queue_wait_ms.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_method_weights() {
+        let overrides = HashMap::from([("getSlot".to_string(), 2)]);
+        let weights = MethodWeights::new(&overrides);
+        assert_eq!(weights.weight("getProgramAccounts"), 10);
+        assert_eq!(weights.weight("getSlot"), 2);
+        assert_eq!(weights.weight("getBalance"), 1);
+
+        let batch = json!([{ "method": "getBlock" }, { "method": "getBalance" }]);
+        assert_eq!(weights.weight_of(&batch), 6);
+    }
+
+    #[test]
+    fn test_reserve_queues_until_limit() {
+        let limiter = RpcLimiter::new(10);
+        assert!(limiter.try_acquire(10));
+        assert!(!limiter.try_acquire(1));
+
+        // 桶已耗尽，预约5个令牌约需等待0.5秒
+        let wait = limiter.reserve(5, Duration::from_secs(1)).unwrap();
+        assert!(wait > Duration::from_millis(400) && wait <= Duration::from_millis(500));
+        // 已预约的令牌计入后续等待时间
+        assert!(limiter.reserve(10, Duration::from_secs(1)).is_none());
+    }
+
+    #[test]
+    fn test_unlimited() {
+        let limiter = RpcLimiter::new(0);
+        for _ in 0..1000 {
+            assert!(limiter.try_acquire(10));
+        }
+    }
+}
@@ -0,0 +1,486 @@
+//! Solana RPC节点池
+//!
+//! 对所有配置的RPC节点定期做健康检查，按延迟和错误率选择最优的健康节点；
+//! 遇到429、5xx、超时或节点落后时换用其他节点重试。
+//! 每个节点的请求都经过该节点的令牌桶限流，最优节点排满时优先分流到其他健康节点。
+
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, Instant};
+use reqwest::{Client, StatusCode};
+use serde::Serialize;
+use serde_json::{json, Value};
+use crate::blockchain::{MethodWeights, RpcLimiter, RpcLimiterMetrics};
+use crate::config::Config;
+use crate::utils::{AppResult, AppError};
+use tracing::{info, warn, debug};
+
+/// 延迟和错误率的指数移动平均系数
+const EWMA_ALPHA: f64 = 0.2;
+/// 连续失败达到该次数后标记为不健康，等待下一次健康检查或请求成功恢复
+const MAX_CONSECUTIVE_FAILURES: u32 = 3;
+/// 默认健康检查间隔（秒）
+const DEFAULT_HEALTH_CHECK_SECONDS: u64 = 30;
+/// 节点落后（Node is behind）的JSON-RPC错误码
+const RPC_NODE_UNHEALTHY: i64 = -32005;
+/// 默认限流排队截止时间（毫秒）
+const DEFAULT_QUEUE_TIMEOUT_MS: u64 = 2000;
+
+/// 单个节点的运行统计
+#[derive(Debug, Clone)]
+struct EndpointStats {
+    healthy: bool,
+    latency_ms: Option<f64>,
+    error_rate: f64,
+    requests: u64,
+    errors: u64,
+    consecutive_failures: u32,
+    last_error: Option<String>,
+    last_checked: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl Default for EndpointStats {
+    fn default() -> Self {
+        // 首次健康检查前视为健康，保证启动阶段可用
+        Self {
+            healthy: true,
+            latency_ms: None,
+            error_rate: 0.0,
+            requests: 0,
+            errors: 0,
+            consecutive_failures: 0,
+            last_error: None,
+            last_checked: None,
+        }
+    }
+}
+
+impl EndpointStats {
+    fn record_success(&mut self, latency: Duration) {
+        let latency_ms = latency.as_secs_f64() * 1000.0;
+        self.latency_ms = Some(match self.latency_ms {
+            Some(avg) => avg + EWMA_ALPHA * (latency_ms - avg),
+            None => latency_ms,
+        });
+        self.error_rate *= 1.0 - EWMA_ALPHA;
+        self.requests += 1;
+        self.consecutive_failures = 0;
+        self.healthy = true;
+    }
+
+    fn record_failure(&mut self, error: &str) {
+        self.error_rate += EWMA_ALPHA * (1.0 - self.error_rate);
+        self.requests += 1;
+        self.errors += 1;
+        self.consecutive_failures += 1;
+        self.last_error = Some(error.to_string());
+        if self.consecutive_failures >= MAX_CONSECUTIVE_FAILURES {
+            self.healthy = false;
+        }
+    }
+
+    /// 选择节点的评分，越小越好：平均延迟按错误率加权，无延迟数据的节点排在有数据的节点之后
+    fn score(&self) -> f64 {
+        let latency = self.latency_ms.unwrap_or(1000.0);
+        latency * (1.0 + 4.0 * self.error_rate)
+    }
+}
+
+/// 节点状态快照，只包含节点序号和主机名，不暴露完整地址
+#[derive(Debug, Clone, Serialize)]
+pub struct RpcEndpointStatus {
+    pub index: usize,
+    pub host: String,
+    pub healthy: bool,
+    pub latency_ms: Option<f64>,
+    pub error_rate: f64,
+    pub requests: u64,
+    pub errors: u64,
+    pub last_error: Option<String>,
+    pub last_checked: Option<chrono::DateTime<chrono::Utc>>,
+    pub limiter: RpcLimiterMetrics,
+}
+
+/// RPC节点
+struct RpcEndpoint {
+    url: String,
+    stats: Mutex<EndpointStats>,
+    limiter: RpcLimiter,
+}
+
+impl RpcEndpoint {
+    fn stats(&self) -> EndpointStats {
+        self.stats.lock().unwrap().clone()
+    }
+
+    /// 用于日志和错误信息的脱敏地址
+    fn host(&self) -> String {
+        redact_endpoint_url(&self.url)
+    }
+
+    /// 去掉文本中的完整节点地址，reqwest的错误信息会带上请求地址
+    fn redact(&self, text: &str) -> String {
+        redact_url_in(text, &self.url, &self.host())
+    }
+}
+
+/// 单次请求的失败类型
+enum AttemptError {
+    /// 可以换节点重试：429、5xx、网络错误、超时、节点落后
+    Retryable(String),
+    /// 不应重试的错误，例如4xx
+    Fatal(String),
+}
+
+/// Solana RPC节点池
+pub struct RpcPool {
+    endpoints: Vec<Arc<RpcEndpoint>>,
+    http_client: Client,
+    max_retries: u32,
+    weights: MethodWeights,
+    queue_timeout: Duration,
+}
+
+impl RpcPool {
+    /// 按配置创建节点池：`solana.rpc_url`在前，其后为`sol_endpoint.http`中的其他节点
+    pub fn from_config(config: &Config) -> AppResult<Self> {
+        let mut urls = vec![config.solana.rpc_url.clone()];
+        urls.extend(config.sol_endpoint.http.iter().cloned());
+
+        let timeout = Duration::from_secs(config.solana.timeout_seconds.unwrap_or(30));
+        let queue_timeout = config.sol_endpoint.rpc_queue_timeout_ms.unwrap_or(DEFAULT_QUEUE_TIMEOUT_MS);
+        Ok(Self::new(urls, timeout, config.solana.max_retries.unwrap_or(3), config.sol_endpoint.rpc_limit)?
+            .with_method_weights(MethodWeights::new(&config.sol_endpoint.method_weights))
+            .with_queue_timeout(Duration::from_millis(queue_timeout)))
+    }
+
+    /// 创建节点池，重复和空的地址会被忽略；`rpc_limit`为每个节点每秒的请求权重上限，0表示不限流
+    pub fn new(urls: Vec<String>, timeout: Duration, max_retries: u32, rpc_limit: u32) -> AppResult<Self> {
+        let mut endpoints: Vec<Arc<RpcEndpoint>> = Vec::new();
+        for url in urls {
+            let url = url.trim().to_string();
+            if url.is_empty() || endpoints.iter().any(|endpoint| endpoint.url == url) {
+                continue;
+            }
+            endpoints.push(Arc::new(RpcEndpoint {
+                url,
+                stats: Mutex::new(EndpointStats::default()),
+                limiter: RpcLimiter::new(rpc_limit),
+            }));
+        }
+        if endpoints.is_empty() {
+            return Err(AppError::Config(config::ConfigError::Message("未配置Solana RPC节点".to_string())));
+        }
+
+        let http_client = Client::builder()
+            .timeout(timeout)
+            .build()
+            .map_err(|e| AppError::BlockchainError(format!("创建HTTP客户端失败: {}", e)))?;
+
+        Ok(Self {
+            endpoints,
+            http_client,
+            max_retries,
+            weights: MethodWeights::default(),
+            queue_timeout: Duration::from_millis(DEFAULT_QUEUE_TIMEOUT_MS),
+        })
+    }
+
+    /// 设置方法权重
+    pub fn with_method_weights(mut self, weights: MethodWeights) -> Self {
+        self.weights = weights;
+        self
+    }
+
+    /// 设置限流排队的最长等待时间
+    pub fn with_queue_timeout(mut self, queue_timeout: Duration) -> Self {
+        self.queue_timeout = queue_timeout;
+        self
+    }
+
+    /// 按优先级排列的节点：健康节点按评分排序，不健康的节点排在最后作为兜底
+    fn ranked(&self) -> Vec<Arc<RpcEndpoint>> {
+        let mut ranked: Vec<(bool, f64, Arc<RpcEndpoint>)> = self
+            .endpoints
+            .iter()
+            .map(|endpoint| {
+                let stats = endpoint.stats();
+                (!stats.healthy, stats.score(), endpoint.clone())
+            })
+            .collect();
+        ranked.sort_by(|a, b| a.0.cmp(&b.0).then(a.1.total_cmp(&b.1)));
+        ranked.into_iter().map(|(_, _, endpoint)| endpoint).collect()
+    }
+
+    /// 发送JSON-RPC请求体，返回节点响应的JSON
+    ///
+    /// 失败时依次换用下一个节点，最多重试`max_retries`次；节点数少于尝试次数时从头轮换。
+    /// 所有节点限流排队都超过截止时间时返回错误，不再重试。
+    pub async fn send(&self, body: &Value) -> AppResult<Value> {
+        let ranked = self.ranked();
+        let attempts = self.max_retries as usize + 1;
+        let weight = self.weights.weight_of(body);
+        let mut last_error = String::new();
+
+        for attempt in 0..attempts {
+            if attempt >= ranked.len() {
+                // 所有节点都已尝试过，稍作等待再轮换
+                tokio::time::sleep(Duration::from_millis(100 * attempt as u64)).await;
+            }
+            let deadline = Instant::now() + self.queue_timeout;
+            let endpoint = self.acquire(&ranked, attempt % ranked.len(), weight, deadline).await?;
+
+            match self.send_to(endpoint, body).await {
+                Ok(response) => return Ok(response),
+                Err(AttemptError::Fatal(error)) => return Err(AppError::BlockchainError(endpoint.redact(&error))),
+                Err(AttemptError::Retryable(error)) => {
+                    let error = endpoint.redact(&error);
+                    warn!("RPC节点 {} 请求失败（第{}次尝试）: {}", endpoint.host(), attempt + 1, error);
+                    last_error = error;
+                }
+            }
+        }
+
+        Err(AppError::BlockchainError(format!("所有RPC节点请求失败: {}", last_error)))
+    }
+
+    /// 从`preferred`开始选择有令牌的节点
+    ///
+    /// 首选节点令牌不足时分流到其他健康节点；都没有令牌时在首选节点排队直到截止时间。
+    async fn acquire<'a>(
+        &self,
+        ranked: &'a [Arc<RpcEndpoint>],
+        preferred: usize,
+        weight: u32,
+        deadline: Instant,
+    ) -> AppResult<&'a Arc<RpcEndpoint>> {
+        let candidates = ranked[preferred..].iter().chain(&ranked[..preferred]);
+        for (index, endpoint) in candidates.enumerate() {
+            if (index == 0 || endpoint.stats().healthy) && endpoint.limiter.try_acquire(weight) {
+                return Ok(endpoint);
+            }
+        }
+
+        let endpoint = &ranked[preferred];
+        if endpoint.limiter.acquire(weight, deadline).await {
+            Ok(endpoint)
+        } else {
+            Err(AppError::BlockchainError(format!("RPC请求限流排队超时: {}", endpoint.host())))
+        }
+    }
+
+    /// 向指定节点发送请求并更新统计
+    async fn send_to(&self, endpoint: &RpcEndpoint, body: &Value) -> Result<Value, AttemptError> {
+        let started = Instant::now();
+        let result = self.post(&endpoint.url, body).await;
+
+        let mut stats = endpoint.stats.lock().unwrap();
+        match &result {
+            Ok(_) => stats.record_success(started.elapsed()),
+            Err(AttemptError::Retryable(error)) => stats.record_failure(error),
+            // 4xx通常是请求本身的问题，不计入节点错误率
+            Err(AttemptError::Fatal(_)) => {}
+        }
+        result
+    }
+
+    async fn post(&self, url: &str, body: &Value) -> Result<Value, AttemptError> {
+        let response = self
+            .http_client
+            .post(url)
+            .json(body)
+            .send()
+            .await
+            .map_err(|e| AttemptError::Retryable(format!("RPC请求失败: {}", e)))?;
+
+        let status = response.status();
+        if status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error() {
+            return Err(AttemptError::Retryable(format!("RPC响应错误: {}", status)));
+        }
+        if !status.is_success() {
+            return Err(AttemptError::Fatal(format!("RPC响应错误: {}", status)));
+        }
+
+        let value: Value = response
+            .json()
+            .await
+            .map_err(|e| AttemptError::Retryable(format!("解析RPC响应失败: {}", e)))?;
+
+        if value["error"]["code"].as_i64() == Some(RPC_NODE_UNHEALTHY) {
+            return Err(AttemptError::Retryable(format!("RPC节点不健康: {}", value["error"]["message"])));
+        }
+        Ok(value)
+    }
+
+    /// 对所有节点执行一次`getHealth`检查
+    pub async fn check_health(&self) {
+        let body = json!({ "jsonrpc": "2.0", "id": 0, "method": "getHealth", "params": [] });
+
+        let checks = self.endpoints.iter().map(|endpoint| {
+            let body = &body;
+            async move {
+                // 健康检查同样占用节点配额，排队超时则跳过本轮
+                if !endpoint.limiter.acquire(1, Instant::now() + self.queue_timeout).await {
+                    return;
+                }
+                let started = Instant::now();
+                let result = match self.post(&endpoint.url, body).await {
+                    Ok(value) if value["result"] == "ok" => Ok(()),
+                    Ok(value) => Err(format!("节点状态异常: {}", value["error"]["message"])),
+                    Err(AttemptError::Retryable(error)) | Err(AttemptError::Fatal(error)) => Err(error),
+                };
+
+                let mut stats = endpoint.stats.lock().unwrap();
+                stats.last_checked = Some(chrono::Utc::now());
+                match result {
+                    Ok(()) => {
+                        if !stats.healthy {
+                            info!("RPC节点恢复: {}", endpoint.host());
+                        }
+                        stats.record_success(started.elapsed());
+                    }
+                    Err(error) => {
+                        if stats.healthy {
+                            warn!("RPC节点健康检查失败: {} - {}", endpoint.host(), endpoint.redact(&error));
+                        }
+                        stats.record_failure(&error);
+                        stats.healthy = false;
+                    }
+                }
+            }
+        });
+        futures::future::join_all(checks).await;
+    }
+
+    /// 启动后台健康检查，节点池被释放后自动退出
+    pub fn start_health_checks(self: &Arc<Self>, interval_seconds: Option<u64>) {
+        let pool: Weak<Self> = Arc::downgrade(self);
+        let interval = Duration::from_secs(interval_seconds.unwrap_or(DEFAULT_HEALTH_CHECK_SECONDS).max(1));
+
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                let Some(pool) = pool.upgrade() else {
+                    break;
+                };
+                pool.check_health().await;
+                debug!("RPC节点健康检查完成，健康节点数: {}", pool.healthy_count());
+            }
+        });
+    }
+
+    /// 健康节点数
+    pub fn healthy_count(&self) -> usize {
+        self.endpoints.iter().filter(|endpoint| endpoint.stats().healthy).count()
+    }
+
+    /// 所有节点的状态
+    pub fn statuses(&self) -> Vec<RpcEndpointStatus> {
+        self.endpoints
+            .iter()
+            .enumerate()
+            .map(|(index, endpoint)| {
+                let stats = endpoint.stats();
+                let host = endpoint.host();
+                RpcEndpointStatus {
+                    index,
+                    healthy: stats.healthy,
+                    latency_ms: stats.latency_ms,
+                    error_rate: stats.error_rate,
+                    requests: stats.requests,
+                    errors: stats.errors,
+                    // 请求错误信息中带有完整地址
+                    last_error: stats.last_error.map(|error| endpoint.redact(&error)),
+                    last_checked: stats.last_checked,
+                    limiter: endpoint.limiter.metrics(),
+                    host,
+                }
+            })
+            .collect()
+    }
+}
+
+/// 节点地址只保留协议和主机，付费节点的API密钥通常放在路径或查询参数中
+pub fn redact_endpoint_url(url: &str) -> String {
+    let Ok(parsed) = reqwest::Url::parse(url) else {
+        return "<invalid url>".to_string();
+    };
+    match (parsed.host_str(), parsed.port()) {
+        (Some(host), Some(port)) => format!("{}://{}:{}", parsed.scheme(), host, port),
+        (Some(host), None) => format!("{}://{}", parsed.scheme(), host),
+        (None, _) => "<invalid url>".to_string(),
+    }
+}
+
+/// 把文本中出现的完整地址替换为脱敏后的地址
+pub fn redact_url_in(text: &str, url: &str, redacted: &str) -> String {
+    let mut text = text.replace(url, redacted);
+    if let Ok(parsed) = reqwest::Url::parse(url) {
+        text = text.replace(parsed.as_str(), redacted);
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool(urls: &[&str]) -> RpcPool {
+        RpcPool::new(urls.iter().map(|url| url.to_string()).collect(), Duration::from_secs(1), 2, 0).unwrap()
+    }
+
+    fn set_stats(pool: &RpcPool, index: usize, update: impl FnOnce(&mut EndpointStats)) {
+        update(&mut pool.endpoints[index].stats.lock().unwrap());
+    }
+
+    #[test]
+    fn test_dedup_endpoints() {
+        let pool = pool(&["http://a", "http://b", "http://a", " "]);
+        assert_eq!(pool.endpoints.len(), 2);
+        assert!(RpcPool::new(vec![], Duration::from_secs(1), 0, 0).is_err());
+    }
+
+    #[test]
+    fn test_ranking_prefers_fast_healthy_endpoints() {
+        let pool = pool(&["http://slow", "http://fast", "http://down"]);
+        set_stats(&pool, 0, |stats| stats.record_success(Duration::from_millis(400)));
+        set_stats(&pool, 1, |stats| stats.record_success(Duration::from_millis(50)));
+        set_stats(&pool, 2, |stats| {
+            stats.record_success(Duration::from_millis(10));
+            stats.healthy = false;
+        });
+
+        let order: Vec<String> = pool.ranked().iter().map(|endpoint| endpoint.url.clone()).collect();
+        assert_eq!(order, ["http://fast", "http://slow", "http://down"]);
+    }
+
+    #[test]
+    fn test_consecutive_failures_mark_unhealthy() {
+        let mut stats = EndpointStats::default();
+        for _ in 0..MAX_CONSECUTIVE_FAILURES - 1 {
+            stats.record_failure("timeout");
+        }
+        assert!(stats.healthy);
+        stats.record_failure("timeout");
+        assert!(!stats.healthy);
+        assert!(stats.error_rate > 0.4);
+
+        stats.record_success(Duration::from_millis(20));
+        assert!(stats.healthy);
+        assert_eq!(stats.consecutive_failures, 0);
+    }
+
+    #[test]
+    fn test_statuses_hide_endpoint_keys() {
+        let url = "https://mainnet.helius-rpc.com/?api-key=secret";
+        let pool = pool(&[url, "http://127.0.0.1:8899"]);
+        set_stats(&pool, 0, |stats| stats.record_failure(&format!("error sending request for url ({})", url)));
+
+        let statuses = pool.statuses();
+        assert_eq!(statuses[0].host, "https://mainnet.helius-rpc.com");
+        assert_eq!(statuses[1].host, "http://127.0.0.1:8899");
+        assert!(!statuses[0].last_error.as_deref().unwrap().contains("secret"));
+        assert!(!serde_json::to_string(&statuses).unwrap().contains("secret"));
+    }
+}
@@ -3,15 +3,68 @@
 //! 使用HTTP JSON-RPC调用与Solana区块链交互，避免依赖冲突
 
 use std::sync::Arc;
-use std::time::Duration;
 use std::collections::HashMap;
-use reqwest::Client;
+use base64::{engine::general_purpose::STANDARD, Engine};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use crate::config::Config;
+use crate::blockchain::RpcPool;
 use crate::utils::{AppResult, AppError};
 use tracing::{info, warn, error, debug};
 
+/// SPL Token程序
+pub const TOKEN_PROGRAM_ID: &str = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
+/// Token-2022程序，账户和铸造数据的前缀布局与SPL Token相同
+pub const TOKEN_2022_PROGRAM_ID: &str = "TokenzQdBNbLqP5VEhdkAS6EPFLCC2BjqdsbDrnd7KpFvN";
+/// Wrapped SOL，交换中的原生SOL按该代币记
+pub const NATIVE_MINT: &str = "So11111111111111111111111111111111111111112";
+/// 原生SOL精度
+pub const NATIVE_DECIMALS: u8 = 9;
+/// `getMultipleAccounts`单次最多查询的账户数
+const MAX_MULTIPLE_ACCOUNTS: usize = 100;
+/// SPL Token账户数据长度
+const TOKEN_ACCOUNT_LEN: usize = 165;
+/// SPL Token铸造数据长度
+const MINT_LEN: usize = 82;
+/// 区块被跳过（Slot was skipped）
+const RPC_SLOT_SKIPPED: i32 = -32007;
+/// 区块被跳过或不在长期存储中
+const RPC_LONG_TERM_STORAGE_SLOT_SKIPPED: i32 = -32009;
+/// 区块暂不可用，通常是尚未达到请求的确认级别
+const RPC_BLOCK_NOT_AVAILABLE: i32 = -32004;
+/// 区块状态暂不可用
+const RPC_BLOCK_STATUS_NOT_AVAILABLE_YET: i32 = -32014;
+/// `getBlocks`单次查询的最大slot范围
+pub const MAX_GET_BLOCKS_RANGE: u64 = 500_000;
+
+/// 确认级别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Commitment {
+    /// 节点已处理，所在分叉可能被丢弃
+    Processed,
+    /// 已获得超过2/3投票，极少回滚
+    Confirmed,
+    /// 已最终确认，不会回滚
+    Finalized,
+}
+
+impl Commitment {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Commitment::Processed => "processed",
+            Commitment::Confirmed => "confirmed",
+            Commitment::Finalized => "finalized",
+        }
+    }
+}
+
+impl std::fmt::Display for Commitment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 /// Solana RPC请求结构
 #[derive(Debug, Serialize)]
 struct RpcRequest {
@@ -73,6 +126,28 @@ pub struct Mint {
     pub freeze_authority: Option<String>,
 }
 
+/// 区块查询结果
+#[derive(Debug)]
+pub enum BlockFetch {
+    /// 区块数据（jsonParsed编码）
+    Block(Value),
+    /// 该slot没有出块
+    Skipped,
+    /// 区块暂不可用，稍后重试
+    NotAvailable,
+}
+
+/// 地址相关的交易签名
+#[derive(Debug, Clone, Deserialize)]
+pub struct SignatureInfo {
+    pub signature: String,
+    pub slot: u64,
+    /// 交易执行失败时的错误
+    pub err: Option<Value>,
+    #[serde(rename = "blockTime")]
+    pub block_time: Option<i64>,
+}
+
 /// 代币信息结构
 #[derive(Debug, Clone)]
 pub struct TokenInfo {
@@ -85,44 +160,57 @@ pub struct TokenInfo {
 
 /// Solana客户端服务
 pub struct SolanaClientService {
-    http_client: Client,
-    rpc_url: String,
+    pool: Arc<RpcPool>,
     config: Arc<Config>,
     request_id: std::sync::atomic::AtomicU64,
 }
 
 impl SolanaClientService {
     /// 创建新的Solana客户端
+    ///
+    /// 启动时所有节点都不可用只记录警告，后台健康检查会在节点恢复后自动启用。
     pub async fn new(config: Arc<Config>) -> AppResult<Self> {
-        let rpc_url = config.solana.rpc_url.clone();
-        let timeout = Duration::from_secs(config.solana.timeout_seconds.unwrap_or(30));
-        
-        let http_client = Client::builder()
-            .timeout(timeout)
-            .build()
-            .map_err(|e| AppError::BlockchainError(format!("创建HTTP客户端失败: {}", e)))?;
-
-        let client = Self {
-            http_client,
-            rpc_url: rpc_url.clone(),
+        let pool = Arc::new(RpcPool::from_config(&config)?);
+        pool.check_health().await;
+
+        let healthy = pool.healthy_count();
+        if healthy == 0 {
+            warn!("Solana RPC节点均不可用，将在后台继续检查");
+        } else {
+            info!("Solana RPC连接成功，可用节点: {}/{}", healthy, pool.statuses().len());
+        }
+        pool.start_health_checks(config.sol_endpoint.health_check_seconds);
+
+        Ok(Self {
+            pool,
             config,
             request_id: std::sync::atomic::AtomicU64::new(1),
-        };
-
-        // 测试连接
-        match client.health_check().await {
-            Ok(_) => info!("Solana RPC连接成功: {}", rpc_url),
-            Err(e) => {
-                error!("Solana RPC连接失败: {}", e);
-                return Err(AppError::BlockchainError(format!("RPC连接失败: {}", e)));
-            }
-        }
+        })
+    }
 
-        Ok(client)
+    /// RPC节点池
+    pub fn rpc_pool(&self) -> &Arc<RpcPool> {
+        &self.pool
     }
 
     /// 发送RPC请求
     async fn send_rpc_request<T>(&self, method: &str, params: Value) -> AppResult<T>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        let rpc_response: RpcResponse<T> = self.send_rpc_request_raw(method, params).await?;
+
+        if let Some(error) = rpc_response.error {
+            return Err(AppError::BlockchainError(format!("RPC错误: {} - {}", error.code, error.message)));
+        }
+
+        rpc_response.result.ok_or_else(|| {
+            AppError::BlockchainError("RPC响应中缺少result字段".to_string())
+        })
+    }
+
+    /// 发送RPC请求，返回未处理的响应，供需要区分错误码的调用方使用
+    async fn send_rpc_request_raw<T>(&self, method: &str, params: Value) -> AppResult<RpcResponse<T>>
     where
         T: for<'de> Deserialize<'de>,
     {
@@ -137,29 +225,119 @@ impl SolanaClientService {
 
         debug!("发送RPC请求: {} - {}", method, request_id);
 
-        let response = self.http_client
-            .post(&self.rpc_url)
-            .json(&request)
-            .send()
-            .await
-            .map_err(|e| AppError::BlockchainError(format!("RPC请求失败: {}", e)))?;
+        let body = serde_json::to_value(&request)
+            .map_err(|e| AppError::BlockchainError(format!("序列化RPC请求失败: {}", e)))?;
+        let response = self.pool.send(&body).await?;
+
+        serde_json::from_value(response)
+            .map_err(|e| AppError::BlockchainError(format!("解析RPC响应失败: {}", e)))
+    }
 
-        if !response.status().is_success() {
-            return Err(AppError::BlockchainError(format!("RPC响应错误: {}", response.status())));
+    /// 批量发送RPC请求，在一次HTTP请求中完成
+    ///
+    /// 返回结果与`requests`顺序一致，按响应中的`id`对应；单个请求的错误不影响其他请求。
+    pub async fn send_rpc_batch(&self, requests: Vec<(&str, Value)>) -> AppResult<Vec<AppResult<Value>>> {
+        if requests.is_empty() {
+            return Ok(Vec::new());
         }
 
-        let rpc_response: RpcResponse<T> = response
-            .json()
-            .await
-            .map_err(|e| AppError::BlockchainError(format!("解析RPC响应失败: {}", e)))?;
+        let first_id = self.request_id.fetch_add(requests.len() as u64, std::sync::atomic::Ordering::SeqCst);
+        let batch: Vec<RpcRequest> = requests
+            .into_iter()
+            .enumerate()
+            .map(|(index, (method, params))| RpcRequest {
+                jsonrpc: "2.0".to_string(),
+                id: first_id + index as u64,
+                method: method.to_string(),
+                params,
+            })
+            .collect();
+
+        debug!("发送批量RPC请求: {}个 - {}", batch.len(), first_id);
+
+        let body = serde_json::to_value(&batch)
+            .map_err(|e| AppError::BlockchainError(format!("序列化RPC请求失败: {}", e)))?;
+        let response = self.pool.send(&body).await?;
+
+        // 整个批量请求被拒绝时节点返回单个错误对象
+        let responses: Vec<RpcResponse<Value>> = match response {
+            Value::Array(_) => serde_json::from_value(response)
+                .map_err(|e| AppError::BlockchainError(format!("解析RPC响应失败: {}", e)))?,
+            other => {
+                return Err(AppError::BlockchainError(format!("批量RPC请求失败: {}", other["error"]["message"])));
+            }
+        };
 
-        if let Some(error) = rpc_response.error {
-            return Err(AppError::BlockchainError(format!("RPC错误: {} - {}", error.code, error.message)));
+        let mut by_id: HashMap<u64, RpcResponse<Value>> = responses.into_iter().map(|r| (r.id, r)).collect();
+        Ok(batch
+            .iter()
+            .map(|request| match by_id.remove(&request.id) {
+                Some(RpcResponse { error: Some(error), .. }) => Err(AppError::BlockchainError(format!(
+                    "RPC错误: {} - {}",
+                    error.code, error.message
+                ))),
+                Some(RpcResponse { result: Some(result), .. }) => Ok(result),
+                Some(_) => Err(AppError::BlockchainError("RPC响应中缺少result字段".to_string())),
+                None => Err(AppError::BlockchainError(format!("批量RPC响应中缺少请求: {}", request.id))),
+            })
+            .collect())
+    }
+
+    /// 批量获取账户信息，按每批100个拆分后在一次批量请求中发送
+    ///
+    /// 返回结果与`pubkeys`顺序一致，不存在的账户为`None`。
+    pub async fn get_multiple_accounts(&self, pubkeys: &[String]) -> AppResult<Vec<Option<AccountInfo>>> {
+        let requests: Vec<(&str, Value)> = pubkeys
+            .chunks(MAX_MULTIPLE_ACCOUNTS)
+            .map(|chunk| {
+                let params = json!([
+                    chunk,
+                    {
+                        "encoding": "base64",
+                        "commitment": "confirmed"
+                    }
+                ]);
+                ("getMultipleAccounts", params)
+            })
+            .collect();
+
+        let mut accounts = Vec::with_capacity(pubkeys.len());
+        for (chunk, result) in pubkeys.chunks(MAX_MULTIPLE_ACCOUNTS).zip(self.send_rpc_batch(requests).await?) {
+            let values = result?["value"].as_array().cloned().unwrap_or_default();
+            if values.len() != chunk.len() {
+                return Err(AppError::BlockchainError("getMultipleAccounts返回的账户数不匹配".to_string()));
+            }
+            for value in values {
+                let account = if value.is_null() {
+                    None
+                } else {
+                    Some(serde_json::from_value::<AccountInfo>(value)
+                        .map_err(|e| AppError::BlockchainError(format!("解析账户信息失败: {}", e)))?)
+                };
+                accounts.push(account);
+            }
         }
+        Ok(accounts)
+    }
 
-        rpc_response.result.ok_or_else(|| {
-            AppError::BlockchainError("RPC响应中缺少result字段".to_string())
-        })
+    /// 批量获取代币铸造信息，跳过不存在或不属于代币程序的账户
+    pub async fn get_mints(&self, mints: &[String]) -> AppResult<HashMap<String, Mint>> {
+        let accounts = self.get_multiple_accounts(mints).await?;
+
+        let mut result = HashMap::new();
+        for (mint, account) in mints.iter().zip(accounts) {
+            let Some(account) = account else { continue };
+            if account.owner != TOKEN_PROGRAM_ID && account.owner != TOKEN_2022_PROGRAM_ID {
+                continue;
+            }
+            match decode_account_data(&account).and_then(|data| parse_mint_data(&data)) {
+                Ok(parsed) => {
+                    result.insert(mint.clone(), parsed);
+                }
+                Err(e) => warn!("解析代币铸造数据失败: {} - {}", mint, e),
+            }
+        }
+        Ok(result)
     }
 
     /// 获取账户信息
@@ -191,23 +369,7 @@ impl SolanaClientService {
 
     /// 解析代币账户数据
     fn parse_token_account_data(&self, data: &[u8]) -> AppResult<TokenAccount> {
-        if data.len() < 165 { // SPL Token账户最小长度
-            return Err(AppError::BlockchainError("代币账户数据长度不足".to_string()));
-        }
-
-        // 简化的代币账户解析（实际应该使用完整的SPL Token格式）
-        // 这里只是示例，实际项目中需要完整实现
-        Ok(TokenAccount {
-            mint: "".to_string(), // 需要从数据中解析
-            owner: "".to_string(),
-            amount: 0,
-            decimals: 0,
-            delegate: None,
-            state: 1,
-            is_native: None,
-            delegated_amount: 0,
-            close_authority: None,
-        })
+        parse_token_account_data(data)
     }
 
     /// 获取代币账户信息
@@ -217,7 +379,7 @@ impl SolanaClientService {
         match account {
             Some(acc) => {
                 // 检查是否是SPL Token程序拥有的账户
-                if acc.owner != "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA" {
+                if acc.owner != TOKEN_PROGRAM_ID && acc.owner != TOKEN_2022_PROGRAM_ID {
                     return Ok(None);
                 }
 
@@ -273,24 +435,30 @@ impl SolanaClientService {
             .map_err(|e| AppError::BlockchainError(format!("解析余额失败: {}", e)))
     }
 
-    /// 获取钱包的所有代币账户
+    /// 获取钱包的所有代币账户（SPL Token和Token-2022），两个程序的查询合并为一次批量请求
     pub async fn get_token_accounts_by_owner(&self, owner: &str) -> AppResult<Vec<(String, TokenAccount)>> {
-        let params = json!([
-            owner,
-            {
-                "programId": "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"
-            },
-            {
-                "encoding": "base64",
-                "commitment": "confirmed"
-            }
-        ]);
+        let requests = [TOKEN_PROGRAM_ID, TOKEN_2022_PROGRAM_ID]
+            .into_iter()
+            .map(|program_id| {
+                let params = json!([
+                    owner,
+                    {
+                        "programId": program_id
+                    },
+                    {
+                        "encoding": "base64",
+                        "commitment": "confirmed"
+                    }
+                ]);
+                ("getTokenAccountsByOwner", params)
+            })
+            .collect();
 
-        let result: Value = self.send_rpc_request("getTokenAccountsByOwner", params).await?;
-        
         let mut token_accounts = Vec::new();
         
-        if let Some(accounts) = result["value"].as_array() {
+        for result in self.send_rpc_batch(requests).await? {
+            let result = result?;
+            let Some(accounts) = result["value"].as_array() else { continue };
             for account in accounts {
                 if let Some(pubkey) = account["pubkey"].as_str() {
                     if let Some(account_data) = account["account"]["data"].as_array() {
@@ -311,15 +479,38 @@ impl SolanaClientService {
 
     /// 获取最新区块高度
     pub async fn get_slot(&self) -> AppResult<u64> {
+        self.get_slot_with_commitment(Commitment::Confirmed).await
+    }
+
+    /// 获取指定确认级别的最新区块高度
+    pub async fn get_slot_with_commitment(&self, commitment: Commitment) -> AppResult<u64> {
         let params = json!([
             {
-                "commitment": "confirmed"
+                "commitment": commitment.as_str()
             }
         ]);
 
         self.send_rpc_request("getSlot", params).await
     }
 
+    /// 获取`[start_slot, end_slot]`内达到确认级别的区块，不在结果中的slot已被跳过
+    ///
+    /// 范围不能超过`MAX_GET_BLOCKS_RANGE`；`commitment`不支持`processed`。
+    pub async fn get_blocks(&self, start_slot: u64, end_slot: u64, commitment: Commitment) -> AppResult<Vec<u64>> {
+        if end_slot < start_slot || end_slot - start_slot >= MAX_GET_BLOCKS_RANGE {
+            return Err(AppError::validation(format!("Invalid slot range: {} - {}", start_slot, end_slot)));
+        }
+        let params = json!([
+            start_slot,
+            end_slot,
+            {
+                "commitment": commitment.as_str()
+            }
+        ]);
+
+        self.send_rpc_request("getBlocks", params).await
+    }
+
     /// 检查RPC连接健康状态
     pub async fn health_check(&self) -> AppResult<()> {
         let params = json!([]);
@@ -341,7 +532,7 @@ impl SolanaClientService {
         match account {
             Some(acc) => {
                 // 检查是否是代币铸造账户
-                if acc.owner != "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA" {
+                if acc.owner != TOKEN_PROGRAM_ID && acc.owner != TOKEN_2022_PROGRAM_ID {
                     return Ok(None);
                 }
 
@@ -361,22 +552,167 @@ impl SolanaClientService {
         }
     }
 
+    /// 获取区块及其全部交易，交易使用jsonParsed编码以便解析代币指令
+    pub async fn get_block(&self, slot: u64) -> AppResult<BlockFetch> {
+        let params = json!([
+            slot,
+            {
+                "encoding": "jsonParsed",
+                "transactionDetails": "full",
+                "rewards": false,
+                "commitment": "confirmed",
+                "maxSupportedTransactionVersion": 0
+            }
+        ]);
+
+        let response: RpcResponse<Value> = self.send_rpc_request_raw("getBlock", params).await?;
+        match (response.result, response.error) {
+            (_, Some(error)) => match error.code {
+                RPC_SLOT_SKIPPED | RPC_LONG_TERM_STORAGE_SLOT_SKIPPED => Ok(BlockFetch::Skipped),
+                RPC_BLOCK_NOT_AVAILABLE | RPC_BLOCK_STATUS_NOT_AVAILABLE_YET => Ok(BlockFetch::NotAvailable),
+                code => Err(AppError::BlockchainError(format!("RPC错误: {} - {}", code, error.message))),
+            },
+            (Some(block), None) if !block.is_null() => Ok(BlockFetch::Block(block)),
+            _ => Ok(BlockFetch::NotAvailable),
+        }
+    }
+
+    /// 按时间倒序获取涉及地址的交易签名
+    ///
+    /// 从`before`（不含）开始向前查询，到`until`（不含）为止，单次最多1000条。
+    pub async fn get_signatures_for_address(
+        &self,
+        address: &str,
+        before: Option<&str>,
+        until: Option<&str>,
+        limit: usize,
+    ) -> AppResult<Vec<SignatureInfo>> {
+        let mut options = json!({
+            "limit": limit.clamp(1, 1000),
+            "commitment": "confirmed"
+        });
+        if let Some(before) = before {
+            options["before"] = json!(before);
+        }
+        if let Some(until) = until {
+            options["until"] = json!(until);
+        }
+
+        self.send_rpc_request("getSignaturesForAddress", json!([address, options])).await
+    }
+
     /// 获取交易信息
     pub async fn get_transaction(&self, signature: &str) -> AppResult<Option<Value>> {
         let params = json!([
             signature,
             {
-                "encoding": "json",
+                "encoding": "jsonParsed",
                 "commitment": "confirmed",
                 "maxSupportedTransactionVersion": 0
             }
         ]);
 
-        match self.send_rpc_request::<Value>("getTransaction", params).await {
-            Ok(result) => Ok(Some(result)),
-            Err(_) => Ok(None), // 交易不存在或其他错误
+        // 交易不存在时result为null，RPC错误（限流、超时等）返回给调用方
+        let response: RpcResponse<Value> = self.send_rpc_request_raw("getTransaction", params).await?;
+        if let Some(error) = response.error {
+            return Err(AppError::BlockchainError(format!("RPC错误: {} - {}", error.code, error.message)));
         }
+        Ok(response.result.filter(|result| !result.is_null()))
     }
 }
 
-// 添加base64依赖的简单实现（避免额外依赖）
+/// 解码base64编码的账户数据
+fn decode_account_data(account: &AccountInfo) -> AppResult<Vec<u8>> {
+    let data = account.data.first()
+        .ok_or_else(|| AppError::BlockchainError("账户数据为空".to_string()))?;
+    STANDARD.decode(data).map_err(|e| AppError::BlockchainError(format!("Base64解码失败: {}", e)))
+}
+
+fn read_pubkey(data: &[u8], offset: usize) -> String {
+    bs58::encode(&data[offset..offset + 32]).into_string()
+}
+
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&data[offset..offset + 8]);
+    u64::from_le_bytes(bytes)
+}
+
+/// 读取`COption<Pubkey>`：4字节标记后跟32字节公钥
+fn read_optional_pubkey(data: &[u8], offset: usize) -> Option<String> {
+    (data[offset..offset + 4] != [0, 0, 0, 0]).then(|| read_pubkey(data, offset + 4))
+}
+
+/// 按SPL Token账户布局解析数据，Token-2022账户的扩展数据位于165字节之后，忽略
+pub fn parse_token_account_data(data: &[u8]) -> AppResult<TokenAccount> {
+    if data.len() < TOKEN_ACCOUNT_LEN {
+        return Err(AppError::BlockchainError("代币账户数据长度不足".to_string()));
+    }
+
+    Ok(TokenAccount {
+        mint: read_pubkey(data, 0),
+        owner: read_pubkey(data, 32),
+        amount: read_u64(data, 64),
+        // 代币账户数据不包含精度，需要从铸造账户获取
+        decimals: 0,
+        delegate: read_optional_pubkey(data, 72),
+        state: data[108],
+        is_native: (data[109..113] != [0, 0, 0, 0]).then(|| read_u64(data, 113)),
+        delegated_amount: read_u64(data, 121),
+        close_authority: read_optional_pubkey(data, 129),
+    })
+}
+
+/// 按SPL Token铸造账户布局解析数据
+pub fn parse_mint_data(data: &[u8]) -> AppResult<Mint> {
+    if data.len() < MINT_LEN {
+        return Err(AppError::BlockchainError("代币铸造数据长度不足".to_string()));
+    }
+
+    Ok(Mint {
+        mint_authority: read_optional_pubkey(data, 0),
+        supply: read_u64(data, 36),
+        decimals: data[44],
+        is_initialized: data[45] != 0,
+        freeze_authority: read_optional_pubkey(data, 46),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_token_account_data() {
+        let mut data = vec![0u8; TOKEN_ACCOUNT_LEN];
+        data[0..32].copy_from_slice(&[1u8; 32]);
+        data[32..64].copy_from_slice(&[2u8; 32]);
+        data[64..72].copy_from_slice(&1_500_000u64.to_le_bytes());
+        data[108] = 1;
+
+        let account = parse_token_account_data(&data).unwrap();
+        assert_eq!(account.mint, bs58::encode([1u8; 32]).into_string());
+        assert_eq!(account.owner, bs58::encode([2u8; 32]).into_string());
+        assert_eq!(account.amount, 1_500_000);
+        assert_eq!(account.delegate, None);
+        assert_eq!(account.state, 1);
+        assert!(parse_token_account_data(&data[..100]).is_err());
+    }
+
+    #[test]
+    fn test_parse_mint_data() {
+        let mut data = vec![0u8; MINT_LEN];
+        data[0] = 1;
+        data[4..36].copy_from_slice(&[3u8; 32]);
+        data[36..44].copy_from_slice(&1_000_000_000u64.to_le_bytes());
+        data[44] = 6;
+        data[45] = 1;
+
+        let mint = parse_mint_data(&data).unwrap();
+        assert_eq!(mint.mint_authority, Some(bs58::encode([3u8; 32]).into_string()));
+        assert_eq!(mint.supply, 1_000_000_000);
+        assert_eq!(mint.decimals, 6);
+        assert!(mint.is_initialized);
+        assert_eq!(mint.freeze_authority, None);
+    }
+}
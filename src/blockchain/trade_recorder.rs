@@ -0,0 +1,221 @@
+//! 交易记录服务
+//!
+//! 将监听到的以SOL计价的代币交换写入交易表，并同步更新持仓和统计。
+//! 交易在confirmed级别写入；所在slot最终确认后标记为finalized，slot被跳过时撤销交易及其对持仓和统计的影响。
+
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use chrono::Utc;
+use crate::blockchain::{
+    Commitment, SolanaClientService, TransactionEvent, MAX_GET_BLOCKS_RANGE, NATIVE_DECIMALS, NATIVE_MINT,
+};
+use crate::models::solana::SolTransaction;
+use crate::repositories::SolanaRepository;
+use crate::utils::{AppError, AppResult};
+use tracing::{debug, warn};
+
+/// 单次结算最多处理的slot数
+const MAX_SETTLE_SLOTS: u32 = 1000;
+
+/// 一次结算的结果
+#[derive(Debug, Default)]
+pub struct SettleOutcome {
+    /// 标记为最终确认的交易数
+    pub finalized: u64,
+    /// 因slot被跳过而撤销的交易
+    pub reverted: Vec<SolTransaction>,
+}
+
+/// 交易记录器
+pub struct TradeRecorder {
+    solana_client: Arc<SolanaClientService>,
+    repository: SolanaRepository,
+    /// 代币精度缓存
+    decimals: Mutex<HashMap<String, u8>>,
+}
+
+impl TradeRecorder {
+    pub fn new(solana_client: Arc<SolanaClientService>, repository: SolanaRepository) -> Self {
+        Self {
+            solana_client,
+            repository,
+            decimals: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 交换中非SOL一侧的代币，两侧都不是SOL时返回`None`
+    pub fn traded_mint(event: &TransactionEvent) -> Option<&str> {
+        match event {
+            TransactionEvent::TokenSwap { token_in, token_out, .. } if token_in == NATIVE_MINT => Some(token_out),
+            TransactionEvent::TokenSwap { token_in, token_out, .. } if token_out == NATIVE_MINT => Some(token_in),
+            _ => None,
+        }
+    }
+
+    /// 由交换事件构建交易记录，SOL价格为每个代币的SOL数量
+    ///
+    /// 历史USD价格未知，USD字段为0；仓位类型和盈亏在写入时按持仓计算。
+    pub fn build_trade(event: &TransactionEvent, token_decimals: u8) -> Option<SolTransaction> {
+        let TransactionEvent::TokenSwap {
+            signature, user, token_in, token_out, amount_in, amount_out, slot, commitment, timestamp,
+        } = event
+        else {
+            return None;
+        };
+        let (mint, token_raw, sol_raw, is_buy) = if token_in == NATIVE_MINT {
+            (token_out, *amount_out, *amount_in, true)
+        } else if token_out == NATIVE_MINT {
+            (token_in, *amount_in, *amount_out, false)
+        } else {
+            return None;
+        };
+
+        let token_amount = token_raw as f64 / 10f64.powi(token_decimals as i32);
+        let sol_amount = sol_raw as f64 / 10f64.powi(NATIVE_DECIMALS as i32);
+        let now = Utc::now();
+        Some(SolTransaction {
+            id: 0,
+            created_at: now,
+            updated_at: now,
+            deleted_at: None,
+            create_time: *timestamp,
+            mint: mint.clone(),
+            curve: String::new(),
+            signature: signature.clone(),
+            signer: user.clone(),
+            token_amount,
+            sol_amount,
+            new_price_usd: 0.0,
+            new_price_sol: if token_amount > 0.0 { sol_amount / token_amount } else { 0.0 },
+            is_buy,
+            volume_usd: 0.0,
+            slot: slot.to_string(),
+            commitment: commitment.to_string(),
+            pnl: 0.0,
+            transfer_type: 0,
+        })
+    }
+
+    /// 代币精度，优先使用缓存
+    ///
+    /// 未查到铸造账户时返回错误，由事件管道重试，不缓存也不按0位精度写入。
+    async fn token_decimals(&self, mint: &str) -> AppResult<u8> {
+        if let Some(decimals) = self.decimals.lock().unwrap().get(mint) {
+            return Ok(*decimals);
+        }
+        let mint = mint.to_string();
+        let decimals = self
+            .solana_client
+            .get_mints(std::slice::from_ref(&mint))
+            .await?
+            .get(&mint)
+            .map(|chain_mint| chain_mint.decimals)
+            .ok_or_else(|| AppError::blockchain_error(format!("未获取到代币铸造信息: {}", mint)))?;
+        self.decimals.lock().unwrap().insert(mint, decimals);
+        Ok(decimals)
+    }
+
+    /// 写入confirmed级别的交换，其他事件忽略。已写入过的交易返回`false`
+    pub async fn record(&self, event: &TransactionEvent) -> AppResult<bool> {
+        if event.commitment() != Commitment::Confirmed {
+            return Ok(false);
+        }
+        let Some(mint) = Self::traded_mint(event) else {
+            return Ok(false);
+        };
+        let decimals = self.token_decimals(mint).await?;
+        let Some(mut trade) = Self::build_trade(event, decimals) else {
+            return Ok(false);
+        };
+        self.repository.record_trade(&mut trade).await
+    }
+
+    /// 结算已达到最终确认高度的交易：slot仍有区块的标记为finalized，被跳过的撤销
+    pub async fn settle(&self) -> AppResult<SettleOutcome> {
+        let finalized_slot = self.solana_client.get_slot_with_commitment(Commitment::Finalized).await?;
+        let slots = self.repository.find_unfinalized_slots(finalized_slot, MAX_SETTLE_SLOTS).await?;
+        let Some(&first) = slots.first() else {
+            return Ok(SettleOutcome::default());
+        };
+        let last = slots
+            .iter()
+            .copied()
+            .take_while(|slot| slot - first < MAX_GET_BLOCKS_RANGE)
+            .last()
+            .unwrap_or(first);
+
+        let blocks: HashSet<u64> = self
+            .solana_client
+            .get_blocks(first, last, Commitment::Finalized)
+            .await?
+            .into_iter()
+            .collect();
+
+        let mut outcome = SettleOutcome::default();
+        for slot in slots.into_iter().filter(|slot| *slot <= last) {
+            if blocks.contains(&slot) {
+                outcome.finalized += self.repository.finalize_slot(slot).await?;
+            } else {
+                let reverted = self.repository.revert_slot(slot).await?;
+                warn!("区块 {} 已被跳过，撤销 {} 笔交易", slot, reverted.len());
+                outcome.reverted.extend(reverted);
+            }
+        }
+        debug!("结算到区块 {}: 最终确认 {} 笔，撤销 {} 笔", last, outcome.finalized, outcome.reverted.len());
+        Ok(outcome)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MINT: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+
+    fn swap(token_in: &str, token_out: &str, amount_in: u64, amount_out: u64) -> TransactionEvent {
+        TransactionEvent::TokenSwap {
+            signature: "sig".to_string(),
+            user: "alice".to_string(),
+            token_in: token_in.to_string(),
+            token_out: token_out.to_string(),
+            amount_in,
+            amount_out,
+            slot: 42,
+            commitment: Commitment::Confirmed,
+            timestamp: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_build_trade_converts_amounts() {
+        let buy = TradeRecorder::build_trade(&swap(NATIVE_MINT, MINT, 2_000_000_000, 4_000_000), 6).unwrap();
+        assert!(buy.is_buy);
+        assert_eq!((buy.mint.as_str(), buy.token_amount, buy.sol_amount), (MINT, 4.0, 2.0));
+        assert_eq!((buy.new_price_sol, buy.slot.as_str(), buy.commitment.as_str()), (0.5, "42", "confirmed"));
+
+        let sell = TradeRecorder::build_trade(&swap(MINT, NATIVE_MINT, 1_000_000, 1_000_000_000), 6).unwrap();
+        assert!(!sell.is_buy);
+        assert_eq!((sell.token_amount, sell.sol_amount), (1.0, 1.0));
+
+        assert!(TradeRecorder::build_trade(&swap(MINT, "other", 1, 1), 6).is_none());
+    }
+
+    #[test]
+    fn test_holder_delta_reverses_exactly() {
+        let mut buy = TradeRecorder::build_trade(&swap(NATIVE_MINT, MINT, 2_000_000_000, 4_000_000), 6).unwrap();
+        let delta = buy.apply_to_holding(0.0, 0.0);
+        assert_eq!((buy.transfer_type, delta.amount, delta.bet), (1, 4.0, 2.0));
+
+        // 持有4个、成本2 SOL，以1 SOL卖出1个：成本0.5，盈利0.5
+        let mut sell = TradeRecorder::build_trade(&swap(MINT, NATIVE_MINT, 1_000_000, 1_000_000_000), 6).unwrap();
+        let delta = sell.apply_to_holding(4.0, 2.0);
+        assert_eq!((sell.transfer_type, sell.pnl), (4, 0.5));
+        assert_eq!((delta.amount, delta.bet, delta.pnl), (-1.0, -0.5, 0.5));
+        // 撤销时仅依赖交易记录即可还原相同的增量
+        assert_eq!(sell.holder_delta(), delta);
+
+        let mut close = TradeRecorder::build_trade(&swap(MINT, NATIVE_MINT, 4_000_000, 1_000_000_000), 6).unwrap();
+        close.apply_to_holding(4.0, 2.0);
+        assert_eq!((close.transfer_type, close.pnl), (2, -1.0));
+    }
+}
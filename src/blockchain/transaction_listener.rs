@@ -1,18 +1,86 @@
-//! 交易监听服务 - HTTP实现
+//! 交易监听服务
 //! 
-//! 监听Solana区块链上的交易事件
+//! 监听Solana区块链上的交易事件：通过WebSocket订阅新区块、关注程序和地址的交易日志以及池子账户变化，
+//! WebSocket不可用时退回到定时轮询区块高度。
+//!
+//! 事件标注确认级别：交易在confirmed级别获取并写入交易表，池子账户变化为processed级别。
+//! 已写入的交易定期按最终确认的区块结算，所在slot被跳过的交易会被撤销。
+//! 多次重试仍无法获取的区块记录在Redis中，由重试任务定期重新处理。
+//! 事件通过有界的事件管道交给各输出端处理。
 
-use std::sync::Arc;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::{Arc, Mutex, RwLock};
 use std::time::Duration;
-use tokio::sync::mpsc;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
 use tokio::time::{interval, sleep};
 use crate::config::Config;
-use crate::utils::{AppResult, AppError};
-use crate::blockchain::SolanaClientService;
+use crate::utils::{AppResult, AppError, Validator};
+use crate::blockchain::{
+    BlockFetch, Commitment, EventPipeline, SolanaClientService, SolanaWsClient, Subscription, TradeRecorder, NATIVE_MINT,
+};
+use crate::repositories::RedisRepository;
 use tracing::{info, warn, error, debug};
 
-/// 交易事件类型
-#[derive(Debug, Clone)]
+/// 记录最近处理过的交易数，用于日志通知和区块处理之间去重
+const RECENT_SIGNATURE_CAPACITY: usize = 50_000;
+/// 区块持续不可用时的最大等待次数，超过后视为缺失并跳过
+const MAX_SLOT_WAIT_ATTEMPTS: u32 = 150;
+/// 区块处理连续出错的最大次数，超过后跳过
+const MAX_SLOT_ERROR_ATTEMPTS: u32 = 5;
+/// 重新处理被跳过区块的间隔（秒）
+const SKIPPED_SLOT_RETRY_INTERVAL_SECONDS: u64 = 60;
+/// 每次重新处理的最大区块数
+const SKIPPED_SLOT_RETRY_BATCH: usize = 20;
+/// 重启后默认最多追赶的区块数（约一天），更早的区块需要通过回填处理
+const DEFAULT_MAX_CATCH_UP_SLOTS: u64 = 216_000;
+/// 结算已写入交易的间隔（秒），最终确认约落后confirmed 32个slot
+const SETTLE_INTERVAL_SECONDS: u64 = 10;
+
+/// 单个区块的处理结果
+#[derive(Debug, PartialEq)]
+enum SlotOutcome {
+    /// 已处理，附带发送的事件数
+    Processed(usize),
+    /// 该slot没有出块
+    Skipped,
+    /// 区块暂不可用，需要稍后重试
+    NotAvailable,
+}
+
+/// 最近处理过的交易签名，超过容量时淘汰最早的记录
+struct RecentSignatures {
+    inner: Mutex<(HashSet<String>, VecDeque<String>)>,
+}
+
+impl RecentSignatures {
+    fn new() -> Self {
+        Self {
+            inner: Mutex::new((HashSet::new(), VecDeque::new())),
+        }
+    }
+
+    /// 记录签名，已存在时返回`false`
+    fn insert(&self, signature: &str) -> bool {
+        let mut guard = self.inner.lock().unwrap();
+        let (set, order) = &mut *guard;
+        if !set.insert(signature.to_string()) {
+            return false;
+        }
+        order.push_back(signature.to_string());
+        if order.len() > RECENT_SIGNATURE_CAPACITY {
+            if let Some(oldest) = order.pop_front() {
+                set.remove(&oldest);
+            }
+        }
+        true
+    }
+}
+
+/// 交易事件类型，JSON中以`type`字段区分
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
 pub enum TransactionEvent {
     /// 代币转账事件
     TokenTransfer {
@@ -22,6 +90,7 @@ pub enum TransactionEvent {
         mint: String,
         amount: u64,
         slot: u64,
+        commitment: Commitment,
         timestamp: chrono::DateTime<chrono::Utc>,
     },
     /// 代币交换事件
@@ -33,9 +102,10 @@ pub enum TransactionEvent {
         amount_in: u64,
         amount_out: u64,
         slot: u64,
+        commitment: Commitment,
         timestamp: chrono::DateTime<chrono::Utc>,
     },
-    /// 新代币创建事件
+    /// 代币铸造事件：新建代币或增发，`supply`为本交易铸造的数量
     TokenMint {
         signature: String,
         mint: String,
@@ -43,27 +113,84 @@ pub enum TransactionEvent {
         supply: u64,
         decimals: u8,
         slot: u64,
+        commitment: Commitment,
         timestamp: chrono::DateTime<chrono::Utc>,
     },
+    /// 池子账户变化事件
+    PoolUpdate {
+        pool: String,
+        lamports: u64,
+        #[serde(serialize_with = "serialize_base64", deserialize_with = "deserialize_base64")]
+        data: Vec<u8>,
+        slot: u64,
+        commitment: Commitment,
+        timestamp: chrono::DateTime<chrono::Utc>,
+    },
+    /// 已写入的交易所在slot被跳过，交易及其对持仓和统计的影响已撤销
+    TradeReverted {
+        signature: String,
+        mint: String,
+        signer: String,
+        slot: u64,
+        commitment: Commitment,
+    },
+}
+
+impl TransactionEvent {
+    /// 事件的确认级别
+    pub fn commitment(&self) -> Commitment {
+        match self {
+            TransactionEvent::TokenTransfer { commitment, .. }
+            | TransactionEvent::TokenSwap { commitment, .. }
+            | TransactionEvent::TokenMint { commitment, .. }
+            | TransactionEvent::PoolUpdate { commitment, .. }
+            | TransactionEvent::TradeReverted { commitment, .. } => *commitment,
+        }
+    }
+}
+
+/// 账户数据在JSON中以base64编码
+fn serialize_base64<S: serde::Serializer>(data: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&STANDARD.encode(data))
+}
+
+fn deserialize_base64<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+    let encoded = String::deserialize(deserializer)?;
+    STANDARD.decode(encoded).map_err(serde::de::Error::custom)
 }
 
 /// 交易监听器
 pub struct TransactionListener {
     config: Arc<Config>,
     solana_client: Arc<SolanaClientService>,
-    event_sender: Option<mpsc::UnboundedSender<TransactionEvent>>,
+    ws_client: Arc<SolanaWsClient>,
+    redis: RedisRepository,
+    trade_recorder: Arc<TradeRecorder>,
+    event_pipeline: Arc<EventPipeline>,
+    /// 关注的程序和地址，区块处理时只解析涉及这些地址的交易；`watch_address`会追加
+    mentions: Arc<RwLock<Vec<String>>>,
     is_running: Arc<tokio::sync::RwLock<bool>>,
 }
 
 impl TransactionListener {
-    /// 创建新的交易监听器
-    pub async fn new(config: Arc<Config>) -> AppResult<Self> {
-        let solana_client = Arc::new(SolanaClientService::new(config.clone()).await?);
-        
+    /// 创建新的交易监听器，与其他服务共用同一个RPC节点池
+    pub async fn new(
+        config: Arc<Config>,
+        solana_client: Arc<SolanaClientService>,
+        ws_client: Arc<SolanaWsClient>,
+        redis: RedisRepository,
+        trade_recorder: Arc<TradeRecorder>,
+        event_pipeline: Arc<EventPipeline>,
+    ) -> AppResult<Self> {
+        let mentions = Arc::new(RwLock::new(Self::watched_mentions(&config)));
         Ok(Self {
             config,
             solana_client,
-            event_sender: None,
+            ws_client,
+            redis,
+            trade_recorder,
+            event_pipeline,
+            mentions,
             is_running: Arc::new(tokio::sync::RwLock::new(false)),
         })
     }
@@ -78,21 +205,78 @@ impl TransactionListener {
         *is_running = true;
         info!("启动交易监听服务");
         
-        // 创建事件通道
-        let (tx, mut rx) = mpsc::unbounded_channel();
+        // 事件进入有界管道，由各输出端并发处理
+        let tx = self.event_pipeline.clone();
         
-        // 启动监听任务
+        // 订阅新区块、关注的程序和地址以及池子账户
+        let ws_client = self.ws_client.clone();
+        ws_client.subscribe(Subscription::Slot).await;
+        let watched = self.mentions.read().unwrap().clone();
+        for mention in watched {
+            ws_client.subscribe(Subscription::Logs { mention }).await;
+        }
+        for pool in &self.config.solana.monitoring.watch_pools {
+            ws_client.subscribe(Subscription::Account { pubkey: pool.clone() }).await;
+        }
+        let mut slot_updates = ws_client.slot_updates();
+        let mut logs_updates = ws_client.logs_updates();
+        let mut account_updates = ws_client.account_updates();
+        ws_client.start().await;
+        
+        // 启动区块处理任务：优先由slot通知驱动，WebSocket断开时按间隔轮询
         let solana_client = self.solana_client.clone();
         let is_running_clone = self.is_running.clone();
         let config = self.config.clone();
+        let slot_tx = tx.clone();
+        let recent_signatures = Arc::new(RecentSignatures::new());
+        let seen = recent_signatures.clone();
+        let retry_seen = recent_signatures.clone();
+        let mentions = self.mentions.clone();
+        let redis = self.redis.clone();
         
         tokio::spawn(async move {
-            let mut last_processed_slot = 0u64;
+            // 从上次保存的进度继续，补齐停机期间的区块
+            let mut last_processed_slot = match redis.get_listener_checkpoint().await {
+                Ok(checkpoint) => checkpoint.unwrap_or(0),
+                Err(e) => {
+                    error!("读取监听进度失败: {}", e);
+                    0
+                }
+            };
+            if last_processed_slot > 0 {
+                info!("从区块 {} 继续监听", last_processed_slot);
+            }
+            let max_catch_up_slots = config.solana.monitoring.max_catch_up_slots.unwrap_or(DEFAULT_MAX_CATCH_UP_SLOTS);
+            let mut stalled_attempts = 0u32;
             let check_interval_seconds = config.solana.monitoring.block_check_interval_seconds;
             let mut check_interval = interval(Duration::from_secs(check_interval_seconds));
             
             loop {
-                check_interval.tick().await;
+                let current_slot = tokio::select! {
+                    update = slot_updates.recv() => match update {
+                        Ok(update) => update.slot,
+                        // 落后的通知直接丢弃，下一次通知会补齐中间的区块
+                        Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                            warn!("slot通知处理落后，跳过 {} 条", skipped);
+                            continue;
+                        }
+                        Err(broadcast::error::RecvError::Closed) => break,
+                    },
+                    _ = check_interval.tick() => {
+                        // 推送正常时无需轮询
+                        if ws_client.is_connected() {
+                            continue;
+                        }
+                        match solana_client.get_slot().await {
+                            Ok(slot) => slot,
+                            Err(e) => {
+                                error!("获取区块高度失败: {}", e);
+                                sleep(Duration::from_secs(10)).await;
+                                continue;
+                            }
+                        }
+                    }
+                };
                 
                 // 检查是否应该停止
                 {
@@ -102,37 +286,166 @@ impl TransactionListener {
                     }
                 }
                 
-                // 获取最新区块高度
-                match solana_client.get_slot().await {
-                    Ok(current_slot) => {
-                        if last_processed_slot == 0 {
-                            last_processed_slot = current_slot.saturating_sub(10); // 从10个区块前开始
+                if current_slot <= last_processed_slot {
+                    continue;
+                }
+                if last_processed_slot == 0 {
+                    last_processed_slot = current_slot.saturating_sub(10); // 从10个区块前开始
+                } else if current_slot - last_processed_slot > max_catch_up_slots {
+                    let resume_slot = current_slot - max_catch_up_slots;
+                    warn!("监听中断过久，区块 {} 到 {} 需要通过回填处理", last_processed_slot + 1, resume_slot);
+                    last_processed_slot = resume_slot;
+                }
+                let previous_slot = last_processed_slot;
+                
+                // 按顺序处理新区块，遇到暂不可用或出错的区块时停在该处，下一轮从该区块继续
+                let watched = mentions.read().unwrap().clone();
+                for slot in (last_processed_slot + 1)..=current_slot {
+                    let (advance, limit) = match Self::process_slot(&solana_client, slot, &watched, &seen, &slot_tx).await {
+                        Ok(SlotOutcome::NotAvailable) => (false, MAX_SLOT_WAIT_ATTEMPTS),
+                        Ok(_) => (true, 0),
+                        Err(e) => {
+                            warn!("处理区块 {} 失败: {}", slot, e);
+                            (false, MAX_SLOT_ERROR_ATTEMPTS)
                         }
-                        
-                        // 处理新区块
-                        for slot in (last_processed_slot + 1)..=current_slot {
-                            if let Err(e) = Self::process_slot(&solana_client, slot, &tx).await {
-                                warn!("处理区块 {} 失败: {}", slot, e);
-                            }
+                    };
+                    
+                    if !advance {
+                        stalled_attempts += 1;
+                        if stalled_attempts < limit {
+                            break;
+                        }
+                        // 记录下来由重试任务稍后处理，记录失败时停在该区块
+                        if let Err(e) = redis.add_skipped_slot(slot).await {
+                            error!("记录跳过的区块 {} 失败: {}", slot, e);
+                            break;
                         }
-                        
-                        last_processed_slot = current_slot;
-                        debug!("已处理到区块: {}", current_slot);
+                        error!("区块 {} 已重试 {} 次仍无法处理，暂时跳过并稍后重新处理", slot, stalled_attempts);
                     }
-                    Err(e) => {
-                        error!("获取区块高度失败: {}", e);
-                        sleep(Duration::from_secs(10)).await;
+                    stalled_attempts = 0;
+                    last_processed_slot = slot;
+                }
+                
+                if last_processed_slot > previous_slot {
+                    if let Err(e) = redis.set_listener_checkpoint(last_processed_slot).await {
+                        warn!("保存监听进度失败: {}", e);
                     }
                 }
+                debug!("已处理到区块: {}", last_processed_slot);
             }
             
             info!("交易监听服务已停止");
         });
 
-        // 启动事件处理任务
+        // 启动重试任务：重新处理之前多次重试仍失败而跳过的区块
+        let solana_client = self.solana_client.clone();
+        let is_running_clone = self.is_running.clone();
+        let mentions = self.mentions.clone();
+        let redis = self.redis.clone();
+        let retry_tx = tx.clone();
+        tokio::spawn(async move {
+            let mut retry_interval = interval(Duration::from_secs(SKIPPED_SLOT_RETRY_INTERVAL_SECONDS));
+            loop {
+                retry_interval.tick().await;
+                if !*is_running_clone.read().await {
+                    break;
+                }
+                
+                let slots = match redis.skipped_slots(SKIPPED_SLOT_RETRY_BATCH).await {
+                    Ok(slots) => slots,
+                    Err(e) => {
+                        warn!("读取跳过的区块失败: {}", e);
+                        continue;
+                    }
+                };
+                let watched = mentions.read().unwrap().clone();
+                for slot in slots {
+                    match Self::process_slot(&solana_client, slot, &watched, &retry_seen, &retry_tx).await {
+                        Ok(SlotOutcome::NotAvailable) => debug!("跳过的区块 {} 仍不可用", slot),
+                        Ok(_) => {
+                            info!("已重新处理跳过的区块 {}", slot);
+                            if let Err(e) = redis.remove_skipped_slot(slot).await {
+                                warn!("移除跳过的区块 {} 失败: {}", slot, e);
+                            }
+                        }
+                        Err(e) => warn!("重新处理区块 {} 失败: {}", slot, e),
+                    }
+                }
+            }
+        });
+
+        // 启动日志通知处理任务：实时获取关注程序和地址的成功交易
+        let solana_client = self.solana_client.clone();
+        let logs_tx = tx.clone();
         tokio::spawn(async move {
-            while let Some(event) = rx.recv().await {
-                Self::handle_transaction_event(event).await;
+            loop {
+                match logs_updates.recv().await {
+                    Ok(update) if update.err.is_none() => {
+                        if let Err(e) = Self::process_signature(&solana_client, &update.signature, update.slot, &recent_signatures, &logs_tx).await {
+                            warn!("处理交易 {} 失败: {}", update.signature, e);
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => warn!("日志通知处理落后，跳过 {} 条", skipped),
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        });
+
+        // 启动池子账户通知处理任务
+        let account_tx = tx.clone();
+        tokio::spawn(async move {
+            loop {
+                match account_updates.recv().await {
+                    Ok(update) => {
+                        let event = TransactionEvent::PoolUpdate {
+                            pool: update.pubkey,
+                            lamports: update.lamports,
+                            data: update.data,
+                            slot: update.slot,
+                            commitment: Commitment::Processed,
+                            timestamp: chrono::Utc::now(),
+                        };
+                        if account_tx.publish(event).await.is_err() {
+                            break;
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => warn!("账户通知处理落后，跳过 {} 条", skipped),
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        });
+
+        // 启动结算任务：已写入的交易达到最终确认后标记，所在slot被跳过的撤销
+        let trade_recorder = self.trade_recorder.clone();
+        let is_running_clone = self.is_running.clone();
+        tokio::spawn(async move {
+            let mut settle_interval = interval(Duration::from_secs(SETTLE_INTERVAL_SECONDS));
+            loop {
+                settle_interval.tick().await;
+                if !*is_running_clone.read().await {
+                    break;
+                }
+                
+                let outcome = match trade_recorder.settle().await {
+                    Ok(outcome) => outcome,
+                    Err(e) => {
+                        warn!("结算交易失败: {}", e);
+                        continue;
+                    }
+                };
+                for trade in outcome.reverted {
+                    let event = TransactionEvent::TradeReverted {
+                        slot: trade.slot.parse().unwrap_or_default(),
+                        signature: trade.signature,
+                        mint: trade.mint,
+                        signer: trade.signer,
+                        commitment: Commitment::Finalized,
+                    };
+                    if tx.publish(event).await.is_err() {
+                        return;
+                    }
+                }
             }
         });
         
@@ -143,82 +456,148 @@ impl TransactionListener {
     pub async fn stop_listening(&self) -> AppResult<()> {
         let mut is_running = self.is_running.write().await;
         *is_running = false;
+        self.ws_client.stop();
         info!("停止交易监听服务");
         Ok(())
     }
 
     /// 处理单个区块
+    ///
+    /// 配置了关注地址时只解析涉及这些地址的交易，否则解析区块中的全部交易。
     async fn process_slot(
         solana_client: &SolanaClientService,
         slot: u64,
-        event_sender: &mpsc::UnboundedSender<TransactionEvent>,
-    ) -> AppResult<()> {
-        // 这里应该实现具体的区块处理逻辑
-        // 由于使用HTTP RPC，我们需要通过其他方式获取区块信息
-        
-        debug!("处理区块: {}", slot);
-        
-        // 在实际实现中，这里会：
-        // 1. 通过RPC获取区块中的所有交易
-        // 2. 解析每个交易的指令
-        // 3. 识别代币相关的操作
-        // 4. 发送相应的事件
+        mentions: &[String],
+        seen: &RecentSignatures,
+        event_pipeline: &EventPipeline,
+    ) -> AppResult<SlotOutcome> {
+        let mut block = match solana_client.get_block(slot).await? {
+            BlockFetch::Block(block) => block,
+            BlockFetch::Skipped => {
+                debug!("区块 {} 已跳过", slot);
+                return Ok(SlotOutcome::Skipped);
+            }
+            BlockFetch::NotAvailable => return Ok(SlotOutcome::NotAvailable),
+        };
         
-        // 示例：创建一个测试事件
-        if slot % 100 == 0 { // 每100个区块发送一个测试事件
-            let test_event = TransactionEvent::TokenTransfer {
-                signature: format!("test_signature_{}", slot),
-                from: "test_from_address".to_string(),
-                to: "test_to_address".to_string(),
-                mint: "test_mint_address".to_string(),
-                amount: 1000000,
-                slot,
-                timestamp: chrono::Utc::now(),
+        let block_time = block["blockTime"].clone();
+        let mut sent = 0;
+        for transaction in block["transactions"].as_array_mut().into_iter().flatten() {
+            let Some(signature) = transaction["transaction"]["signatures"][0].as_str().map(str::to_string) else {
+                continue;
             };
+            if !mentions.is_empty() && !Self::mentions_any(transaction, mentions) {
+                continue;
+            }
+            if !seen.insert(&signature) {
+                continue;
+            }
             
-            if let Err(e) = event_sender.send(test_event) {
-                warn!("发送事件失败: {}", e);
+            // 区块中的交易不含slot和blockTime，补充后交给解析器
+            transaction["slot"] = serde_json::json!(slot);
+            transaction["blockTime"] = block_time.clone();
+            
+            match TransactionParser::parse_transaction(&signature, transaction, Commitment::Confirmed) {
+                Ok(events) => {
+                    for event in events {
+                        event_pipeline.publish(event).await?;
+                        sent += 1;
+                    }
+                }
+                Err(e) => warn!("解析交易 {} 失败: {}", signature, e),
             }
         }
         
+        debug!("处理区块: {}，事件数: {}", slot, sent);
+        Ok(SlotOutcome::Processed(sent))
+    }
+
+    /// 交易的账户列表是否包含任一关注地址
+    fn mentions_any(transaction: &serde_json::Value, mentions: &[String]) -> bool {
+        transaction["transaction"]["message"]["accountKeys"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|key| key["pubkey"].as_str().or(key.as_str()))
+            .any(|key| mentions.iter().any(|mention| mention == key))
+    }
+
+    /// 处理日志通知中的单笔交易
+    async fn process_signature(
+        solana_client: &SolanaClientService,
+        signature: &str,
+        slot: u64,
+        seen: &RecentSignatures,
+        event_pipeline: &EventPipeline,
+    ) -> AppResult<()> {
+        debug!("处理交易: {} - 区块 {}", signature, slot);
+        
+        // 获取失败时不记录签名，由区块处理兜底
+        let Some(transaction) = solana_client.get_transaction(signature).await? else {
+            return Ok(());
+        };
+        if !seen.insert(signature) {
+            return Ok(());
+        }
+        
+        for event in TransactionParser::parse_transaction(signature, &transaction, Commitment::Confirmed)? {
+            event_pipeline.publish(event).await?;
+        }
+        
         Ok(())
     }
 
-    /// 处理交易事件
-    async fn handle_transaction_event(event: TransactionEvent) {
-        match event {
-            TransactionEvent::TokenTransfer { signature, from, to, mint, amount, .. } => {
-                info!("代币转账: {} -> {}, 代币: {}, 数量: {}, 交易: {}", 
-                      from, to, mint, amount, signature);
-                // 这里可以将事件存储到数据库或发送到其他服务
-            }
-            TransactionEvent::TokenSwap { signature, user, token_in, token_out, amount_in, amount_out, .. } => {
-                info!("代币交换: 用户: {}, {} {} -> {} {}, 交易: {}", 
-                      user, amount_in, token_in, amount_out, token_out, signature);
+    /// 需要通过logsSubscribe关注的程序和地址，忽略无效地址
+    fn watched_mentions(config: &Config) -> Vec<String> {
+        let monitoring = &config.solana.monitoring;
+        let programs = [&config.raydium_launchpad_program, &config.cpmm_program, &config.cook_contract];
+        
+        let mut mentions: Vec<String> = Vec::new();
+        for address in programs.into_iter().chain(&monitoring.watch_addresses).chain(&monitoring.watch_tokens) {
+            if !Validator::is_valid_solana_address(address) {
+                if !address.is_empty() {
+                    warn!("忽略无效的监听地址: {}", address);
+                }
+                continue;
             }
-            TransactionEvent::TokenMint { signature, mint, authority, supply, decimals, .. } => {
-                info!("新代币创建: {}, 发行方: {}, 供应量: {}, 精度: {}, 交易: {}", 
-                      mint, authority, supply, decimals, signature);
+            if !mentions.contains(address) {
+                mentions.push(address.clone());
             }
         }
+        mentions
     }
 
     /// 监听特定地址的交易
     pub async fn watch_address(&self, address: &str) -> AppResult<()> {
+        if !Validator::is_valid_solana_address(address) {
+            return Err(AppError::validation(format!("Invalid Solana address: {}", address)));
+        }
         info!("开始监听地址: {}", address);
         
-        // 这里可以实现对特定地址的监听逻辑
-        // 例如定期查询地址的交易历史
-        
+        // 未配置任何关注地址时区块中的交易全部解析，已经包含该地址
+        {
+            let mut mentions = self.mentions.write().unwrap();
+            if !mentions.is_empty() && !mentions.iter().any(|mention| mention == address) {
+                mentions.push(address.to_string());
+            }
+        }
+        self.ws_client.subscribe(Subscription::Logs { mention: address.to_string() }).await;
         Ok(())
     }
 
     /// 监听特定代币的交易
     pub async fn watch_token(&self, mint: &str) -> AppResult<()> {
-        info!("开始监听代币: {}", mint);
-        
-        // 这里可以实现对特定代币的监听逻辑
+        self.watch_address(mint).await
+    }
+
+    /// 监听池子账户变化
+    pub async fn watch_pool(&self, pool: &str) -> AppResult<()> {
+        if !Validator::is_valid_solana_address(pool) {
+            return Err(AppError::validation(format!("Invalid Solana address: {}", pool)));
+        }
+        info!("开始监听池子账户: {}", pool);
         
+        self.ws_client.subscribe(Subscription::Account { pubkey: pool.to_string() }).await;
         Ok(())
     }
 
@@ -226,43 +605,445 @@ impl TransactionListener {
     pub async fn is_listening(&self) -> bool {
         *self.is_running.read().await
     }
+
+    /// 等待重新处理的区块数
+    pub async fn skipped_slot_count(&self) -> AppResult<u64> {
+        self.redis.skipped_slot_count().await
+    }
 }
 
-/// 交易解析器
+/// jsonParsed编码中SPL Token和Token-2022程序的名称
+const SPL_TOKEN_PROGRAMS: [&str; 2] = ["spl-token", "spl-token-2022"];
+
+/// 代币账户在交易前后的余额
+#[derive(Debug, Clone)]
+struct TokenBalance {
+    mint: String,
+    owner: String,
+    amount: u64,
+    decimals: u8,
+}
+
+/// jsonParsed编码交易的解析上下文
+struct ParsedTransaction<'a> {
+    signature: &'a str,
+    slot: u64,
+    timestamp: chrono::DateTime<chrono::Utc>,
+    account_keys: Vec<String>,
+    meta: &'a serde_json::Value,
+    /// 代币账户地址 -> 交易前余额
+    pre_balances: HashMap<String, TokenBalance>,
+    /// 代币账户地址 -> 交易后余额
+    post_balances: HashMap<String, TokenBalance>,
+    /// 代币程序的指令（包含内部指令），按执行顺序排列
+    token_instructions: Vec<(&'a str, &'a serde_json::Value)>,
+}
+
+impl<'a> ParsedTransaction<'a> {
+    /// 解析交易，执行失败的交易没有链上效果，返回`None`
+    ///
+    /// `slot`和`blockTime`取自交易数据，区块中的交易由调用方补充。
+    fn new(signature: &'a str, transaction_data: &'a serde_json::Value) -> AppResult<Option<Self>> {
+        let meta = &transaction_data["meta"];
+        if meta.is_null() {
+            return Err(AppError::transaction_parsing_error(format!("交易缺少meta: {}", signature)));
+        }
+        if !meta["err"].is_null() {
+            return Ok(None);
+        }
+
+        let message = &transaction_data["transaction"]["message"];
+        let account_keys: Vec<String> = message["accountKeys"]
+            .as_array()
+            .ok_or_else(|| AppError::transaction_parsing_error(format!("交易缺少accountKeys: {}", signature)))?
+            .iter()
+            .map(|key| key["pubkey"].as_str().or(key.as_str()).unwrap_or_default().to_string())
+            .collect();
+
+        let inner_instructions = meta["innerInstructions"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|inner| inner["instructions"].as_array())
+            .flatten();
+        let token_instructions = message["instructions"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .chain(inner_instructions)
+            .filter(|instruction| {
+                instruction["program"].as_str().is_some_and(|program| SPL_TOKEN_PROGRAMS.contains(&program))
+            })
+            .filter_map(|instruction| {
+                let parsed = &instruction["parsed"];
+                parsed["type"].as_str().map(|kind| (kind, &parsed["info"]))
+            })
+            .collect();
+
+        let timestamp = transaction_data["blockTime"]
+            .as_i64()
+            .and_then(|seconds| chrono::DateTime::from_timestamp(seconds, 0))
+            .unwrap_or_else(chrono::Utc::now);
+
+        Ok(Some(Self {
+            signature,
+            slot: transaction_data["slot"].as_u64().unwrap_or_default(),
+            timestamp,
+            pre_balances: Self::token_balances(&meta["preTokenBalances"], &account_keys),
+            post_balances: Self::token_balances(&meta["postTokenBalances"], &account_keys),
+            account_keys,
+            meta,
+            token_instructions,
+        }))
+    }
+
+    fn token_balances(balances: &serde_json::Value, account_keys: &[String]) -> HashMap<String, TokenBalance> {
+        balances
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|balance| {
+                let account = account_keys.get(balance["accountIndex"].as_u64()? as usize)?;
+                let token_amount = &balance["uiTokenAmount"];
+                Some((
+                    account.clone(),
+                    TokenBalance {
+                        mint: balance["mint"].as_str()?.to_string(),
+                        owner: balance["owner"].as_str().unwrap_or_default().to_string(),
+                        amount: token_amount["amount"].as_str()?.parse().ok()?,
+                        decimals: token_amount["decimals"].as_u64().unwrap_or_default() as u8,
+                    },
+                ))
+            })
+            .collect()
+    }
+
+    /// 代币账户交易前或交易后的余额信息，账户在交易中被关闭或创建时只存在其中一个
+    fn balance(&self, account: &str) -> Option<&TokenBalance> {
+        self.post_balances.get(account).or_else(|| self.pre_balances.get(account))
+    }
+
+    /// 代币账户的持有人，未知时返回账户地址本身
+    fn owner_of(&self, account: &str) -> String {
+        self.balance(account)
+            .map(|balance| balance.owner.clone())
+            .filter(|owner| !owner.is_empty())
+            .unwrap_or_else(|| account.to_string())
+    }
+
+    /// 交易手续费支付方，即发起交易的用户
+    fn fee_payer(&self) -> Option<&str> {
+        self.account_keys.first().map(String::as_str)
+    }
+}
+
+/// 指令中的代币数量：`amount`字段或Checked指令的`tokenAmount.amount`
+fn instruction_amount(info: &serde_json::Value) -> Option<u64> {
+    info["amount"]
+        .as_str()
+        .or_else(|| info["tokenAmount"]["amount"].as_str())
+        .and_then(|amount| amount.parse().ok())
+        .or_else(|| info["amount"].as_u64())
+}
+
+/// 交易解析器，解析jsonParsed编码的交易（`getBlock`或`getTransaction`）
 pub struct TransactionParser;
 
 impl TransactionParser {
-    /// 解析代币转账交易
+    /// 解析交易中的全部代币事件，事件标注为获取交易时使用的确认级别
+    pub fn parse_transaction(
+        signature: &str,
+        transaction_data: &serde_json::Value,
+        commitment: Commitment,
+    ) -> AppResult<Vec<TransactionEvent>> {
+        let mut events = Vec::new();
+        events.extend(Self::parse_token_mint(signature, transaction_data, commitment)?);
+        events.extend(Self::parse_token_swap(signature, transaction_data, commitment)?);
+        events.extend(Self::parse_token_transfer(signature, transaction_data, commitment)?);
+        Ok(events)
+    }
+
+    /// 解析代币转账交易：SPL Token和Token-2022的`transfer`与`transferChecked`指令
+    ///
+    /// `transfer`指令不包含代币地址，通过交易前后的代币余额确定代币和双方持有人。
     pub fn parse_token_transfer(
         signature: &str,
         transaction_data: &serde_json::Value,
-    ) -> AppResult<Option<TransactionEvent>> {
-        // 这里应该实现具体的交易解析逻辑
-        // 解析Solana交易的指令数据，识别SPL代币转账
+        commitment: Commitment,
+    ) -> AppResult<Vec<TransactionEvent>> {
+        let Some(tx) = ParsedTransaction::new(signature, transaction_data)? else {
+            return Ok(Vec::new());
+        };
         
-        debug!("解析代币转账交易: {}", signature);
-        Ok(None)
+        let mut events = Vec::new();
+        for (kind, info) in &tx.token_instructions {
+            if *kind != "transfer" && *kind != "transferChecked" {
+                continue;
+            }
+            let (Some(source), Some(destination), Some(amount)) =
+                (info["source"].as_str(), info["destination"].as_str(), instruction_amount(info))
+            else {
+                debug!("忽略无法解析的转账指令: {} - {}", tx.signature, info);
+                continue;
+            };
+            let mint = info["mint"]
+                .as_str()
+                .map(str::to_string)
+                .or_else(|| tx.balance(source).or_else(|| tx.balance(destination)).map(|balance| balance.mint.clone()));
+            let Some(mint) = mint else {
+                debug!("无法确定转账代币: {} - {}", tx.signature, source);
+                continue;
+            };
+            
+            events.push(TransactionEvent::TokenTransfer {
+                signature: tx.signature.to_string(),
+                from: tx.owner_of(source),
+                to: tx.owner_of(destination),
+                mint,
+                amount,
+                slot: tx.slot,
+                commitment,
+                timestamp: tx.timestamp,
+            });
+        }
+        Ok(events)
     }
 
     /// 解析代币交换交易
+    ///
+    /// 按交易发起人的余额变化识别：恰好一种代币减少、另一种代币增加。
+    /// 原生SOL按Wrapped SOL计，已扣除手续费。
     pub fn parse_token_swap(
         signature: &str,
         transaction_data: &serde_json::Value,
+        commitment: Commitment,
     ) -> AppResult<Option<TransactionEvent>> {
-        // 解析DEX交易，如Raydium、Orca等
+        let Some(tx) = ParsedTransaction::new(signature, transaction_data)? else {
+            return Ok(None);
+        };
+        let Some(user) = tx.fee_payer() else {
+            return Ok(None);
+        };
+        
+        let mut deltas: HashMap<&str, i128> = HashMap::new();
+        for balance in tx.post_balances.values().filter(|balance| balance.owner == user) {
+            *deltas.entry(balance.mint.as_str()).or_default() += balance.amount as i128;
+        }
+        for balance in tx.pre_balances.values().filter(|balance| balance.owner == user) {
+            *deltas.entry(balance.mint.as_str()).or_default() -= balance.amount as i128;
+        }
+        deltas.retain(|_, delta| *delta != 0);
         
-        debug!("解析代币交换交易: {}", signature);
-        Ok(None)
+        // 只有一种代币变化时，用原生SOL的变化作为另一侧
+        if deltas.len() == 1 && !deltas.contains_key(NATIVE_MINT) {
+            let lamports = |field: &str| tx.meta[field][0].as_i64().unwrap_or_default() as i128;
+            let fee = tx.meta["fee"].as_i64().unwrap_or_default() as i128;
+            let sol_delta = lamports("postBalances") - lamports("preBalances") + fee;
+            if sol_delta != 0 {
+                deltas.insert(NATIVE_MINT, sol_delta);
+            }
+        }
+        
+        let spent: Vec<(&str, i128)> = deltas.iter().filter(|(_, delta)| **delta < 0).map(|(mint, delta)| (*mint, *delta)).collect();
+        let received: Vec<(&str, i128)> = deltas.iter().filter(|(_, delta)| **delta > 0).map(|(mint, delta)| (*mint, *delta)).collect();
+        let ([(token_in, amount_in)], [(token_out, amount_out)]) = (spent.as_slice(), received.as_slice()) else {
+            return Ok(None);
+        };
+        
+        Ok(Some(TransactionEvent::TokenSwap {
+            signature: tx.signature.to_string(),
+            user: user.to_string(),
+            token_in: token_in.to_string(),
+            token_out: token_out.to_string(),
+            amount_in: amount_in.unsigned_abs() as u64,
+            amount_out: *amount_out as u64,
+            slot: tx.slot,
+            commitment,
+            timestamp: tx.timestamp,
+        }))
     }
 
-    /// 解析代币创建交易
+    /// 解析代币铸造交易：`initializeMint`/`initializeMint2`创建代币，`mintTo`/`mintToChecked`增发
+    ///
+    /// 同一交易内创建并铸造的代币合并为一个事件，供应量为本交易铸造的数量。
     pub fn parse_token_mint(
         signature: &str,
         transaction_data: &serde_json::Value,
-    ) -> AppResult<Option<TransactionEvent>> {
-        // 解析新代币创建交易
+        commitment: Commitment,
+    ) -> AppResult<Vec<TransactionEvent>> {
+        let Some(tx) = ParsedTransaction::new(signature, transaction_data)? else {
+            return Ok(Vec::new());
+        };
+        
+        // 按出现顺序记录代币：(代币, 权限地址, 铸造数量, 精度)
+        let mut mints: Vec<(String, String, u64, Option<u8>)> = Vec::new();
+        for (kind, info) in &tx.token_instructions {
+            let Some(mint) = info["mint"].as_str() else { continue };
+            let authority = info["mintAuthority"].as_str().unwrap_or_default();
+            let (amount, decimals) = match *kind {
+                "initializeMint" | "initializeMint2" => (0, info["decimals"].as_u64().map(|decimals| decimals as u8)),
+                "mintTo" | "mintToChecked" => (
+                    instruction_amount(info).unwrap_or_default(),
+                    info["tokenAmount"]["decimals"].as_u64().map(|decimals| decimals as u8),
+                ),
+                _ => continue,
+            };
+            
+            match mints.iter_mut().find(|(existing, ..)| existing == mint) {
+                Some(entry) => {
+                    entry.2 += amount;
+                    if entry.1.is_empty() {
+                        entry.1 = authority.to_string();
+                    }
+                    entry.3 = entry.3.or(decimals);
+                }
+                None => mints.push((mint.to_string(), authority.to_string(), amount, decimals)),
+            }
+        }
         
-        debug!("解析代币创建交易: {}", signature);
-        Ok(None)
+        Ok(mints
+            .into_iter()
+            .map(|(mint, authority, supply, decimals)| {
+                // mintTo指令不含精度时取交易后的代币余额
+                let decimals = decimals
+                    .or_else(|| tx.post_balances.values().find(|balance| balance.mint == mint).map(|balance| balance.decimals))
+                    .unwrap_or_default();
+                TransactionEvent::TokenMint {
+                    signature: tx.signature.to_string(),
+                    mint,
+                    authority,
+                    supply,
+                    decimals,
+                    slot: tx.slot,
+                    commitment,
+                    timestamp: tx.timestamp,
+                }
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const MINT: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+
+    fn token_balance(index: u64, mint: &str, owner: &str, amount: u64) -> serde_json::Value {
+        json!({
+            "accountIndex": index,
+            "mint": mint,
+            "owner": owner,
+            "uiTokenAmount": { "amount": amount.to_string(), "decimals": 6 }
+        })
+    }
+
+    fn transaction(instructions: serde_json::Value, meta: serde_json::Value) -> serde_json::Value {
+        let mut meta = meta;
+        meta["err"] = serde_json::Value::Null;
+        json!({
+            "slot": 42,
+            "blockTime": 1_700_000_000,
+            "meta": meta,
+            "transaction": {
+                "signatures": ["sig"],
+                "message": {
+                    "accountKeys": [
+                        { "pubkey": "alice" },
+                        { "pubkey": "alice_ata" },
+                        { "pubkey": "bob_ata" },
+                        { "pubkey": "alice_sol_ata" }
+                    ],
+                    "instructions": instructions
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn test_parse_transfer_resolves_mint_and_owners() {
+        let tx = transaction(
+            json!([{
+                "program": "spl-token",
+                "parsed": { "type": "transfer", "info": { "source": "alice_ata", "destination": "bob_ata", "amount": "250", "authority": "alice" } }
+            }]),
+            json!({
+                "preTokenBalances": [token_balance(1, MINT, "alice", 1000), token_balance(2, MINT, "bob", 0)],
+                "postTokenBalances": [token_balance(1, MINT, "alice", 750), token_balance(2, MINT, "bob", 250)]
+            }),
+        );
+
+        let events = TransactionParser::parse_token_transfer("sig", &tx, Commitment::Confirmed).unwrap();
+        assert_eq!(events.len(), 1);
+        let TransactionEvent::TokenTransfer { from, to, mint, amount, slot, .. } = &events[0] else {
+            panic!("expected transfer");
+        };
+        assert_eq!((from.as_str(), to.as_str(), mint.as_str()), ("alice", "bob", MINT));
+        assert_eq!((*amount, *slot), (250, 42));
+    }
+
+    #[test]
+    fn test_parse_swap_from_balance_changes() {
+        let tx = transaction(
+            json!([]),
+            json!({
+                "fee": 5000,
+                "preBalances": [1_000_000_000u64, 0, 0, 0],
+                "postBalances": [999_995_000u64, 0, 0, 0],
+                "preTokenBalances": [token_balance(1, MINT, "alice", 1000), token_balance(3, NATIVE_MINT, "alice", 0)],
+                "postTokenBalances": [token_balance(1, MINT, "alice", 400), token_balance(3, NATIVE_MINT, "alice", 3_000_000)]
+            }),
+        );
+
+        let Some(TransactionEvent::TokenSwap { user, token_in, token_out, amount_in, amount_out, commitment, .. }) =
+            TransactionParser::parse_token_swap("sig", &tx, Commitment::Confirmed).unwrap()
+        else {
+            panic!("expected swap");
+        };
+        assert_eq!((user.as_str(), commitment), ("alice", Commitment::Confirmed));
+        assert_eq!((token_in.as_str(), amount_in), (MINT, 600));
+        assert_eq!((token_out.as_str(), amount_out), (NATIVE_MINT, 3_000_000));
+    }
+
+    #[test]
+    fn test_parse_mint_merges_initialize_and_mint_to() {
+        let tx = transaction(
+            json!([
+                { "program": "spl-token-2022", "parsed": { "type": "initializeMint2", "info": { "mint": MINT, "decimals": 9, "mintAuthority": "alice" } } },
+                { "program": "system", "parsed": { "type": "transfer", "info": {} } }
+            ]),
+            json!({
+                "innerInstructions": [{ "index": 0, "instructions": [
+                    { "program": "spl-token-2022", "parsed": { "type": "mintTo", "info": { "mint": MINT, "account": "alice_ata", "amount": "1000000", "mintAuthority": "alice" } } }
+                ]}]
+            }),
+        );
+
+        let events = TransactionParser::parse_token_mint("sig", &tx, Commitment::Confirmed).unwrap();
+        assert_eq!(events.len(), 1);
+        let TransactionEvent::TokenMint { mint, authority, supply, decimals, .. } = &events[0] else {
+            panic!("expected mint");
+        };
+        assert_eq!((mint.as_str(), authority.as_str()), (MINT, "alice"));
+        assert_eq!((*supply, *decimals), (1_000_000, 9));
+    }
+
+    #[test]
+    fn test_failed_transaction_has_no_events() {
+        let mut tx = transaction(json!([]), json!({}));
+        tx["meta"]["err"] = json!({ "InstructionError": [0, "Custom"] });
+        assert!(TransactionParser::parse_transaction("sig", &tx, Commitment::Confirmed).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_recent_signatures_evicts_oldest() {
+        let seen = RecentSignatures::new();
+        assert!(seen.insert("a"));
+        assert!(!seen.insert("a"));
+        for i in 0..RECENT_SIGNATURE_CAPACITY {
+            seen.insert(&i.to_string());
+        }
+        assert!(seen.insert("a"));
     }
 }
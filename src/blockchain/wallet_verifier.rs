@@ -2,16 +2,371 @@
 //! 
 //! 验证Solana钱包的签名和所有权
 
+use std::fmt;
+use std::str::FromStr;
 use std::sync::Arc;
+use chrono::{DateTime, SecondsFormat, Utc};
 use ed25519_dalek::{Verifier, VerifyingKey, Signature};
 use bs58;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
 use crate::config::Config;
+use crate::repositories::RedisRepository;
 use crate::utils::{AppResult, AppError};
 use tracing::{info, warn, error, debug};
 
+/// 登录挑战有效期（秒）
+pub const CHALLENGE_TTL_SECONDS: usize = 300;
+
 /// 钱包验证器
 pub struct WalletVerifier {
     config: Arc<Config>,
+    redis: RedisRepository,
+}
+
+/// 服务端保存的登录挑战
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoginChallenge {
+    pub nonce: String,
+    pub message: String,
+    pub issued_at: i64,
+}
+
+/// 登录挑战校验错误
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum ChallengeError {
+    #[error("挑战消息格式无效")]
+    Malformed,
+    #[error("挑战消息已过期")]
+    Expired,
+    #[error("挑战消息不存在或与服务端记录不符")]
+    Unknown,
+    #[error("挑战消息已被使用")]
+    Reused,
+    #[error("{0}")]
+    Siws(SiwsError),
+}
+
+impl From<SiwsError> for ChallengeError {
+    fn from(err: SiwsError) -> Self {
+        match err {
+            SiwsError::Parse(_) => ChallengeError::Malformed,
+            SiwsError::Expired => ChallengeError::Expired,
+            other => ChallengeError::Siws(other),
+        }
+    }
+}
+
+/// SIWS消息错误
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum SiwsError {
+    #[error("SIWS消息格式无效: {0}")]
+    Parse(String),
+    #[error("SIWS消息缺少字段: {0}")]
+    MissingField(&'static str),
+    #[error("域名不匹配: {0}")]
+    DomainMismatch(String),
+    #[error("钱包地址不匹配: {0}")]
+    AddressMismatch(String),
+    #[error("链ID不匹配: {0}")]
+    ChainMismatch(String),
+    #[error("URI不匹配: {0}")]
+    UriMismatch(String),
+    #[error("SIWS消息已过期")]
+    Expired,
+    #[error("SIWS消息尚未生效")]
+    NotYetValid,
+}
+
+const SIWS_HEADER_SUFFIX: &str = " wants you to sign in with your Solana account:";
+
+/// Sign-In-With-Solana 结构化消息
+///
+/// 文本格式与钱包标准`createSignInMessageText`一致，Phantom、Solflare、Backpack生成的消息可直接解析。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SiwsMessage {
+    pub domain: String,
+    pub address: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub statement: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uri: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chain_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub issued_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expiration_time: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub not_before: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub resources: Vec<String>,
+}
+
+impl SiwsMessage {
+    /// 创建消息构建器
+    pub fn builder(domain: impl Into<String>, address: impl Into<String>) -> SiwsMessageBuilder {
+        SiwsMessageBuilder {
+            message: SiwsMessage {
+                domain: domain.into(),
+                address: address.into(),
+                statement: None,
+                uri: None,
+                version: Some("1".to_string()),
+                chain_id: None,
+                nonce: None,
+                issued_at: None,
+                expiration_time: None,
+                not_before: None,
+                request_id: None,
+                resources: Vec::new(),
+            },
+        }
+    }
+
+    /// 校验消息的域名、地址、链ID和有效期
+    pub fn validate(
+        &self,
+        domain: &str,
+        address: &str,
+        chain_id: Option<&str>,
+        now: DateTime<Utc>,
+    ) -> Result<(), SiwsError> {
+        if self.domain != domain {
+            return Err(SiwsError::DomainMismatch(self.domain.clone()));
+        }
+        if self.address != address {
+            return Err(SiwsError::AddressMismatch(self.address.clone()));
+        }
+        if let Some(expected) = chain_id {
+            if self.chain_id.as_deref() != Some(expected) {
+                return Err(SiwsError::ChainMismatch(self.chain_id.clone().unwrap_or_default()));
+            }
+        }
+        if matches!(self.expiration_time, Some(expiration) if now >= expiration) {
+            return Err(SiwsError::Expired);
+        }
+        if matches!(self.not_before, Some(not_before) if now < not_before) {
+            return Err(SiwsError::NotYetValid);
+        }
+        Ok(())
+    }
+
+    /// 判断客户端提交的消息是否与服务端签发的挑战一致
+    ///
+    /// 域名和地址由`validate`单独校验，这里比较其余由服务端决定的字段。
+    pub fn matches_issued(&self, issued: &SiwsMessage) -> bool {
+        self.statement == issued.statement
+            && self.uri == issued.uri
+            && self.chain_id == issued.chain_id
+            && self.nonce == issued.nonce
+            && self.issued_at == issued.issued_at
+            && self.expiration_time == issued.expiration_time
+            && self.not_before == issued.not_before
+            && self.request_id == issued.request_id
+            && self.resources == issued.resources
+    }
+}
+
+fn format_siws_time(time: &DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+impl fmt::Display for SiwsMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}\n{}", self.domain, SIWS_HEADER_SUFFIX, self.address)?;
+        if let Some(statement) = &self.statement {
+            write!(f, "\n\n{}", statement)?;
+        }
+
+        let mut fields = Vec::new();
+        if let Some(uri) = &self.uri {
+            fields.push(format!("URI: {}", uri));
+        }
+        if let Some(version) = &self.version {
+            fields.push(format!("Version: {}", version));
+        }
+        if let Some(chain_id) = &self.chain_id {
+            fields.push(format!("Chain ID: {}", chain_id));
+        }
+        if let Some(nonce) = &self.nonce {
+            fields.push(format!("Nonce: {}", nonce));
+        }
+        if let Some(issued_at) = &self.issued_at {
+            fields.push(format!("Issued At: {}", format_siws_time(issued_at)));
+        }
+        if let Some(expiration_time) = &self.expiration_time {
+            fields.push(format!("Expiration Time: {}", format_siws_time(expiration_time)));
+        }
+        if let Some(not_before) = &self.not_before {
+            fields.push(format!("Not Before: {}", format_siws_time(not_before)));
+        }
+        if let Some(request_id) = &self.request_id {
+            fields.push(format!("Request ID: {}", request_id));
+        }
+        if !self.resources.is_empty() {
+            fields.push("Resources:".to_string());
+            fields.extend(self.resources.iter().map(|resource| format!("- {}", resource)));
+        }
+
+        if !fields.is_empty() {
+            write!(f, "\n\n{}", fields.join("\n"))?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for SiwsMessage {
+    type Err = SiwsError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        const FIELD_PREFIXES: [&str; 9] = [
+            "URI: ", "Version: ", "Chain ID: ", "Nonce: ", "Issued At: ",
+            "Expiration Time: ", "Not Before: ", "Request ID: ", "Resources:",
+        ];
+        let parse_time = |value: &str| {
+            DateTime::parse_from_rfc3339(value)
+                .map(|time| time.with_timezone(&Utc))
+                .map_err(|e| SiwsError::Parse(format!("invalid time {}: {}", value, e)))
+        };
+
+        let mut lines = text.lines().peekable();
+        let domain = lines
+            .next()
+            .and_then(|line| line.strip_suffix(SIWS_HEADER_SUFFIX))
+            .filter(|domain| !domain.is_empty())
+            .ok_or_else(|| SiwsError::Parse("missing header".to_string()))?;
+        let address = lines
+            .next()
+            .filter(|address| !address.is_empty())
+            .ok_or_else(|| SiwsError::Parse("missing address".to_string()))?;
+
+        let mut message = SiwsMessage::builder(domain, address).message;
+        message.version = None;
+
+        // 可选的声明段
+        if lines.peek() == Some(&"") {
+            lines.next();
+            if let Some(line) = lines.peek() {
+                if !FIELD_PREFIXES.iter().any(|prefix| line.starts_with(prefix)) {
+                    message.statement = Some(line.to_string());
+                    lines.next();
+                    if lines.next().is_some_and(|line| !line.is_empty()) {
+                        return Err(SiwsError::Parse("statement must be a single line".to_string()));
+                    }
+                }
+            }
+        }
+
+        while let Some(line) = lines.next() {
+            if line == "Resources:" {
+                for resource in lines.by_ref() {
+                    let resource = resource
+                        .strip_prefix("- ")
+                        .ok_or_else(|| SiwsError::Parse(format!("invalid resource line: {}", resource)))?;
+                    message.resources.push(resource.to_string());
+                }
+                break;
+            }
+
+            let (key, value) = line
+                .split_once(": ")
+                .ok_or_else(|| SiwsError::Parse(format!("invalid field line: {}", line)))?;
+            match key {
+                "URI" => message.uri = Some(value.to_string()),
+                "Version" => message.version = Some(value.to_string()),
+                "Chain ID" => message.chain_id = Some(value.to_string()),
+                "Nonce" => message.nonce = Some(value.to_string()),
+                "Issued At" => message.issued_at = Some(parse_time(value)?),
+                "Expiration Time" => message.expiration_time = Some(parse_time(value)?),
+                "Not Before" => message.not_before = Some(parse_time(value)?),
+                "Request ID" => message.request_id = Some(value.to_string()),
+                other => return Err(SiwsError::Parse(format!("unknown field: {}", other))),
+            }
+        }
+
+        Ok(message)
+    }
+}
+
+/// SIWS消息构建器
+#[derive(Debug, Clone)]
+pub struct SiwsMessageBuilder {
+    message: SiwsMessage,
+}
+
+impl SiwsMessageBuilder {
+    pub fn statement(mut self, statement: impl Into<String>) -> Self {
+        self.message.statement = Some(statement.into());
+        self
+    }
+
+    pub fn uri(mut self, uri: impl Into<String>) -> Self {
+        self.message.uri = Some(uri.into());
+        self
+    }
+
+    pub fn chain_id(mut self, chain_id: impl Into<String>) -> Self {
+        self.message.chain_id = Some(chain_id.into());
+        self
+    }
+
+    pub fn nonce(mut self, nonce: impl Into<String>) -> Self {
+        self.message.nonce = Some(nonce.into());
+        self
+    }
+
+    pub fn issued_at(mut self, issued_at: DateTime<Utc>) -> Self {
+        self.message.issued_at = Some(issued_at);
+        self
+    }
+
+    pub fn expiration_time(mut self, expiration_time: DateTime<Utc>) -> Self {
+        self.message.expiration_time = Some(expiration_time);
+        self
+    }
+
+    pub fn not_before(mut self, not_before: DateTime<Utc>) -> Self {
+        self.message.not_before = Some(not_before);
+        self
+    }
+
+    pub fn request_id(mut self, request_id: impl Into<String>) -> Self {
+        self.message.request_id = Some(request_id.into());
+        self
+    }
+
+    pub fn resource(mut self, resource: impl Into<String>) -> Self {
+        self.message.resources.push(resource.into());
+        self
+    }
+
+    /// 构建消息，登录场景要求必须包含随机数和签发时间
+    pub fn build(self) -> Result<SiwsMessage, SiwsError> {
+        if self.message.nonce.is_none() {
+            return Err(SiwsError::MissingField("nonce"));
+        }
+        if self.message.issued_at.is_none() {
+            return Err(SiwsError::MissingField("issuedAt"));
+        }
+        if self.message.statement.as_deref().is_some_and(|statement| statement.contains('\n')) {
+            return Err(SiwsError::Parse("statement must be a single line".to_string()));
+        }
+        Ok(self.message)
+    }
+}
+
+impl From<ChallengeError> for AppError {
+    fn from(err: ChallengeError) -> Self {
+        AppError::wallet_verification_error(err.to_string())
+    }
 }
 
 /// 签名验证请求
@@ -33,8 +388,8 @@ pub struct SignatureVerificationResult {
 
 impl WalletVerifier {
     /// 创建新的钱包验证器
-    pub fn new(config: Arc<Config>) -> AppResult<Self> {
-        Ok(Self { config })
+    pub fn new(config: Arc<Config>, redis: RedisRepository) -> AppResult<Self> {
+        Ok(Self { config, redis })
     }
 
     /// 验证钱包签名
@@ -143,42 +498,64 @@ impl WalletVerifier {
         })
     }
 
-    /// 生成登录挑战消息
-    pub fn generate_login_challenge(&self, wallet_address: &str) -> AppResult<String> {
-        let timestamp = chrono::Utc::now().timestamp();
-        let nonce = uuid::Uuid::new_v4().to_string();
-        
-        let challenge = format!(
-            "请签名此消息以验证您的钱包所有权:\n\n钱包地址: {}\n时间戳: {}\n随机数: {}\n\n此签名仅用于身份验证，不会产生任何费用。",
-            wallet_address,
-            timestamp,
-            nonce
-        );
+    /// 生成SIWS登录挑战，并在Redis中记录随机数
+    pub async fn generate_login_challenge(&self, wallet_address: &str) -> AppResult<SiwsMessage> {
+        let siws = &self.config.siws;
+        let now = Utc::now();
+        let nonce = uuid::Uuid::new_v4().simple().to_string();
+
+        let mut builder = SiwsMessage::builder(&siws.domain, wallet_address)
+            .uri(&siws.uri)
+            .chain_id(&siws.chain_id)
+            .nonce(&nonce)
+            .issued_at(now)
+            .expiration_time(now + chrono::Duration::seconds(CHALLENGE_TTL_SECONDS as i64));
+        if let Some(statement) = &siws.statement {
+            builder = builder.statement(statement);
+        }
+        let message = builder.build().map_err(ChallengeError::from)?;
+
+        let record = LoginChallenge {
+            nonce,
+            message: message.to_string(),
+            issued_at: now.timestamp(),
+        };
+        self.redis
+            .store_login_challenge(wallet_address, &record.nonce, &record, CHALLENGE_TTL_SECONDS)
+            .await?;
 
         debug!("生成登录挑战: {}", wallet_address);
-        Ok(challenge)
+        Ok(message)
     }
 
-    /// 验证登录挑战
-    pub fn verify_login_challenge(
+    /// 验证SIWS登录挑战
+    ///
+    /// 消息须绑定配置的域名、URI与链ID，各字段与服务端签发的挑战一致且未过期；签名验证通过后挑战即被消费，不可重放。
+    pub async fn verify_login_challenge(
         &self,
         wallet_address: &str,
         challenge: &str,
         signature: &str,
     ) -> AppResult<SignatureVerificationResult> {
-        // 检查挑战消息的时效性（5分钟内有效）
-        if let Some(timestamp_str) = self.extract_timestamp_from_challenge(challenge) {
-            if let Ok(timestamp) = timestamp_str.parse::<i64>() {
-                let now = chrono::Utc::now().timestamp();
-                if now - timestamp > 300 { // 5分钟
-                    return Ok(SignatureVerificationResult {
-                        is_valid: false,
-                        wallet_address: wallet_address.to_string(),
-                        message: challenge.to_string(),
-                        error: Some("挑战消息已过期".to_string()),
-                    });
-                }
-            }
+        let message: SiwsMessage = challenge.parse().map_err(ChallengeError::from)?;
+        let nonce = message.nonce.clone().ok_or(ChallengeError::Malformed)?;
+        let issued_at = message.issued_at.ok_or(ChallengeError::Malformed)?;
+
+        if self.redis.is_challenge_used(&nonce).await? {
+            return Err(ChallengeError::Reused.into());
+        }
+
+        // 检查域名绑定和时效性
+        let siws = &self.config.siws;
+        let now = Utc::now();
+        message
+            .validate(&siws.domain, wallet_address, Some(&siws.chain_id), now)
+            .map_err(ChallengeError::from)?;
+        if message.uri.as_deref() != Some(siws.uri.as_str()) {
+            return Err(ChallengeError::from(SiwsError::UriMismatch(message.uri.clone().unwrap_or_default())).into());
+        }
+        if (now - issued_at).num_seconds() > CHALLENGE_TTL_SECONDS as i64 {
+            return Err(ChallengeError::Expired.into());
         }
 
         // 验证签名
@@ -187,18 +564,28 @@ impl WalletVerifier {
             message: challenge.to_string(),
             signature: signature.to_string(),
         };
+        let result = self.verify_signature(&request)?;
+        if !result.is_valid {
+            return Ok(result);
+        }
 
-        self.verify_signature(&request)
-    }
-
-    /// 从挑战消息中提取时间戳
-    fn extract_timestamp_from_challenge(&self, challenge: &str) -> Option<String> {
-        for line in challenge.lines() {
-            if line.starts_with("时间戳: ") {
-                return Some(line.replace("时间戳: ", ""));
+        // 原子地消费服务端记录，并发重放只有一个能拿到
+        let record: Option<LoginChallenge> = self.redis.take_login_challenge(wallet_address, &nonce).await?;
+        let record = match record {
+            Some(record) => record,
+            None if self.redis.is_challenge_used(&nonce).await? => {
+                return Err(ChallengeError::Reused.into());
             }
+            None => return Err(ChallengeError::Unknown.into()),
+        };
+        let issued: SiwsMessage = record.message.parse().map_err(|_| ChallengeError::Unknown)?;
+        if record.nonce != nonce || !message.matches_issued(&issued) {
+            return Err(ChallengeError::Unknown.into());
         }
-        None
+
+        self.redis.mark_challenge_used(&nonce, CHALLENGE_TTL_SECONDS).await?;
+
+        Ok(result)
     }
 
     /// 验证钱包是否拥有特定代币
@@ -288,6 +675,16 @@ impl WalletType {
             WalletType::Other(user_agent.to_string())
         }
     }
+
+    /// 钱包类型名称，`Other`不携带原始User-Agent
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WalletType::Phantom => "phantom",
+            WalletType::Solflare => "solflare",
+            WalletType::Backpack => "backpack",
+            WalletType::Other(_) => "other",
+        }
+    }
 }
 
 /// 钱包连接信息
@@ -331,3 +728,85 @@ impl WalletConnection {
         duration.num_minutes() > timeout_minutes
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_message() -> SiwsMessage {
+        let issued_at = DateTime::parse_from_rfc3339("2024-01-01T00:00:00.000Z").unwrap().with_timezone(&Utc);
+        SiwsMessage::builder("watermelo.io", "7xKXtg2CW87d97TXJSDpbD5jBkheTqA83TZRuJosgAsU")
+            .statement("Sign in to watermelo.io")
+            .uri("https://watermelo.io")
+            .chain_id("mainnet")
+            .nonce("3f9a1c2b7d")
+            .issued_at(issued_at)
+            .expiration_time(issued_at + chrono::Duration::minutes(5))
+            .resource("https://watermelo.io/terms")
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_siws_roundtrip() {
+        let message = sample_message();
+        let text = message.to_string();
+        assert!(text.starts_with("watermelo.io wants you to sign in with your Solana account:\n7xKX"));
+        assert!(text.contains("\n\nSign in to watermelo.io\n\nURI: https://watermelo.io\nVersion: 1\nChain ID: mainnet\n"));
+        assert!(text.contains("Issued At: 2024-01-01T00:00:00.000Z"));
+        assert_eq!(text.parse::<SiwsMessage>().unwrap(), message);
+    }
+
+    #[test]
+    fn test_siws_parse_without_statement() {
+        let text = "watermelo.io wants you to sign in with your Solana account:\nabc\n\nURI: https://watermelo.io\nNonce: 12345678";
+        let message: SiwsMessage = text.parse().unwrap();
+        assert_eq!(message.statement, None);
+        assert_eq!(message.nonce.as_deref(), Some("12345678"));
+        assert_eq!(message.to_string(), text);
+        assert!("not a siws message".parse::<SiwsMessage>().is_err());
+    }
+
+    #[test]
+    fn test_siws_validate() {
+        let message = sample_message();
+        let address = message.address.clone();
+        let now = message.issued_at.unwrap() + chrono::Duration::minutes(1);
+
+        assert!(message.validate("watermelo.io", &address, Some("mainnet"), now).is_ok());
+        assert_eq!(
+            message.validate("phishing.example", &address, Some("mainnet"), now),
+            Err(SiwsError::DomainMismatch("watermelo.io".to_string()))
+        );
+        assert!(matches!(message.validate("watermelo.io", "other", None, now), Err(SiwsError::AddressMismatch(_))));
+        assert!(matches!(message.validate("watermelo.io", &address, Some("devnet"), now), Err(SiwsError::ChainMismatch(_))));
+        assert_eq!(
+            message.validate("watermelo.io", &address, None, now + chrono::Duration::minutes(10)),
+            Err(SiwsError::Expired)
+        );
+    }
+
+    #[test]
+    fn test_siws_matches_issued() {
+        let issued = sample_message();
+        assert!(issued.to_string().parse::<SiwsMessage>().unwrap().matches_issued(&issued));
+
+        let mut tampered = issued.clone();
+        tampered.uri = Some("https://phishing.example".to_string());
+        assert!(!tampered.matches_issued(&issued));
+
+        let mut tampered = issued.clone();
+        tampered.statement = Some("Approve all transfers".to_string());
+        assert!(!tampered.matches_issued(&issued));
+
+        let mut tampered = issued.clone();
+        tampered.resources.push("https://phishing.example/claim".to_string());
+        assert!(!tampered.matches_issued(&issued));
+    }
+
+    #[test]
+    fn test_siws_builder_requires_nonce() {
+        let result = SiwsMessage::builder("watermelo.io", "abc").issued_at(Utc::now()).build();
+        assert_eq!(result, Err(SiwsError::MissingField("nonce")));
+    }
+}
@@ -0,0 +1,527 @@
+//! Solana WebSocket订阅客户端
+//!
+//! 支持`slotSubscribe`、`logsSubscribe`（按mentions过滤）和`accountSubscribe`。
+//! 断线后按指数退避轮换节点重连，并自动恢复全部订阅；通知按类型分发到各自的广播通道。
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use futures::{SinkExt, StreamExt};
+use rand::Rng;
+use serde::Serialize;
+use serde_json::{json, Value};
+use tokio::net::TcpStream;
+use tokio::sync::{broadcast, mpsc, watch, Mutex, RwLock};
+use tokio_tungstenite::{connect_async, tungstenite::Message, MaybeTlsStream, WebSocketStream};
+use crate::config::Config;
+use crate::blockchain::{redact_endpoint_url, redact_url_in, Commitment};
+use tracing::{info, warn, debug};
+
+/// 首次重连等待时间（毫秒）
+const INITIAL_BACKOFF_MS: u64 = 500;
+/// 最长重连等待时间（毫秒）
+const MAX_BACKOFF_MS: u64 = 30_000;
+/// 心跳间隔（秒）
+const PING_INTERVAL_SECONDS: u64 = 30;
+/// 超过该时间没有收到任何消息视为连接失效（秒）
+const IDLE_TIMEOUT_SECONDS: u64 = 60;
+/// 每种通知的广播通道容量，消费者落后超过该数量会丢失最早的通知
+const CHANNEL_CAPACITY: usize = 1024;
+/// 交易日志订阅的确认级别，与按签名获取交易的级别一致
+const LOGS_COMMITMENT: Commitment = Commitment::Confirmed;
+/// 账户订阅的确认级别，池子状态只用于实时展示，优先低延迟
+const ACCOUNT_COMMITMENT: Commitment = Commitment::Processed;
+
+type WsStream = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// 订阅类型
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Subscription {
+    /// 新区块
+    Slot,
+    /// 提及指定地址（程序或账户）的交易日志
+    Logs { mention: String },
+    /// 账户数据变化
+    Account { pubkey: String },
+}
+
+impl Subscription {
+    /// 订阅请求
+    fn request(&self, id: u64) -> Value {
+        match self {
+            Subscription::Slot => json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "method": "slotSubscribe"
+            }),
+            Subscription::Logs { mention } => json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "method": "logsSubscribe",
+                "params": [
+                    { "mentions": [mention] },
+                    { "commitment": LOGS_COMMITMENT.as_str() }
+                ]
+            }),
+            Subscription::Account { pubkey } => json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "method": "accountSubscribe",
+                "params": [
+                    pubkey,
+                    { "encoding": "base64", "commitment": ACCOUNT_COMMITMENT.as_str() }
+                ]
+            }),
+        }
+    }
+}
+
+/// 新区块通知
+#[derive(Debug, Clone, Serialize)]
+pub struct SlotUpdate {
+    pub slot: u64,
+    pub parent: u64,
+    pub root: u64,
+}
+
+/// 交易日志通知
+#[derive(Debug, Clone, Serialize)]
+pub struct LogsUpdate {
+    /// 触发通知的订阅地址
+    pub mention: String,
+    pub slot: u64,
+    pub signature: String,
+    /// 交易执行失败时的错误
+    pub err: Option<Value>,
+    pub logs: Vec<String>,
+}
+
+/// 账户变化通知
+#[derive(Debug, Clone, Serialize)]
+pub struct AccountUpdate {
+    pub pubkey: String,
+    pub slot: u64,
+    pub lamports: u64,
+    pub owner: String,
+    #[serde(skip)]
+    pub data: Vec<u8>,
+    pub executable: bool,
+}
+
+/// 已解析的通知
+#[derive(Debug, Clone)]
+enum Update {
+    Slot(SlotUpdate),
+    Logs(LogsUpdate),
+    Account(AccountUpdate),
+}
+
+/// 节点推送的消息
+#[derive(Debug, PartialEq)]
+enum Incoming {
+    /// 订阅请求成功，返回订阅ID
+    Subscribed { request_id: u64, subscription_id: u64 },
+    /// 订阅请求失败
+    Rejected { request_id: u64, message: String },
+    /// 订阅通知
+    Notification { subscription_id: u64, result: Value },
+    Other,
+}
+
+fn parse_message(text: &str) -> Incoming {
+    let Ok(message) = serde_json::from_str::<Value>(text) else {
+        return Incoming::Other;
+    };
+
+    if let Some(request_id) = message["id"].as_u64() {
+        if let Some(subscription_id) = message["result"].as_u64() {
+            return Incoming::Subscribed { request_id, subscription_id };
+        }
+        if !message["error"].is_null() {
+            let error = &message["error"];
+            let message = error["message"].as_str().map_or_else(|| error.to_string(), str::to_string);
+            return Incoming::Rejected { request_id, message };
+        }
+    }
+
+    match message["params"]["subscription"].as_u64() {
+        Some(subscription_id) => Incoming::Notification {
+            subscription_id,
+            result: message["params"]["result"].clone(),
+        },
+        None => Incoming::Other,
+    }
+}
+
+/// 按订阅类型解析通知内容
+fn parse_update(subscription: &Subscription, result: &Value) -> Option<Update> {
+    match subscription {
+        Subscription::Slot => Some(Update::Slot(SlotUpdate {
+            slot: result["slot"].as_u64()?,
+            parent: result["parent"].as_u64().unwrap_or_default(),
+            root: result["root"].as_u64().unwrap_or_default(),
+        })),
+        Subscription::Logs { mention } => {
+            let value = &result["value"];
+            Some(Update::Logs(LogsUpdate {
+                mention: mention.clone(),
+                slot: result["context"]["slot"].as_u64().unwrap_or_default(),
+                signature: value["signature"].as_str()?.to_string(),
+                err: Some(value["err"].clone()).filter(|err| !err.is_null()),
+                logs: value["logs"]
+                    .as_array()
+                    .map(|logs| logs.iter().filter_map(|log| log.as_str().map(str::to_string)).collect())
+                    .unwrap_or_default(),
+            }))
+        }
+        Subscription::Account { pubkey } => {
+            let value = &result["value"];
+            let data = value["data"][0].as_str().and_then(|data| STANDARD.decode(data).ok()).unwrap_or_default();
+            Some(Update::Account(AccountUpdate {
+                pubkey: pubkey.clone(),
+                slot: result["context"]["slot"].as_u64().unwrap_or_default(),
+                lamports: value["lamports"].as_u64()?,
+                owner: value["owner"].as_str().unwrap_or_default().to_string(),
+                data,
+                executable: value["executable"].as_bool().unwrap_or_default(),
+            }))
+        }
+    }
+}
+
+/// 订阅客户端状态
+#[derive(Debug, Clone, Serialize)]
+pub struct WsClientStatus {
+    pub enabled: bool,
+    pub connected: bool,
+    pub url: Option<String>,
+    pub subscriptions: usize,
+    pub reconnects: u64,
+}
+
+/// Solana WebSocket订阅客户端
+pub struct SolanaWsClient {
+    urls: Vec<String>,
+    /// 需要保持的订阅，重连后全部重新订阅
+    subscriptions: RwLock<Vec<Subscription>>,
+    commands: mpsc::UnboundedSender<Subscription>,
+    command_rx: Mutex<Option<mpsc::UnboundedReceiver<Subscription>>>,
+    slot_tx: broadcast::Sender<SlotUpdate>,
+    logs_tx: broadcast::Sender<LogsUpdate>,
+    account_tx: broadcast::Sender<AccountUpdate>,
+    connected_url: std::sync::RwLock<Option<String>>,
+    connected: AtomicBool,
+    reconnects: AtomicU64,
+    shutdown: watch::Sender<bool>,
+}
+
+impl SolanaWsClient {
+    /// 按配置创建客户端：`solana.ws_url`在前，其后为`sol_endpoint.wss`中的其他节点
+    pub fn from_config(config: &Config) -> Self {
+        let mut urls: Vec<String> = config.solana.ws_url.iter().cloned().collect();
+        urls.extend(config.sol_endpoint.wss.iter().cloned());
+        Self::new(urls)
+    }
+
+    /// 创建客户端，重复和空的地址会被忽略；没有地址时客户端不可用
+    pub fn new(urls: Vec<String>) -> Self {
+        let mut unique: Vec<String> = Vec::new();
+        for url in urls {
+            let url = url.trim().to_string();
+            if !url.is_empty() && !unique.contains(&url) {
+                unique.push(url);
+            }
+        }
+
+        let (commands, command_rx) = mpsc::unbounded_channel();
+        Self {
+            urls: unique,
+            subscriptions: RwLock::new(Vec::new()),
+            commands,
+            command_rx: Mutex::new(Some(command_rx)),
+            slot_tx: broadcast::channel(CHANNEL_CAPACITY).0,
+            logs_tx: broadcast::channel(CHANNEL_CAPACITY).0,
+            account_tx: broadcast::channel(CHANNEL_CAPACITY).0,
+            connected_url: std::sync::RwLock::new(None),
+            connected: AtomicBool::new(false),
+            reconnects: AtomicU64::new(0),
+            shutdown: watch::channel(false).0,
+        }
+    }
+
+    /// 是否配置了WebSocket节点
+    pub fn is_enabled(&self) -> bool {
+        !self.urls.is_empty()
+    }
+
+    /// 当前是否已连接
+    pub fn is_connected(&self) -> bool {
+        self.connected.load(Ordering::Relaxed)
+    }
+
+    /// 添加订阅，已连接时立即订阅，否则在连接建立后订阅
+    pub async fn subscribe(&self, subscription: Subscription) {
+        let mut subscriptions = self.subscriptions.write().await;
+        if subscriptions.contains(&subscription) {
+            return;
+        }
+        subscriptions.push(subscription.clone());
+        let _ = self.commands.send(subscription);
+    }
+
+    /// 新区块通知
+    pub fn slot_updates(&self) -> broadcast::Receiver<SlotUpdate> {
+        self.slot_tx.subscribe()
+    }
+
+    /// 所有日志订阅的通知，按`mention`区分来源
+    pub fn logs_updates(&self) -> broadcast::Receiver<LogsUpdate> {
+        self.logs_tx.subscribe()
+    }
+
+    /// 所有账户订阅的通知
+    pub fn account_updates(&self) -> broadcast::Receiver<AccountUpdate> {
+        self.account_tx.subscribe()
+    }
+
+    /// 启动后台连接任务，已在运行时忽略
+    pub async fn start(self: &Arc<Self>) {
+        if !self.is_enabled() {
+            warn!("未配置Solana WebSocket节点，订阅客户端未启动");
+            return;
+        }
+        let Some(commands) = self.command_rx.lock().await.take() else {
+            return;
+        };
+
+        self.shutdown.send_replace(false);
+        let client = self.clone();
+        tokio::spawn(async move {
+            let commands = client.run(commands).await;
+            *client.command_rx.lock().await = Some(commands);
+            info!("Solana WebSocket订阅客户端已停止");
+        });
+    }
+
+    /// 停止后台连接任务
+    pub fn stop(&self) {
+        self.shutdown.send_replace(true);
+    }
+
+    /// 当前状态
+    pub async fn status(&self) -> WsClientStatus {
+        let subscriptions = self.subscriptions.read().await.len();
+        WsClientStatus {
+            enabled: self.is_enabled(),
+            connected: self.is_connected(),
+            url: self.connected_url.read().unwrap().as_deref().map(redact_endpoint_url),
+            subscriptions,
+            reconnects: self.reconnects.load(Ordering::Relaxed),
+        }
+    }
+
+    /// 连接循环，停止后归还命令通道以便再次启动
+    async fn run(&self, mut commands: mpsc::UnboundedReceiver<Subscription>) -> mpsc::UnboundedReceiver<Subscription> {
+        let mut shutdown = self.shutdown.subscribe();
+        let mut backoff = INITIAL_BACKOFF_MS;
+        let mut attempt = 0usize;
+
+        while !*shutdown.borrow() {
+            let url = &self.urls[attempt % self.urls.len()];
+            let host = redact_endpoint_url(url);
+            attempt += 1;
+
+            match connect_async(url.as_str()).await {
+                Ok((stream, _)) => {
+                    info!("Solana WebSocket已连接: {}", host);
+                    *self.connected_url.write().unwrap() = Some(url.clone());
+                    self.connected.store(true, Ordering::Relaxed);
+
+                    let started = Instant::now();
+                    if let Err(e) = self.run_connection(stream, &mut commands, &mut shutdown).await {
+                        warn!("Solana WebSocket连接断开: {} - {}", host, redact_url_in(&e.to_string(), url, &host));
+                    }
+
+                    self.connected.store(false, Ordering::Relaxed);
+                    *self.connected_url.write().unwrap() = None;
+                    // 连接保持过一段时间才重置退避，避免连上即断时频繁重连
+                    if started.elapsed() > Duration::from_secs(IDLE_TIMEOUT_SECONDS) {
+                        backoff = INITIAL_BACKOFF_MS;
+                    }
+                }
+                Err(e) => warn!("Solana WebSocket连接失败: {} - {}", host, redact_url_in(&e.to_string(), url, &host)),
+            }
+
+            if *shutdown.borrow() {
+                break;
+            }
+            self.reconnects.fetch_add(1, Ordering::Relaxed);
+
+            let delay = backoff / 2 + rand::thread_rng().gen_range(0..=backoff / 2);
+            tokio::select! {
+                _ = tokio::time::sleep(Duration::from_millis(delay)) => {}
+                _ = shutdown.changed() => {}
+            }
+            backoff = (backoff * 2).min(MAX_BACKOFF_MS);
+        }
+
+        commands
+    }
+
+    /// 处理单个连接，正常停止时返回`Ok`，连接失效时返回原因
+    async fn run_connection(
+        &self,
+        stream: WsStream,
+        commands: &mut mpsc::UnboundedReceiver<Subscription>,
+        shutdown: &mut watch::Receiver<bool>,
+    ) -> Result<(), String> {
+        let (mut sink, mut source) = stream.split();
+        let mut next_id = 1u64;
+        let mut pending: HashMap<u64, Subscription> = HashMap::new();
+        let mut active: HashMap<u64, Subscription> = HashMap::new();
+
+        // 断线期间新增的订阅已包含在订阅列表中
+        while commands.try_recv().is_ok() {}
+
+        let subscriptions = self.subscriptions.read().await.clone();
+        for subscription in subscriptions {
+            Self::send_subscribe(&mut sink, &mut pending, &mut next_id, subscription).await?;
+        }
+
+        let mut ping = tokio::time::interval(Duration::from_secs(PING_INTERVAL_SECONDS));
+        ping.tick().await;
+
+        loop {
+            tokio::select! {
+                message = tokio::time::timeout(Duration::from_secs(IDLE_TIMEOUT_SECONDS), source.next()) => {
+                    match message {
+                        Err(_) => return Err("长时间未收到消息".to_string()),
+                        Ok(None) => return Err("连接已关闭".to_string()),
+                        Ok(Some(Err(e))) => return Err(e.to_string()),
+                        Ok(Some(Ok(Message::Text(text)))) => self.handle_text(&text, &mut pending, &mut active),
+                        Ok(Some(Ok(Message::Close(frame)))) => return Err(format!("节点关闭连接: {:?}", frame)),
+                        Ok(Some(Ok(_))) => {}
+                    }
+                }
+                Some(subscription) = commands.recv() => {
+                    Self::send_subscribe(&mut sink, &mut pending, &mut next_id, subscription).await?;
+                }
+                _ = ping.tick() => {
+                    sink.send(Message::Ping(Vec::new())).await.map_err(|e| e.to_string())?;
+                }
+                _ = shutdown.changed() => {
+                    let _ = sink.send(Message::Close(None)).await;
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    async fn send_subscribe(
+        sink: &mut futures::stream::SplitSink<WsStream, Message>,
+        pending: &mut HashMap<u64, Subscription>,
+        next_id: &mut u64,
+        subscription: Subscription,
+    ) -> Result<(), String> {
+        let id = *next_id;
+        *next_id += 1;
+        debug!("发送订阅请求: {:?} - {}", subscription, id);
+
+        sink.send(Message::Text(subscription.request(id).to_string()))
+            .await
+            .map_err(|e| e.to_string())?;
+        pending.insert(id, subscription);
+        Ok(())
+    }
+
+    fn handle_text(&self, text: &str, pending: &mut HashMap<u64, Subscription>, active: &mut HashMap<u64, Subscription>) {
+        match parse_message(text) {
+            Incoming::Subscribed { request_id, subscription_id } => {
+                if let Some(subscription) = pending.remove(&request_id) {
+                    active.insert(subscription_id, subscription);
+                }
+            }
+            Incoming::Rejected { request_id, message } => {
+                if let Some(subscription) = pending.remove(&request_id) {
+                    warn!("订阅失败: {:?} - {}", subscription, message);
+                }
+            }
+            Incoming::Notification { subscription_id, result } => {
+                let Some(update) = active.get(&subscription_id).and_then(|subscription| parse_update(subscription, &result)) else {
+                    return;
+                };
+                // 没有消费者时发送失败，忽略即可
+                match update {
+                    Update::Slot(update) => {
+                        let _ = self.slot_tx.send(update);
+                    }
+                    Update::Logs(update) => {
+                        let _ = self.logs_tx.send(update);
+                    }
+                    Update::Account(update) => {
+                        let _ = self.account_tx.send(update);
+                    }
+                }
+            }
+            Incoming::Other => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_subscription_responses() {
+        assert_eq!(
+            parse_message(r#"{"jsonrpc":"2.0","result":23784,"id":3}"#),
+            Incoming::Subscribed { request_id: 3, subscription_id: 23784 }
+        );
+        assert_eq!(
+            parse_message(r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Invalid param"},"id":4}"#),
+            Incoming::Rejected { request_id: 4, message: "Invalid param".to_string() }
+        );
+        assert_eq!(parse_message("not json"), Incoming::Other);
+    }
+
+    #[test]
+    fn test_parse_logs_notification() {
+        let text = r#"{"jsonrpc":"2.0","method":"logsNotification","params":{"result":{"context":{"slot":5208469},
+            "value":{"signature":"5h6x","err":null,"logs":["Program log: swap"]}},"subscription":24040}}"#;
+        let Incoming::Notification { subscription_id, result } = parse_message(text) else {
+            panic!("expected notification");
+        };
+        assert_eq!(subscription_id, 24040);
+
+        let subscription = Subscription::Logs { mention: "program".to_string() };
+        let Some(Update::Logs(update)) = parse_update(&subscription, &result) else {
+            panic!("expected logs update");
+        };
+        assert_eq!(update.slot, 5208469);
+        assert_eq!(update.signature, "5h6x");
+        assert_eq!(update.err, None);
+        assert_eq!(update.logs, ["Program log: swap"]);
+    }
+
+    #[test]
+    fn test_parse_slot_and_account_updates() {
+        let slot = parse_update(&Subscription::Slot, &json!({ "parent": 75, "root": 44, "slot": 76 }));
+        assert!(matches!(slot, Some(Update::Slot(SlotUpdate { slot: 76, parent: 75, root: 44 }))));
+
+        let account = parse_update(
+            &Subscription::Account { pubkey: "pool".to_string() },
+            &json!({
+                "context": { "slot": 5199307 },
+                "value": { "data": ["AQID", "base64"], "executable": false, "lamports": 33594, "owner": "11111111111111111111111111111111" }
+            }),
+        );
+        let Some(Update::Account(update)) = account else {
+            panic!("expected account update");
+        };
+        assert_eq!(update.data, [1, 2, 3]);
+        assert_eq!(update.lamports, 33594);
+    }
+}
@@ -17,10 +17,17 @@ pub struct Config {
     pub oklink: OklinkConfig,
     pub gecko: GeckoConfig,
     pub mailgun: MailgunConfig,
+    pub mail_transport: Option<String>, // mailgun 或 file
     pub commission: CommissionConfig,
     pub commission_wallet: HashMap<String, String>,
     pub static_space: Option<String>,
     pub sign_message: Option<String>,
+    #[serde(default)]
+    pub siws: SiwsConfig, // Sign-In-With-Solana 登录消息配置
+    #[serde(default)]
+    pub two_factor: TwoFactorConfig,
+    #[serde(default)]
+    pub rate_limit: RateLimitConfig, // 按路由组的限流策略
     pub draw_wallet: HashMap<String, String>,
     pub draw_min: HashMap<String, f64>,
     pub smartdaili: SmartdailiConfig,
@@ -42,6 +49,8 @@ pub struct DatabaseConfig {
     pub password: String,
     pub database: String,
     pub charset: String,
+    #[serde(default)]
+    pub auto_migrate: bool, // 启动时执行migrations目录中的迁移，默认关闭，生产环境应单独执行
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -63,6 +72,10 @@ pub struct SolEndpointConfig {
     pub http: Vec<String>,
     pub wss: Vec<String>,
     pub rpc_limit: u32,
+    pub health_check_seconds: Option<u64>, // RPC节点健康检查间隔，默认30秒
+    pub rpc_queue_timeout_ms: Option<u64>, // 超出rpc_limit时排队的最长时间，默认2000毫秒
+    #[serde(default)]
+    pub method_weights: HashMap<String, u32>, // 覆盖内置的RPC方法权重
 }
 
 /// 新的Solana配置结构
@@ -90,13 +103,80 @@ pub struct MonitoringConfig {
     pub enabled: bool,
     pub watch_addresses: Vec<String>,
     pub watch_tokens: Vec<String>,
-    pub block_check_interval_seconds: u64,
+    #[serde(default)]
+    pub watch_pools: Vec<String>, // 通过accountSubscribe监听的池子账户
+    pub block_check_interval_seconds: u64, // WebSocket断开时轮询区块高度的间隔
+    pub max_catch_up_slots: Option<u64>, // 重启后最多追赶的区块数，默认216000（约一天）
+    #[serde(default)]
+    pub event_pipeline: EventPipelineConfig, // 事件队列和输出端
+}
+
+/// 事件队列满时的处理策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OverflowPolicy {
+    /// 等待队列有空位，监听随之减速
+    Block,
+    /// 丢弃新事件
+    DropNewest,
+    /// 丢弃队列中最早的事件
+    DropOldest,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct EventPipelineConfig {
+    /// 监听和分发之间的队列容量
+    pub channel_capacity: usize,
+    pub overflow: OverflowPolicy,
+    /// 每个输出端的待投递队列容量，满时事件直接进入死信
+    pub sink_queue_capacity: usize,
+    /// 单个事件的最大投递次数，超过后进入死信
+    pub max_attempts: u32,
+    /// 首次重试的等待时间（毫秒），之后每次翻倍
+    pub retry_backoff_ms: u64,
+    /// 每个输出端保留的死信数量
+    pub dead_letter_max_len: usize,
+    /// 写入交易表、持仓和统计
+    pub mysql_sink: bool,
+    /// 按事件输出日志
+    pub log_sink: bool,
+    /// Redis Stream名称，为空时不启用
+    pub redis_stream: Option<String>,
+    /// Redis Stream保留的近似长度
+    pub redis_stream_max_len: usize,
+    /// 事件推送地址，为空时不启用
+    pub webhook_url: Option<String>,
+    /// 推送签名密钥，设置后请求头`X-Signature`为请求体的HMAC-SHA256
+    pub webhook_secret: Option<String>,
+}
+
+impl Default for EventPipelineConfig {
+    fn default() -> Self {
+        Self {
+            channel_capacity: 10_000,
+            overflow: OverflowPolicy::Block,
+            sink_queue_capacity: 10_000,
+            max_attempts: 5,
+            retry_backoff_ms: 500,
+            dead_letter_max_len: 10_000,
+            mysql_sink: true,
+            log_sink: true,
+            redis_stream: None,
+            redis_stream_max_len: 100_000,
+            webhook_url: None,
+            webhook_secret: None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct JwtTokenConfig {
     pub sign: String,
+    /// 访问令牌有效期（秒）
     pub expire: i64,
+    /// 刷新令牌有效期（秒）
+    pub refresh_expire: Option<i64>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -121,6 +201,78 @@ pub struct MailgunConfig {
     pub sender: String,
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SiwsConfig {
+    pub domain: String,
+    pub uri: String,
+    pub chain_id: String, // mainnet / devnet / testnet
+    pub statement: Option<String>,
+}
+
+impl Default for SiwsConfig {
+    fn default() -> Self {
+        Self {
+            domain: "watermelo.io".to_string(),
+            uri: "https://watermelo.io".to_string(),
+            chain_id: "mainnet".to_string(),
+            statement: Some("Sign in to watermelo.io".to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TwoFactorConfig {
+    /// 验证器App中显示的发行方
+    pub issuer: String,
+    /// TOTP密钥的加密密钥，必须独立于jwt_token.sign配置；为空时无法开启双重认证，
+    /// 已有用户保存了密钥时服务拒绝启动
+    pub encryption_key: String,
+}
+
+impl Default for TwoFactorConfig {
+    fn default() -> Self {
+        Self {
+            issuer: "watermelo.io".to_string(),
+            encryption_key: String::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct RateLimitConfig {
+    pub enabled: bool,
+    /// 路由组名 -> 限流策略，未配置的路由组不限流
+    pub policies: HashMap<String, RateLimitPolicy>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+pub struct RateLimitPolicy {
+    /// 窗口内允许的请求数
+    pub limit: u32,
+    /// 滑动窗口长度（秒）
+    pub window_seconds: u64,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        let policies = [
+            ("auth", 10, 60),
+            ("password_reset", 5, 3600),
+            ("account", 120, 60),
+            ("market", 600, 60),
+        ]
+        .into_iter()
+        .map(|(name, limit, window_seconds)| (name.to_string(), RateLimitPolicy { limit, window_seconds }))
+        .collect();
+
+        Self {
+            enabled: true,
+            policies,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct CommissionConfig {
     pub dex: f64,
@@ -169,7 +321,6 @@ impl Config {
         
         // 设置默认值（类似Go版本的逻辑）
         config.jwt_token.sign = "jincancan".to_string();
-        config.jwt_token.expire = 86400 * 365;
         config.static_space = Some("./space".to_string());
         config.sign_message = Some("watermelo.io".to_string());
         
@@ -215,6 +366,7 @@ impl Default for Config {
                 password: "password".to_string(),
                 database: "dexx".to_string(),
                 charset: "utf8mb4".to_string(),
+                auto_migrate: false,
             },
             redis: RedisConfig {
                 host: "localhost:6379".to_string(),
@@ -230,6 +382,9 @@ impl Default for Config {
                 http: vec!["https://api.mainnet-beta.solana.com".to_string()],
                 wss: vec!["wss://api.mainnet-beta.solana.com".to_string()],
                 rpc_limit: 100,
+                health_check_seconds: Some(30),
+                rpc_queue_timeout_ms: Some(2000),
+                method_weights: HashMap::new(),
             },
             solana: SolanaConfig {
                 rpc_url: "https://api.mainnet-beta.solana.com".to_string(),
@@ -247,13 +402,17 @@ impl Default for Config {
                     enabled: false,
                     watch_addresses: vec![],
                     watch_tokens: vec![],
+                    watch_pools: vec![],
                     block_check_interval_seconds: 5,
+                    max_catch_up_slots: None,
+                    event_pipeline: EventPipelineConfig::default(),
                 },
             },
             mailslurp_key: vec![],
             jwt_token: JwtTokenConfig {
                 sign: "jincancan".to_string(),
-                expire: 86400 * 365,
+                expire: 7200, // 2小时
+                refresh_expire: Some(86400 * 30),
             },
             oklink: OklinkConfig {
                 api_limit: 100,
@@ -269,6 +428,7 @@ impl Default for Config {
                 domain: "".to_string(),
                 sender: "".to_string(),
             },
+            mail_transport: None,
             commission: CommissionConfig {
                 dex: 0.0,
                 l1: 0.0,
@@ -277,6 +437,9 @@ impl Default for Config {
             commission_wallet: HashMap::new(),
             static_space: Some("./space".to_string()),
             sign_message: Some("watermelo.io".to_string()),
+            siws: SiwsConfig::default(),
+            two_factor: TwoFactorConfig::default(),
+            rate_limit: RateLimitConfig::default(),
             draw_wallet: HashMap::new(),
             draw_min: HashMap::new(),
             smartdaili: SmartdailiConfig {
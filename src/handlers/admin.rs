@@ -0,0 +1,138 @@
+use std::collections::HashMap;
+use axum::{extract::State, Json};
+use serde::Deserialize;
+use crate::blockchain::{DeadLetterReplay, PriceData};
+use crate::config::CommissionConfig;
+use crate::models::audit_log::ClientContext;
+use crate::handlers::{response::*, AppState, AuthUser};
+use crate::services::{
+    AuditLogListResponse, BackfillRequest, BackfillSummary, PriceRefreshRequest, ReplayDeadLettersRequest, SetUserRoleRequest,
+    UserListResponse,
+};
+use crate::utils::{AppError, AppResult};
+
+/// 分页请求
+#[derive(Debug, Deserialize)]
+pub struct AdminPageRequest {
+    pub page: Option<u32>,
+    pub limit: Option<u32>,
+}
+
+/// 删除用户请求
+#[derive(Debug, Deserialize)]
+pub struct AdminDeleteUserRequest {
+    pub user_id: u32,
+}
+
+/// 用户列表处理器
+pub async fn admin_users(
+    State(state): State<AppState>,
+    Json(req): Json<AdminPageRequest>,
+) -> AppResult<ApiResponse<UserListResponse>> {
+    let users = state.services.admin_service().list_users(req.page, req.limit).await?;
+    Ok(success(users))
+}
+
+/// 删除用户处理器
+pub async fn admin_delete_user(
+    State(state): State<AppState>,
+    AuthUser { user, .. }: AuthUser,
+    client: ClientContext,
+    Json(req): Json<AdminDeleteUserRequest>,
+) -> AppResult<ApiResponse<()>> {
+    state.services.admin_service().delete_user(&user, req.user_id, &client).await?;
+    Ok(success_empty())
+}
+
+/// 设置用户角色处理器
+pub async fn admin_set_user_role(
+    State(state): State<AppState>,
+    AuthUser { user, .. }: AuthUser,
+    client: ClientContext,
+    Json(req): Json<SetUserRoleRequest>,
+) -> AppResult<ApiResponse<()>> {
+    state.services.admin_service().set_user_role(&user, req, &client).await?;
+    Ok(success_empty())
+}
+
+/// 佣金比例处理器
+pub async fn admin_commission_rates(
+    State(state): State<AppState>,
+) -> AppResult<ApiResponse<CommissionConfig>> {
+    Ok(success(state.services.admin_service().commission_rates()))
+}
+
+/// 刷新代币价格处理器
+pub async fn admin_price_refresh(
+    State(state): State<AppState>,
+    AuthUser { user, .. }: AuthUser,
+    client: ClientContext,
+    Json(req): Json<PriceRefreshRequest>,
+) -> AppResult<ApiResponse<HashMap<String, PriceData>>> {
+    let blockchain_services = state
+        .blockchain_services
+        .as_ref()
+        .ok_or_else(|| AppError::internal("Blockchain services are disabled"))?;
+
+    let prices = state
+        .services
+        .admin_service()
+        .refresh_prices(&user, &blockchain_services.price_service, req, &client)
+        .await?;
+    Ok(success(prices))
+}
+
+/// 回填历史交易处理器
+pub async fn admin_backfill(
+    State(state): State<AppState>,
+    AuthUser { user, .. }: AuthUser,
+    client: ClientContext,
+    Json(req): Json<BackfillRequest>,
+) -> AppResult<ApiResponse<BackfillSummary>> {
+    let blockchain_services = state
+        .blockchain_services
+        .as_ref()
+        .ok_or_else(|| AppError::internal("Blockchain services are disabled"))?;
+
+    let summary = state
+        .services
+        .admin_service()
+        .backfill_transactions(
+            &user,
+            state.services.solana_service(),
+            &blockchain_services.solana_client,
+            req,
+            &client,
+        )
+        .await?;
+    Ok(success(summary))
+}
+
+/// 重放事件管道死信处理器
+pub async fn admin_replay_dead_letters(
+    State(state): State<AppState>,
+    AuthUser { user, .. }: AuthUser,
+    client: ClientContext,
+    Json(req): Json<ReplayDeadLettersRequest>,
+) -> AppResult<ApiResponse<DeadLetterReplay>> {
+    let blockchain_services = state
+        .blockchain_services
+        .as_ref()
+        .ok_or_else(|| AppError::internal("Blockchain services are disabled"))?;
+
+    let replay = state
+        .services
+        .admin_service()
+        .replay_dead_letters(&user, &blockchain_services.event_pipeline, req, &client)
+        .await?;
+    Ok(success(replay))
+}
+
+/// 审计日志处理器
+pub async fn admin_audit_log(
+    State(state): State<AppState>,
+    Json(req): Json<AdminPageRequest>,
+) -> AppResult<ApiResponse<AuditLogListResponse>> {
+    let logs = state.services.admin_service().audit_logs(req.page, req.limit).await?;
+    Ok(success(logs))
+}
@@ -1,17 +1,113 @@
 use axum::response::IntoResponse;
+use std::net::{IpAddr, SocketAddr};
 use axum::{
-    extract::Request,
-    http::{HeaderMap, StatusCode},
+    async_trait,
+    extract::{ConnectInfo, FromRequestParts, Request, State},
+    http::{header, request::Parts, Extensions, HeaderMap, HeaderValue, StatusCode},
     middleware::Next,
     response::Response,
     Json,
 };
-use crate::handlers::response::{error, error_codes};
-use crate::utils::CryptoUtils;
-use crate::models::user::User;
+use crate::handlers::response::{error, error_codes, ApiResponse};
+use crate::handlers::AppState;
+use crate::utils::{AppError, AppResult, Claims, CryptoUtils};
+use crate::models::api_key::ApiKeyScope;
+use crate::models::audit_log::ClientContext;
+use crate::models::user::{User, UserRole};
+use crate::services::ApiKeyGrant;
+
+/// 认证凭据
+#[derive(Debug, Clone)]
+pub enum Credential {
+    /// 登录会话（JWT）
+    Session(Claims),
+    /// API密钥
+    ApiKey(ApiKeyGrant),
+}
+
+impl Credential {
+    /// 登录会话ID，API密钥认证时为空
+    pub fn session_id(&self) -> Option<&str> {
+        match self {
+            Self::Session(claims) => Some(&claims.jti),
+            Self::ApiKey(_) => None,
+        }
+    }
+
+    /// 登录会话拥有全部权限，API密钥只拥有创建时授予的权限
+    pub fn has_scope(&self, scope: ApiKeyScope) -> bool {
+        match self {
+            Self::Session(_) => true,
+            Self::ApiKey(grant) => grant.has_scope(scope),
+        }
+    }
+}
+
+/// 校验JWT token并确认其会话未被注销，返回对应的用户
+async fn authenticate_token(state: &AppState, token: &str) -> Result<(User, Claims), Response> {
+    // 验证JWT token
+    let claims = match CryptoUtils::verify_jwt(token, &state.config.jwt_token.sign) {
+        Ok(claims) => claims,
+        Err(_) => {
+            return Err(Json(error(error_codes::TOKEN_EXPIRED, "Token is invalid or expired")).into_response());
+        }
+    };
+
+    let Ok(uid) = claims.sub.parse::<u32>() else {
+        return Err(Json(error(error_codes::TOKEN_ERROR, "Token is invalid")).into_response());
+    };
+
+    // 检查会话是否已被注销
+    match state.services.user_service().is_session_active(uid, &claims.jti).await {
+        Ok(true) => {}
+        Ok(false) => {
+            return Err(Json(error(error_codes::TOKEN_EXPIRED, "Session has been revoked")).into_response());
+        }
+        Err(err) => {
+            tracing::error!("Failed to check session for user {}: {:?}", uid, err);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR.into_response());
+        }
+    }
+
+    // 加载当前用户
+    match state.services.user_service().user_info(uid).await {
+        Ok(user) => Ok((user, claims)),
+        Err(AppError::Validation { .. }) => {
+            Err(Json(error(error_codes::TOKEN_ERROR, "User not found")).into_response())
+        }
+        Err(err) => {
+            tracing::error!("Failed to load user {}: {:?}", uid, err);
+            Err(StatusCode::INTERNAL_SERVER_ERROR.into_response())
+        }
+    }
+}
+
+/// 使用`X-API-Key`认证，返回对应的用户和权限
+async fn authenticate_api_key(
+    state: &AppState,
+    key: &str,
+    client_ip: Option<IpAddr>,
+) -> Result<(User, ApiKeyGrant), Response> {
+    match state.services.api_key_service().authenticate(key, client_ip).await {
+        Ok(result) => Ok(result),
+        Err(AppError::Authentication { message }) => {
+            Err(Json(error(error_codes::UNAUTHORIZED, message)).into_response())
+        }
+        Err(AppError::Authorization { message }) => {
+            Err(Json(error(error_codes::FORBIDDEN, message)).into_response())
+        }
+        Err(err) => {
+            tracing::error!("Failed to authenticate API key: {:?}", err);
+            Err(StatusCode::INTERNAL_SERVER_ERROR.into_response())
+        }
+    }
+}
 
 /// JWT认证中间件
+///
+/// 使用配置中的密钥校验token并确认其会话未被注销，然后将当前`User`和会话凭据写入请求扩展。
 pub async fn jwt_middleware(
+    State(state): State<AppState>,
     headers: HeaderMap,
     mut request: Request,
     next: Next,
@@ -26,23 +122,156 @@ pub async fn jwt_middleware(
         return Ok(Json(error(error_codes::TOKEN_ERROR, "Token is required")).into_response());
     }
 
-    // 这里需要从配置中获取JWT密钥，暂时硬编码
-    // TODO: 从AppState中获取配置
-    let jwt_secret = "your-jwt-secret"; // 临时占位符
+    let (user, claims) = match authenticate_token(&state, token).await {
+        Ok(result) => result,
+        Err(response) => return Ok(response),
+    };
 
-    // 验证JWT token
-    match CryptoUtils::verify_jwt(token, jwt_secret) {
-        Ok(claims) => {
-            // 将用户信息添加到请求扩展中
-            // 这里需要根据claims创建User对象
-            // TODO: 实现从claims到User的转换
-            request.extensions_mut().insert(claims);
-            Ok(next.run(request).await)
+    request.extensions_mut().insert(user);
+    request.extensions_mut().insert(Credential::Session(claims));
+    Ok(next.run(request).await)
+}
+
+/// API认证中间件
+///
+/// 请求携带`X-API-Key`或`token`时校验凭据并写入当前用户，凭据无效则拒绝；未携带凭据的请求按匿名处理。
+pub async fn api_auth_middleware(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let api_key = headers.get("x-api-key").and_then(|value| value.to_str().ok());
+    let token = headers.get("token").and_then(|value| value.to_str().ok());
+
+    let authenticated = match (api_key, token) {
+        (Some(key), _) if !key.is_empty() => {
+            let client_ip = client_ip(&headers, request.extensions());
+            authenticate_api_key(&state, key, client_ip)
+                .await
+                .map(|(user, grant)| Some((user, Credential::ApiKey(grant))))
         }
-        Err(_) => {
-            Ok(Json(error(error_codes::TOKEN_EXPIRED, "Token is invalid or expired")).into_response())
+        (_, Some(token)) if !token.is_empty() => authenticate_token(&state, token)
+            .await
+            .map(|(user, claims)| Some((user, Credential::Session(claims)))),
+        _ => Ok(None),
+    };
+
+    match authenticated {
+        Ok(Some((user, credential))) => {
+            request.extensions_mut().insert(user);
+            request.extensions_mut().insert(credential);
+        }
+        Ok(None) => {}
+        Err(response) => return response,
+    }
+    next.run(request).await
+}
+
+/// API密钥权限检查，需放在`api_auth_middleware`之后；登录会话和匿名请求不受限制
+pub async fn require_scope(scope: ApiKeyScope, request: Request, next: Next) -> Response {
+    if let Some(credential) = request.extensions().get::<Credential>() {
+        if !credential.has_scope(scope) {
+            if let Credential::ApiKey(grant) = credential {
+                tracing::warn!("API key {} denied: missing scope {}", grant.key_id, scope.as_str());
+            }
+            let message = format!("API key lacks the {} scope", scope.as_str());
+            return Json(error(error_codes::FORBIDDEN, message)).into_response();
         }
     }
+    next.run(request).await
+}
+
+/// 角色检查，需放在`jwt_middleware`或`api_auth_middleware`之后；匿名请求和角色不足的用户都会被拒绝
+pub async fn require_role(role: UserRole, request: Request, next: Next) -> Response {
+    let Some(user) = request.extensions().get::<User>() else {
+        return Json(error(error_codes::UNAUTHORIZED, "Authentication required")).into_response();
+    };
+    if user.role() < role {
+        tracing::warn!("User {} denied: requires role {}", user.id, role.as_str());
+        return Json(error(error_codes::FORBIDDEN, "Insufficient permissions")).into_response();
+    }
+    next.run(request).await
+}
+
+/// 获取客户端地址，仅信任本机或内网反向代理转发的`X-Forwarded-For`/`X-Real-IP`
+pub fn client_ip(headers: &HeaderMap, extensions: &Extensions) -> Option<IpAddr> {
+    let peer = extensions
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip());
+
+    let trusted_proxy = match peer {
+        Some(IpAddr::V4(ip)) => ip.is_loopback() || ip.is_private(),
+        Some(IpAddr::V6(ip)) => ip.is_loopback() || (ip.segments()[0] & 0xfe00) == 0xfc00,
+        None => false,
+    };
+    if !trusted_proxy {
+        return peer;
+    }
+
+    let forwarded = headers
+        .get("x-forwarded-for")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(',').next())
+        .or_else(|| headers.get("x-real-ip").and_then(|value| value.to_str().ok()))
+        .and_then(|value| value.trim().parse().ok());
+    forwarded.or(peer)
+}
+
+/// 已认证用户提取器，需配合`jwt_middleware`或`api_auth_middleware`使用
+#[derive(Debug, Clone)]
+pub struct AuthUser {
+    pub user: User,
+    pub credential: Credential,
+}
+
+impl AuthUser {
+    /// 当前登录会话ID，通过API密钥认证时返回错误
+    pub fn session_id(&self) -> AppResult<&str> {
+        self.credential
+            .session_id()
+            .ok_or_else(|| AppError::authentication("Login session required"))
+    }
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for AuthUser
+where
+    S: Send + Sync,
+{
+    type Rejection = ApiResponse<()>;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let user = parts.extensions.get::<User>().cloned();
+        let credential = parts.extensions.get::<Credential>().cloned();
+
+        match (user, credential) {
+            (Some(user), Some(credential)) => Ok(Self { user, credential }),
+            _ => Err(error(error_codes::UNAUTHORIZED, "Authentication required")),
+        }
+    }
+}
+
+/// 请求来源提取器，用于审计日志
+#[async_trait]
+impl<S> FromRequestParts<S> for ClientContext
+where
+    S: Send + Sync,
+{
+    type Rejection = std::convert::Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let user_agent = parts
+            .headers
+            .get(header::USER_AGENT)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+
+        Ok(Self {
+            ip: client_ip(&parts.headers, &parts.extensions),
+            user_agent,
+        })
+    }
 }
 
 /// CORS中间件
@@ -106,14 +335,54 @@ pub async fn error_handling_middleware(
     response
 }
 
-/// 限流中间件（简单实现）
+/// 限流中间件
+///
+/// 按`config.rate_limit`中`policy`对应的策略计数：优先按API密钥，其次按登录用户，最后按客户端IP。
+/// 需放在认证中间件之后才能识别密钥和用户；超限返回`429`和`Retry-After`，所有响应都带`X-RateLimit-*`。
+/// Redis不可用时放行请求。
 pub async fn rate_limit_middleware(
+    State(state): State<AppState>,
+    policy: &'static str,
     request: Request,
     next: Next,
-) -> Result<Response, StatusCode> {
-    // TODO: 实现基于Redis的限流逻辑
-    // 这里暂时直接通过
-    Ok(next.run(request).await)
+) -> Response {
+    let identity = match (request.extensions().get::<Credential>(), request.extensions().get::<User>()) {
+        (Some(Credential::ApiKey(grant)), _) => format!("key:{}", grant.key_id),
+        (_, Some(user)) => format!("user:{}", user.id),
+        _ => match client_ip(request.headers(), request.extensions()) {
+            Some(ip) => format!("ip:{}", ip),
+            None => "ip:unknown".to_string(),
+        },
+    };
+
+    let status = match state.services.rate_limit_service().check(policy, &identity).await {
+        Ok(Some(status)) => status,
+        Ok(None) => return next.run(request).await,
+        Err(err) => {
+            tracing::error!("Rate limit check failed for {} {}: {:?}", policy, identity, err);
+            return next.run(request).await;
+        }
+    };
+
+    let reset_seconds = status.reset_after_ms.div_ceil(1000).max(1);
+    let mut response = if status.allowed {
+        next.run(request).await
+    } else {
+        tracing::warn!("Rate limit {} exceeded by {}", policy, identity);
+        let mut response = (
+            StatusCode::TOO_MANY_REQUESTS,
+            Json(error(error_codes::TOO_MANY_REQUESTS, "Too many requests")),
+        )
+            .into_response();
+        response.headers_mut().insert(header::RETRY_AFTER, HeaderValue::from(reset_seconds));
+        response
+    };
+
+    let headers = response.headers_mut();
+    headers.insert("x-ratelimit-limit", HeaderValue::from(status.limit));
+    headers.insert("x-ratelimit-remaining", HeaderValue::from(status.remaining));
+    headers.insert("x-ratelimit-reset", HeaderValue::from(reset_seconds));
+    response
 }
 
 /// 从请求扩展中获取用户信息的辅助函数
@@ -123,16 +392,98 @@ pub fn get_user_from_request(request: &Request) -> Option<&User> {
 
 #[cfg(test)]
 mod tests {
-    
-    
+    use super::*;
+    use axum::{body::Body, routing::get, Router};
+    use tower::ServiceExt;
+    use crate::handlers::response::success_empty;
+
+    /// 经过中间件后返回的业务码，放行时为`SUCCESS`
+    async fn response_code(router: Router, extensions: Extensions) -> i32 {
+        let mut request = Request::new(Body::empty());
+        *request.extensions_mut() = extensions;
+        let response = router.oneshot(request).await.unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        serde_json::from_slice::<serde_json::Value>(&body).unwrap()["code"].as_i64().unwrap() as i32
+    }
+
+    fn scoped_router(scope: ApiKeyScope) -> Router {
+        Router::new()
+            .route("/", get(|| async { success_empty() }))
+            .route_layer(axum::middleware::from_fn(move |request, next| require_scope(scope, request, next)))
+    }
+
+    fn role_router(role: UserRole) -> Router {
+        Router::new()
+            .route("/", get(|| async { success_empty() }))
+            .route_layer(axum::middleware::from_fn(move |request, next| require_role(role, request, next)))
+    }
+
+    fn with<T: Clone + Send + Sync + 'static>(value: T) -> Extensions {
+        let mut extensions = Extensions::new();
+        extensions.insert(value);
+        extensions
+    }
+
+    fn api_key(scopes: Vec<ApiKeyScope>) -> Credential {
+        Credential::ApiKey(ApiKeyGrant { key_id: 1, scopes })
+    }
+
+    fn session() -> Credential {
+        Credential::Session(Claims {
+            sub: "1".to_string(),
+            exp: 0,
+            iat: 0,
+            email: None,
+            wallet: None,
+            jti: "session".to_string(),
+        })
+    }
+
+    fn user(role: UserRole) -> User {
+        let mut user = User::new("alice".to_string(), "alice@example.com".to_string(), String::new());
+        user.role = role.as_str().to_string();
+        user
+    }
+
+    fn peer(ip: &str) -> Extensions {
+        with(ConnectInfo(SocketAddr::new(ip.parse().unwrap(), 443)))
+    }
 
     #[tokio::test]
-    async fn test_cors_middleware() {
-        // TODO: 实现CORS中间件测试
+    async fn test_require_scope() {
+        let router = scoped_router(ApiKeyScope::WriteMarket);
+        let granted = api_key(vec![ApiKeyScope::ReadMarket, ApiKeyScope::WriteMarket]);
+        assert_eq!(response_code(router.clone(), with(granted)).await, error_codes::SUCCESS);
+        assert_eq!(response_code(router.clone(), with(session())).await, error_codes::SUCCESS);
+
+        let missing = api_key(vec![ApiKeyScope::ReadMarket]);
+        assert_eq!(response_code(router, with(missing)).await, error_codes::FORBIDDEN);
     }
 
     #[tokio::test]
-    async fn test_jwt_middleware() {
-        // TODO: 实现JWT中间件测试
+    async fn test_require_role() {
+        let router = role_router(UserRole::Operator);
+        assert_eq!(response_code(router.clone(), with(user(UserRole::Admin))).await, error_codes::SUCCESS);
+        assert_eq!(response_code(router.clone(), with(user(UserRole::Operator))).await, error_codes::SUCCESS);
+        assert_eq!(response_code(router.clone(), with(user(UserRole::User))).await, error_codes::FORBIDDEN);
+        assert_eq!(response_code(router, Extensions::new()).await, error_codes::UNAUTHORIZED);
+    }
+
+    #[test]
+    fn test_client_ip() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-forwarded-for", HeaderValue::from_static("203.0.113.7, 10.0.0.1"));
+
+        // 来自内网代理时使用转发的地址，公网直连时忽略伪造的头
+        let forwarded: IpAddr = "203.0.113.7".parse().unwrap();
+        assert_eq!(client_ip(&headers, &peer("10.0.0.2")), Some(forwarded));
+        assert_eq!(client_ip(&headers, &peer("::1")), Some(forwarded));
+        assert_eq!(client_ip(&headers, &peer("198.51.100.1")), Some("198.51.100.1".parse().unwrap()));
+        assert_eq!(client_ip(&headers, &Extensions::new()), None);
+
+        let mut headers = HeaderMap::new();
+        headers.insert("x-real-ip", HeaderValue::from_static("203.0.113.8"));
+        assert_eq!(client_ip(&headers, &peer("127.0.0.1")), Some("203.0.113.8".parse().unwrap()));
+        assert_eq!(client_ip(&HeaderMap::new(), &peer("127.0.0.1")), Some("127.0.0.1".parse().unwrap()));
     }
 }
@@ -10,14 +10,18 @@ use serde_json::{json, Value};
 use crate::config::Config;
 use crate::services::ServicesImpl;
 use crate::blockchain::BlockchainServices;
+use crate::models::api_key::ApiKeyScope;
+use crate::models::user::UserRole;
 
 pub mod user;
 pub mod solana;
+pub mod admin;
 pub mod middleware;
 pub mod response;
 
 pub use user::*;
 pub use solana::*;
+pub use admin::*;
 pub use middleware::*;
 
 /// API状态结构
@@ -36,10 +40,13 @@ pub fn create_routes(state: AppState) -> Router {
         .route("/", get(root_handler))
         
         // 用户相关路由
-        .nest("/user", user_routes())
+        .nest("/user", user_routes(state.clone()))
+        
+        // 管理后台路由
+        .nest("/admin", admin_routes(state.clone()))
         
         // Solana相关路由 (v2 API)
-        .nest("/v2/solana", solana_routes())
+        .nest("/v2/solana", solana_routes(state.clone()))
         
         // 应用状态
         .with_state(state)
@@ -74,6 +81,12 @@ async fn health_check(State(state): State<AppState>) -> Result<Json<Value>, Stat
                 services_status["solana_rpc"] = json!("disconnected");
             }
         }
+        let rpc_pool = blockchain_services.solana_client.rpc_pool();
+        services_status["rpc_endpoints"] = json!({
+            "healthy": rpc_pool.healthy_count(),
+            "total": rpc_pool.statuses().len()
+        });
+        services_status["websocket"] = json!(if blockchain_services.ws_client.is_connected() { "connected" } else { "disconnected" });
 
         // 检查价格服务缓存状态
         let (total_cached, fresh_cached) = blockchain_services.price_service.get_cache_stats().await;
@@ -99,43 +112,135 @@ async fn health_check(State(state): State<AppState>) -> Result<Json<Value>, Stat
 }
 
 /// 用户路由
-fn user_routes() -> Router<AppState> {
-    Router::new()
+fn user_routes(state: AppState) -> Router<AppState> {
+    // 需要登录的路由
+    let protected = Router::new()
+        .route("/userinfo", post(user_info))
+        .route("/updPwd", post(user_update_password))
+        .route("/editUsername", post(user_edit_username))
+        .route("/logout", post(user_logout))
+        .route("/logoutAll", post(user_logout_all))
+        .route("/wallets", post(user_wallets))
+        .route("/linkWallet", post(user_link_wallet))
+        .route("/unlinkWallet", post(user_unlink_wallet))
+        .route("/bindEmail", post(user_bind_email))
+        .route("/2fa/setup", post(user_two_factor_setup))
+        .route("/2fa/enable", post(user_two_factor_enable))
+        .route("/2fa/disable", post(user_two_factor_disable))
+        .route("/securityLog", post(user_security_log))
+        .route("/apiKeys", post(user_api_keys))
+        .route("/apiKey/create", post(user_create_api_key))
+        .route("/apiKey/revoke", post(user_revoke_api_key))
+        .route_layer(axum::middleware::from_fn_with_state(state.clone(), |state, request, next| {
+            rate_limit_middleware(state, "account", request, next)
+        }))
+        .route_layer(axum::middleware::from_fn_with_state(state.clone(), jwt_middleware));
+
+    // 登录注册
+    let auth = Router::new()
+        .route("/walletChallenge", post(user_wallet_challenge))
         .route("/walletLogin", post(user_wallet_login))
         .route("/emailLogin", post(user_email_login))
+        .route("/2fa/login", post(user_two_factor_login))
+        .route("/refresh", post(user_refresh_token))
         .route("/reg", post(user_register))
-        .route("/userinfo", post(user_info).layer(axum::middleware::from_fn_with_state(
-            (), jwt_middleware
-        )))
-        .route("/updPwd", post(user_update_password).layer(axum::middleware::from_fn_with_state(
-            (), jwt_middleware
-        )))
-        .route("/editUsername", post(user_edit_username).layer(axum::middleware::from_fn_with_state(
-            (), jwt_middleware
-        )))
+        .route_layer(axum::middleware::from_fn_with_state(state.clone(), |state, request, next| {
+            rate_limit_middleware(state, "auth", request, next)
+        }));
+
+    // 找回密码
+    let password_reset = Router::new()
         .route("/findPwd", post(user_find_password))
+        .route("/resetPwd", post(user_reset_password))
+        .route_layer(axum::middleware::from_fn_with_state(state, |state, request, next| {
+            rate_limit_middleware(state, "password_reset", request, next)
+        }));
+
+    Router::new()
+        .merge(auth)
+        .merge(password_reset)
+        .merge(protected)
 }
 
-/// Solana路由
-fn solana_routes() -> Router<AppState> {
+/// 管理后台路由
+///
+/// 仅接受登录会话；查询类接口开放给运营，修改类接口仅限管理员。
+fn admin_routes(state: AppState) -> Router<AppState> {
+    let operator = Router::new()
+        .route("/users", post(admin_users))
+        .route("/commissionRates", post(admin_commission_rates))
+        .route("/priceRefresh", post(admin_price_refresh))
+        .route_layer(axum::middleware::from_fn(|request, next| {
+            require_role(UserRole::Operator, request, next)
+        }));
+
+    let admin = Router::new()
+        .route("/user/delete", post(admin_delete_user))
+        .route("/user/role", post(admin_set_user_role))
+        .route("/auditLog", post(admin_audit_log))
+        .route("/backfill", post(admin_backfill))
+        .route("/replayDeadLetters", post(admin_replay_dead_letters))
+        .route_layer(axum::middleware::from_fn(|request, next| {
+            require_role(UserRole::Admin, request, next)
+        }));
+
     Router::new()
+        .merge(operator)
+        .merge(admin)
+        .route_layer(axum::middleware::from_fn_with_state(state, jwt_middleware))
+}
+
+/// Solana路由
+///
+/// 行情等数据可匿名访问，携带`X-API-Key`时按密钥权限限制可访问的路由；Webhook和价格更新仅限运营及以上角色，
+/// 通过API密钥调用时还需要`write-market`权限。
+fn solana_routes(state: AppState) -> Router<AppState> {
+    // 行情数据
+    let market = Router::new()
         .route("/tokenInfo", post(solana_token_info))
         .route("/tokenPrice", post(solana_token_price))
         .route("/search", post(solana_search))
         .route("/rank", post(solana_rank))
         .route("/tokenHolder", post(solana_token_holder))
         .route("/tradelatest", post(solana_trade_latest))
-        .route("/walletPosition", post(solana_wallet_position))
-        .route("/tokenPosition", post(solana_token_position))
         .route("/multiTokenInfo", post(solana_multi_token_info))
         .route("/transactionVolume", post(solana_transaction_volume))
         .route("/dailyTransactionVolume", post(solana_daily_transaction_volume))
+        .route_layer(axum::middleware::from_fn(|request, next| {
+            require_scope(ApiKeyScope::ReadMarket, request, next)
+        }));
+
+    // 持仓数据
+    let positions = Router::new()
+        .route("/walletPosition", post(solana_wallet_position))
+        .route("/tokenPosition", post(solana_token_position))
+        .route_layer(axum::middleware::from_fn(|request, next| {
+            require_scope(ApiKeyScope::ReadPositions, request, next)
+        }));
+
+    // 运维操作，仅限运营及以上角色
+    let operator = Router::new()
         // Webhook路由
         .route("/webhook_v1", post(solana_webhook_v1))
+        .route("/priceUpdate", post(solana_price_update))
+        .route_layer(axum::middleware::from_fn(|request, next| {
+            require_scope(ApiKeyScope::WriteMarket, request, next)
+        }))
+        .route_layer(axum::middleware::from_fn(|request, next| {
+            require_role(UserRole::Operator, request, next)
+        }));
+
+    Router::new()
+        .merge(market)
+        .merge(positions)
+        .merge(operator)
         // 新增区块链相关路由
         .route("/walletVerify", post(solana_wallet_verify))
         .route("/generateChallenge", post(solana_generate_challenge))
         .route("/verifyChallenge", post(solana_verify_challenge))
         .route("/blockchainStatus", get(solana_blockchain_status))
-        .route("/priceUpdate", post(solana_price_update))
+        .route_layer(axum::middleware::from_fn_with_state(state.clone(), |state, request, next| {
+            rate_limit_middleware(state, "market", request, next)
+        }))
+        .layer(axum::middleware::from_fn_with_state(state, api_auth_middleware))
 }
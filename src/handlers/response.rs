@@ -152,6 +152,7 @@ pub mod error_codes {
     pub const UNAUTHORIZED: i32 = 401;
     pub const FORBIDDEN: i32 = 403;
     pub const NOT_FOUND: i32 = 404;
+    pub const TOO_MANY_REQUESTS: i32 = 429;
     pub const INTERNAL_ERROR: i32 = 500;
     pub const TOKEN_ERROR: i32 = 1001;
     pub const TOKEN_EXPIRED: i32 = 1002;
@@ -6,10 +6,12 @@ use axum::{
 use serde::Deserialize;
 use std::collections::HashMap;
 use crate::handlers::{response::*, AppState};
+use crate::models::audit_log::{AuditOutcome, ClientContext, NewAuditLog, EVENT_WALLET_SIGNATURE};
 use crate::utils::AppResult;
 use crate::utils::AppError;
 use crate::models::solana::*;
 use crate::models::trade::Trade;
+use crate::blockchain::redact_endpoint_url;
 
 /// 代币信息请求
 #[derive(Debug, Deserialize)]
@@ -154,8 +156,14 @@ pub async fn solana_wallet_position(
     State(state): State<AppState>,
     Json(req): Json<WalletPositionRequest>,
 ) -> AppResult<Json<ApiResponse<Vec<Position>>>> {
-    // TODO: 实现钱包持仓逻辑
-    Ok(Json(success(vec![])))
+    let blockchain_services = state.blockchain_services
+        .as_ref()
+        .ok_or_else(|| AppError::internal("区块链服务未启用"))?;
+
+    let positions = state.services.solana_service()
+        .wallet_positions(&blockchain_services.solana_client, &req.wallet)
+        .await?;
+    Ok(Json(success(positions)))
 }
 
 /// 代币持仓处理器
@@ -163,8 +171,14 @@ pub async fn solana_token_position(
     State(state): State<AppState>,
     Json(req): Json<TokenPositionRequest>,
 ) -> AppResult<Json<ApiResponse<Option<Position>>>> {
-    // TODO: 实现代币持仓逻辑
-    Ok(Json(success(None)))
+    let blockchain_services = state.blockchain_services
+        .as_ref()
+        .ok_or_else(|| AppError::internal("区块链服务未启用"))?;
+
+    let position = state.services.solana_service()
+        .token_position(&blockchain_services.solana_client, &req.wallet, &req.mint)
+        .await?;
+    Ok(Json(success(position)))
 }
 
 /// 多代币信息处理器
@@ -172,8 +186,14 @@ pub async fn solana_multi_token_info(
     State(state): State<AppState>,
     Json(req): Json<MultiTokenInfoRequest>,
 ) -> AppResult<Json<ApiResponse<Vec<SolToken>>>> {
-    // TODO: 实现多代币信息逻辑
-    Ok(Json(success(vec![])))
+    let blockchain_services = state.blockchain_services
+        .as_ref()
+        .ok_or_else(|| AppError::internal("区块链服务未启用"))?;
+
+    let tokens = state.services.solana_service()
+        .multi_token_info(&blockchain_services.solana_client, &req.mints)
+        .await?;
+    Ok(Json(success(tokens)))
 }
 
 /// 交易量统计处理器
@@ -223,6 +243,7 @@ mod tests {
 /// 钱包验证处理器
 pub async fn solana_wallet_verify(
     State(state): State<AppState>,
+    client: ClientContext,
     Json(req): Json<serde_json::Value>,
 ) -> AppResult<Json<ApiResponse<serde_json::Value>>> {
     tracing::info!("钱包验证请求: {:?}", req);
@@ -249,6 +270,9 @@ pub async fn solana_wallet_verify(
     
     // 执行签名验证
     let result = blockchain_services.wallet_verifier.verify_signature(&verification_request)?;
+    if !result.is_valid {
+        record_signature_failure(&state, &client, wallet_address, "walletVerify", result.error.as_deref()).await;
+    }
     
     let response_data = serde_json::json!({
         "isValid": result.is_valid,
@@ -259,6 +283,22 @@ pub async fn solana_wallet_verify(
     Ok(Json(success(response_data)))
 }
 
+/// 记录钱包签名校验失败
+async fn record_signature_failure(
+    state: &AppState,
+    client: &ClientContext,
+    wallet_address: &str,
+    action: &str,
+    reason: Option<&str>,
+) {
+    let entry = NewAuditLog::new(None, EVENT_WALLET_SIGNATURE)
+        .wallet(wallet_address)
+        .detail(serde_json::json!({ "action": action, "reason": reason }))
+        .outcome(AuditOutcome::Failure)
+        .client(client);
+    state.services.audit_service().record(entry).await;
+}
+
 /// 价格更新处理器
 pub async fn solana_price_update(
     State(state): State<AppState>,
@@ -312,10 +352,11 @@ pub async fn solana_generate_challenge(
         .ok_or_else(|| AppError::bad_request("缺少钱包地址"))?;
     
     // 生成挑战消息
-    let challenge = blockchain_services.wallet_verifier.generate_login_challenge(wallet_address)?;
+    let challenge = blockchain_services.wallet_verifier.generate_login_challenge(wallet_address).await?;
     
     let response_data = serde_json::json!({
-        "challenge": challenge,
+        "challenge": challenge.to_string(),
+        "siws": challenge,
         "walletAddress": wallet_address,
         "timestamp": chrono::Utc::now().to_rfc3339()
     });
@@ -326,6 +367,7 @@ pub async fn solana_generate_challenge(
 /// 验证登录挑战处理器
 pub async fn solana_verify_challenge(
     State(state): State<AppState>,
+    client: ClientContext,
     Json(req): Json<serde_json::Value>,
 ) -> AppResult<Json<ApiResponse<serde_json::Value>>> {
     tracing::info!("验证登录挑战请求");
@@ -344,11 +386,21 @@ pub async fn solana_verify_challenge(
         .ok_or_else(|| AppError::bad_request("缺少签名"))?;
     
     // 验证登录挑战
-    let result = blockchain_services.wallet_verifier.verify_login_challenge(
+    let result = match blockchain_services.wallet_verifier.verify_login_challenge(
         wallet_address,
         challenge,
         signature,
-    )?;
+    ).await {
+        Ok(result) => result,
+        Err(err) => {
+            let reason = err.to_string();
+            record_signature_failure(&state, &client, wallet_address, "verifyChallenge", Some(&reason)).await;
+            return Err(err);
+        }
+    };
+    if !result.is_valid {
+        record_signature_failure(&state, &client, wallet_address, "verifyChallenge", result.error.as_deref()).await;
+    }
     
     let response_data = serde_json::json!({
         "isValid": result.is_valid,
@@ -381,7 +433,7 @@ pub async fn solana_blockchain_status(
         Ok(_) => {
             status["solana_rpc"] = serde_json::json!({
                 "status": "connected",
-                "url": state.config.solana.rpc_url
+                "url": redact_endpoint_url(&state.config.solana.rpc_url)
             });
             
             // 获取当前区块高度
@@ -397,6 +449,12 @@ pub async fn solana_blockchain_status(
         }
     }
     
+    // RPC节点池状态
+    status["rpc_endpoints"] = serde_json::json!(blockchain_services.solana_client.rpc_pool().statuses());
+    
+    // WebSocket订阅状态
+    status["websocket"] = serde_json::json!(blockchain_services.ws_client.status().await);
+    
     // 获取价格服务缓存状态
     let (total_cached, fresh_cached) = blockchain_services.price_service.get_cache_stats().await;
     status["price_service"] = serde_json::json!({
@@ -406,9 +464,14 @@ pub async fn solana_blockchain_status(
     
     // 获取交易监听状态
     let is_listening = blockchain_services.transaction_listener.is_listening().await;
+    let skipped_slots = blockchain_services.transaction_listener.skipped_slot_count().await.ok();
     status["transaction_listener"] = serde_json::json!({
-        "status": if is_listening { "running" } else { "stopped" }
+        "status": if is_listening { "running" } else { "stopped" },
+        "skipped_slots": skipped_slots
     });
     
+    // 事件管道和各输出端状态
+    status["event_pipeline"] = serde_json::json!(blockchain_services.event_pipeline.status().await);
+    
     Ok(Json(success(status)))
 }
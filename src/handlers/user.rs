@@ -1,28 +1,31 @@
 use axum::{
-    extract::{Request, State},
+    extract::State,
     http::StatusCode,
     Json,
 };
 use serde::{Deserialize, Serialize};
-use crate::handlers::{response::*, AppState};
+use crate::handlers::{response::*, AppState, AuthUser};
+use crate::models::audit_log::ClientContext;
+use crate::models::user_wallet::UserWallet;
 use crate::services::user::*;
+use crate::services::{ApiKeyResponse, AuditLogListResponse, CreateApiKeyRequest, CreateApiKeyResponse};
+use crate::utils::AppResult;
 
 /// 钱包登录请求
 #[derive(Debug, Deserialize)]
 pub struct WalletLoginRequest {
     pub wallet_address: String,
     pub network: String,
+    pub challenge: String,
     pub signature: String,
     pub invite_code: Option<String>,
 }
 
-/// 钱包登录响应
-#[derive(Debug, Serialize)]
-pub struct WalletLoginResponse {
-    pub username: String,
-    pub wallet: String,
-    pub token: String,
-    pub invite_code: String,
+/// 钱包登录挑战请求
+#[derive(Debug, Deserialize)]
+pub struct WalletChallengeRequest {
+    pub wallet_address: String,
+    pub network: String,
 }
 
 /// 邮箱登录请求
@@ -64,36 +67,101 @@ pub struct EditUsernameRequest {
     pub username: String,
 }
 
+/// 绑定钱包请求
+#[derive(Debug, Deserialize)]
+pub struct LinkWalletRequest {
+    pub wallet_address: String,
+    pub network: String,
+    pub challenge: String,
+    pub signature: String,
+}
+
+/// 解绑钱包请求
+#[derive(Debug, Deserialize)]
+pub struct UnlinkWalletRequest {
+    pub wallet_address: String,
+}
+
+/// 绑定邮箱请求
+#[derive(Debug, Deserialize)]
+pub struct BindEmailRequest {
+    pub email: String,
+    pub password: String,
+}
+
+/// 双重认证验证码请求
+#[derive(Debug, Deserialize)]
+pub struct TwoFactorCodeRequest {
+    pub code: String,
+}
+
+/// 安全日志请求
+#[derive(Debug, Deserialize)]
+pub struct SecurityLogRequest {
+    pub page: Option<u32>,
+    pub limit: Option<u32>,
+}
+
+/// 吊销API密钥请求
+#[derive(Debug, Deserialize)]
+pub struct RevokeApiKeyRequest {
+    pub id: u32,
+}
+
 /// 找回密码请求
 #[derive(Debug, Deserialize)]
 pub struct FindPasswordRequest {
     pub email: String,
 }
 
+/// 重置密码请求
+#[derive(Debug, Deserialize)]
+pub struct ResetPasswordRequest {
+    pub email: String,
+    pub code: String,
+    pub new_password: String,
+}
+
+/// 钱包登录挑战处理器
+pub async fn user_wallet_challenge(
+    State(state): State<AppState>,
+    Json(req): Json<WalletChallengeRequest>,
+) -> AppResult<ApiResponse<WalletChallengeResponse>> {
+    let response = state.services.user_service().wallet_challenge(req.into()).await?;
+    Ok(success(response))
+}
+
 /// 钱包登录处理器
 pub async fn user_wallet_login(
-    State(_state): State<AppState>,
+    State(state): State<AppState>,
+    client: ClientContext,
     Json(req): Json<WalletLoginRequest>,
-) -> Result<ApiResponse<WalletLoginResponse>, StatusCode> {
-    // TODO: 实现钱包签名验证
-    // 暂时返回模拟响应
-    
-    let response = WalletLoginResponse {
-        username: "user123".to_string(),
-        wallet: req.wallet_address,
-        token: "mock_jwt_token".to_string(),
-        invite_code: "123456".to_string(),
-    };
-    
+) -> AppResult<ApiResponse<WalletLoginResult>> {
+    match state.services.user_service().wallet_login(req.into(), &client).await {
+        Ok(response) => Ok(success(response)),
+        Err(err) => {
+            tracing::warn!("Wallet login failed: {:?}", err);
+            Err(err)
+        }
+    }
+}
+
+/// 刷新令牌处理器
+pub async fn user_refresh_token(
+    State(state): State<AppState>,
+    Json(req): Json<RefreshTokenRequest>,
+) -> AppResult<ApiResponse<RefreshTokenResponse>> {
+    let response = state.services.user_service().refresh_token(req).await?;
     Ok(success(response))
 }
 
 /// 邮箱登录处理器
 pub async fn user_email_login(
     State(state): State<AppState>,
+    client: ClientContext,
     Json(req): Json<EmailLoginRequest>,
-) -> Result<ApiResponse<UserTokenResponse>, StatusCode> {
-    match state.services.user_service().email_login(req.into()).await {
+) -> Result<ApiResponse<EmailLoginResponse>, StatusCode> {
+    match state.services.user_service().email_login(req.into(), &client).await {
         Ok(response) => Ok(success(response)),
         Err(_err) => {
             tracing::error!("Email login failed: {:?}", _err);
@@ -104,61 +172,286 @@ pub async fn user_email_login(
 
 /// 用户注册处理器
 pub async fn user_register(
-    State(_state): State<AppState>,
-    Json(_req): Json<UserRegisterRequest>,
-) -> Result<ApiResponse<UserTokenResponse>, StatusCode> {
-    // TODO: 实现用户注册逻辑
-    // 暂时返回错误
-    Err(StatusCode::NOT_IMPLEMENTED)
+    State(state): State<AppState>,
+    Json(req): Json<UserRegisterRequest>,
+) -> AppResult<ApiResponse<UserTokenResponse>> {
+    let response = state.services.user_service().register(req.into()).await?;
+    Ok(success(response))
 }
 
 /// 获取用户信息处理器
 pub async fn user_info(
-    State(_state): State<AppState>,
-    _request: Request,
+    State(state): State<AppState>,
+    AuthUser { user, .. }: AuthUser,
 ) -> Result<ApiResponse<UserInfoResponse>, StatusCode> {
-    // TODO: 从JWT中获取用户ID
-    // 暂时返回模拟数据
-    
     let response = UserInfoResponse {
-        id: 1,
-        username: "test_user".to_string(),
-        email: "test@example.com".to_string(),
-        invite_code: "123456".to_string(),
-        created_at: chrono::Utc::now().to_rfc3339(),
+        id: user.id,
+        invite_code: state.services.user_service().get_invite_code(user.id),
+        username: user.username,
+        email: user.email,
+        created_at: user.created_at.to_rfc3339(),
     };
     
     Ok(success(response))
 }
 
+/// 注销当前会话处理器
+pub async fn user_logout(
+    State(state): State<AppState>,
+    auth: AuthUser,
+) -> AppResult<ApiResponse<()>> {
+    state.services.user_service().logout(auth.user.id, auth.session_id()?).await?;
+    Ok(success_empty())
+}
+
+/// 注销全部会话处理器
+pub async fn user_logout_all(
+    State(state): State<AppState>,
+    AuthUser { user, .. }: AuthUser,
+) -> AppResult<ApiResponse<()>> {
+    state.services.user_service().logout_all(user.id).await?;
+    Ok(success_empty())
+}
+
 /// 更新密码处理器
 pub async fn user_update_password(
-    State(_state): State<AppState>,
-    Json(_req): Json<UpdatePasswordRequest>,
-) -> Result<ApiResponse<()>, StatusCode> {
-    // TODO: 实现密码更新逻辑
+    State(state): State<AppState>,
+    auth: AuthUser,
+    client: ClientContext,
+    Json(req): Json<UpdatePasswordRequest>,
+) -> AppResult<ApiResponse<()>> {
+    let session_id = auth.session_id()?.to_string();
+    state.services.user_service().update_password(auth.user, &session_id, req.into(), &client).await?;
     Ok(success_empty())
 }
 
 /// 编辑用户名处理器
 pub async fn user_edit_username(
-    State(_state): State<AppState>,
-    Json(_req): Json<EditUsernameRequest>,
-) -> Result<ApiResponse<()>, StatusCode> {
-    // TODO: 实现用户名编辑逻辑
+    State(state): State<AppState>,
+    AuthUser { user, .. }: AuthUser,
+    Json(req): Json<EditUsernameRequest>,
+) -> AppResult<ApiResponse<()>> {
+    state.services.user_service().edit_username(user, &req.username).await?;
+    Ok(success_empty())
+}
+
+/// 绑定钱包处理器
+pub async fn user_link_wallet(
+    State(state): State<AppState>,
+    AuthUser { user, .. }: AuthUser,
+    client: ClientContext,
+    Json(req): Json<LinkWalletRequest>,
+) -> AppResult<ApiResponse<UserWallet>> {
+    let wallet = state.services.user_service().link_wallet(&user, req.into(), &client).await?;
+    Ok(success(wallet))
+}
+
+/// 解绑钱包处理器
+pub async fn user_unlink_wallet(
+    State(state): State<AppState>,
+    AuthUser { user, .. }: AuthUser,
+    Json(req): Json<UnlinkWalletRequest>,
+) -> AppResult<ApiResponse<()>> {
+    state.services.user_service().unlink_wallet(&user, &req.wallet_address).await?;
+    Ok(success_empty())
+}
+
+/// 已绑定钱包列表处理器
+pub async fn user_wallets(
+    State(state): State<AppState>,
+    AuthUser { user, .. }: AuthUser,
+) -> AppResult<ApiResponse<Vec<UserWallet>>> {
+    let wallets = state.services.user_service().user_wallets(user.id).await?;
+    Ok(success(wallets))
+}
+
+/// 绑定邮箱处理器
+pub async fn user_bind_email(
+    State(state): State<AppState>,
+    AuthUser { user, .. }: AuthUser,
+    Json(req): Json<BindEmailRequest>,
+) -> AppResult<ApiResponse<()>> {
+    state.services.user_service().bind_email(&user, req.into()).await?;
+    Ok(success_empty())
+}
+
+/// 双重认证登录处理器
+pub async fn user_two_factor_login(
+    State(state): State<AppState>,
+    client: ClientContext,
+    Json(req): Json<TwoFactorLoginRequest>,
+) -> AppResult<ApiResponse<UserTokenResponse>> {
+    let response = state.services.user_service().two_factor_login(req, &client).await?;
+    Ok(success(response))
+}
+
+/// 开始设置双重认证处理器
+pub async fn user_two_factor_setup(
+    State(state): State<AppState>,
+    AuthUser { user, .. }: AuthUser,
+) -> AppResult<ApiResponse<TwoFactorSetupResponse>> {
+    let response = state.services.user_service().two_factor_setup(&user).await?;
+    Ok(success(response))
+}
+
+/// 启用双重认证处理器
+pub async fn user_two_factor_enable(
+    State(state): State<AppState>,
+    AuthUser { user, .. }: AuthUser,
+    client: ClientContext,
+    Json(req): Json<TwoFactorCodeRequest>,
+) -> AppResult<ApiResponse<TwoFactorEnableResponse>> {
+    let response = state.services.user_service().two_factor_enable(&user, &req.code, &client).await?;
+    Ok(success(response))
+}
+
+/// 关闭双重认证处理器
+pub async fn user_two_factor_disable(
+    State(state): State<AppState>,
+    AuthUser { user, .. }: AuthUser,
+    client: ClientContext,
+    Json(req): Json<TwoFactorCodeRequest>,
+) -> AppResult<ApiResponse<()>> {
+    state.services.user_service().two_factor_disable(&user, &req.code, &client).await?;
+    Ok(success_empty())
+}
+
+/// 安全日志处理器，返回当前用户的登录、改密等认证事件
+pub async fn user_security_log(
+    State(state): State<AppState>,
+    AuthUser { user, .. }: AuthUser,
+    Json(req): Json<SecurityLogRequest>,
+) -> AppResult<ApiResponse<AuditLogListResponse>> {
+    let logs = state.services.user_service().security_log(user.id, req.page, req.limit).await?;
+    Ok(success(logs))
+}
+
+/// API密钥列表处理器
+pub async fn user_api_keys(
+    State(state): State<AppState>,
+    AuthUser { user, .. }: AuthUser,
+) -> AppResult<ApiResponse<Vec<ApiKeyResponse>>> {
+    let keys = state.services.api_key_service().list_api_keys(user.id).await?;
+    Ok(success(keys))
+}
+
+/// 创建API密钥处理器
+pub async fn user_create_api_key(
+    State(state): State<AppState>,
+    AuthUser { user, .. }: AuthUser,
+    Json(req): Json<CreateApiKeyRequest>,
+) -> AppResult<ApiResponse<CreateApiKeyResponse>> {
+    let response = state.services.api_key_service().create_api_key(user.id, req).await?;
+    Ok(success(response))
+}
+
+/// 吊销API密钥处理器
+pub async fn user_revoke_api_key(
+    State(state): State<AppState>,
+    AuthUser { user, .. }: AuthUser,
+    Json(req): Json<RevokeApiKeyRequest>,
+) -> AppResult<ApiResponse<()>> {
+    state.services.api_key_service().revoke_api_key(user.id, req.id).await?;
     Ok(success_empty())
 }
 
 /// 找回密码处理器
 pub async fn user_find_password(
-    State(_state): State<AppState>,
-    Json(_req): Json<FindPasswordRequest>,
-) -> Result<ApiResponse<()>, StatusCode> {
-    // TODO: 实现找回密码逻辑
+    State(state): State<AppState>,
+    Json(req): Json<FindPasswordRequest>,
+) -> AppResult<ApiResponse<()>> {
+    state.services.user_service().find_password(req.into()).await?;
+    Ok(success_empty())
+}
+
+/// 重置密码处理器
+pub async fn user_reset_password(
+    State(state): State<AppState>,
+    client: ClientContext,
+    Json(req): Json<ResetPasswordRequest>,
+) -> AppResult<ApiResponse<()>> {
+    state.services.user_service().reset_password(req.into(), &client).await?;
     Ok(success_empty())
 }
 
 // 请求转换实现
+impl From<WalletLoginRequest> for crate::services::user::WalletLoginRequest {
+    fn from(req: WalletLoginRequest) -> Self {
+        Self {
+            wallet_address: req.wallet_address,
+            network: req.network,
+            challenge: req.challenge,
+            signature: req.signature,
+            invite_code: req.invite_code,
+        }
+    }
+}
+
+impl From<WalletChallengeRequest> for crate::services::user::WalletChallengeRequest {
+    fn from(req: WalletChallengeRequest) -> Self {
+        Self {
+            wallet_address: req.wallet_address,
+            network: req.network,
+        }
+    }
+}
+
+impl From<LinkWalletRequest> for crate::services::user::LinkWalletRequest {
+    fn from(req: LinkWalletRequest) -> Self {
+        Self {
+            wallet_address: req.wallet_address,
+            network: req.network,
+            challenge: req.challenge,
+            signature: req.signature,
+        }
+    }
+}
+
+impl From<BindEmailRequest> for crate::services::user::BindEmailRequest {
+    fn from(req: BindEmailRequest) -> Self {
+        Self {
+            email: req.email,
+            password: req.password,
+        }
+    }
+}
+
+impl From<UserRegisterRequest> for crate::services::user::UserRegisterRequest {
+    fn from(req: UserRegisterRequest) -> Self {
+        Self {
+            email: req.email,
+            password: req.password,
+            username: req.username,
+            invite_code: req.invite_code,
+        }
+    }
+}
+
+impl From<FindPasswordRequest> for crate::services::user::FindPasswordRequest {
+    fn from(req: FindPasswordRequest) -> Self {
+        Self { email: req.email }
+    }
+}
+
+impl From<ResetPasswordRequest> for crate::services::user::ResetPasswordRequest {
+    fn from(req: ResetPasswordRequest) -> Self {
+        Self {
+            email: req.email,
+            code: req.code,
+            new_password: req.new_password,
+        }
+    }
+}
+
+impl From<UpdatePasswordRequest> for crate::services::user::UpdatePasswordRequest {
+    fn from(req: UpdatePasswordRequest) -> Self {
+        Self {
+            old_password: req.old_password,
+            new_password: req.new_password,
+        }
+    }
+}
+
 impl From<EmailLoginRequest> for crate::services::user::EmailLoginRequest {
     fn from(req: EmailLoginRequest) -> Self {
         Self {
@@ -6,6 +6,7 @@ mod services;
 mod handlers;
 mod blockchain;
 
+use std::net::SocketAddr;
 use std::sync::Arc;
 use config::Config;
 use utils::AppResult;
@@ -42,7 +43,11 @@ async fn main() -> AppResult<()> {
     tracing::info!("Database and Redis connections established");
     
     // 初始化区块链服务
-    let blockchain_services = BlockchainServices::new(config.clone()).await?;
+    let blockchain_services = BlockchainServices::new(
+        config.clone(),
+        repositories.redis_repository().clone(),
+        repositories.solana_repository().clone(),
+    ).await?;
     tracing::info!("Blockchain services initialized successfully");
     
     // 启动区块链后台服务
@@ -56,10 +61,11 @@ async fn main() -> AppResult<()> {
     tracing::info!("Services initialized successfully");
     
     // 创建应用状态
+    let blockchain_services = Arc::new(blockchain_services);
     let app_state = AppState {
         config: config.clone(),
         services,
-        blockchain_services: Some(Arc::new(blockchain_services)),
+        blockchain_services: Some(blockchain_services.clone()),
     };
     
     // 创建路由
@@ -81,7 +87,46 @@ async fn main() -> AppResult<()> {
     tracing::info!("🔗 Solana API: http://{}/v2/solana/*", config.http_listen);
     tracing::info!("⛓️  Blockchain services: {}", if config.solana.monitoring.enabled { "Enabled" } else { "Disabled" });
     
-    axum::serve(listener, app).await?;
+    axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
+        .with_graceful_shutdown(shutdown_signal())
+        .await?;
+    
+    // 停止监听并等待事件管道处理完已入队的事件
+    if config.solana.monitoring.enabled {
+        blockchain_services.stop_background_services().await?;
+        tracing::info!("Blockchain background services stopped");
+    }
     
     Ok(())
 }
+
+/// 等待Ctrl+C或SIGTERM
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            tracing::error!("Failed to listen for Ctrl+C: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+    
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(e) => {
+                tracing::error!("Failed to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+    
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+    tracing::info!("Shutdown signal received, stopping server");
+}
@@ -0,0 +1,160 @@
+use std::net::IpAddr;
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use sqlx::FromRow;
+
+/// API密钥权限
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ApiKeyScope {
+    ReadMarket,
+    ReadPositions,
+    Trade,
+    /// 推送价格和Webhook，仅对运营及以上角色的密钥生效
+    WriteMarket,
+}
+
+impl ApiKeyScope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::ReadMarket => "read-market",
+            Self::ReadPositions => "read-positions",
+            Self::Trade => "trade",
+            Self::WriteMarket => "write-market",
+        }
+    }
+
+    pub fn parse(scope: &str) -> Option<Self> {
+        match scope.trim() {
+            "read-market" => Some(Self::ReadMarket),
+            "read-positions" => Some(Self::ReadPositions),
+            "trade" => Some(Self::Trade),
+            "write-market" => Some(Self::WriteMarket),
+            _ => None,
+        }
+    }
+}
+
+/// 用户API密钥
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ApiKey {
+    pub id: u32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub user_id: u32,
+    pub name: String,
+    pub prefix: String, // 明文前缀，便于用户辨认
+    pub scopes: String, // 逗号分隔
+    pub ip_allowlist: Option<String>, // 逗号分隔的IP或CIDR，为空表示不限制
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+impl ApiKey {
+    pub fn scope_list(&self) -> Vec<ApiKeyScope> {
+        self.scopes.split(',').filter_map(ApiKeyScope::parse).collect()
+    }
+
+    pub fn ip_list(&self) -> Vec<String> {
+        self.ip_allowlist
+            .as_deref()
+            .unwrap_or("")
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// 检查来源IP是否在白名单内；未设置白名单时允许任意来源
+    pub fn allows_ip(&self, ip: Option<IpAddr>) -> bool {
+        let allowlist = self.ip_list();
+        if allowlist.is_empty() {
+            return true;
+        }
+        match ip {
+            Some(ip) => allowlist.iter().any(|entry| ip_matches(entry, ip)),
+            None => false,
+        }
+    }
+}
+
+/// 校验白名单条目格式（IP或CIDR）
+pub fn is_valid_ip_entry(entry: &str) -> bool {
+    parse_ip_entry(entry).is_some()
+}
+
+fn parse_ip_entry(entry: &str) -> Option<(IpAddr, u8)> {
+    let (addr, prefix) = match entry.split_once('/') {
+        Some((addr, prefix)) => (addr, Some(prefix.parse::<u8>().ok()?)),
+        None => (entry, None),
+    };
+    let addr: IpAddr = addr.trim().parse().ok()?;
+    let max = if addr.is_ipv4() { 32 } else { 128 };
+    let prefix = prefix.unwrap_or(max);
+    (prefix <= max).then_some((addr, prefix))
+}
+
+fn ip_matches(entry: &str, ip: IpAddr) -> bool {
+    let Some((network, prefix)) = parse_ip_entry(entry) else {
+        return false;
+    };
+    match (network, ip) {
+        (IpAddr::V4(network), IpAddr::V4(ip)) => {
+            let mask = u32::MAX.checked_shl(32 - prefix as u32).unwrap_or(0);
+            u32::from(network) & mask == u32::from(ip) & mask
+        }
+        (IpAddr::V6(network), IpAddr::V6(ip)) => {
+            let mask = u128::MAX.checked_shl(128 - prefix as u32).unwrap_or(0);
+            u128::from(network) & mask == u128::from(ip) & mask
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key_with_allowlist(allowlist: Option<&str>) -> ApiKey {
+        ApiKey {
+            id: 1,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            user_id: 1,
+            name: "bot".to_string(),
+            prefix: "wm_abcdefgh".to_string(),
+            scopes: "read-market,trade,unknown".to_string(),
+            ip_allowlist: allowlist.map(str::to_string),
+            last_used_at: None,
+            revoked_at: None,
+        }
+    }
+
+    #[test]
+    fn test_scopes() {
+        let key = key_with_allowlist(None);
+        assert_eq!(key.scope_list(), vec![ApiKeyScope::ReadMarket, ApiKeyScope::Trade]);
+        assert_eq!(ApiKeyScope::parse("read-positions"), Some(ApiKeyScope::ReadPositions));
+        assert_eq!(ApiKeyScope::ReadPositions.as_str(), "read-positions");
+        assert_eq!(ApiKeyScope::parse("write-market"), Some(ApiKeyScope::WriteMarket));
+    }
+
+    #[test]
+    fn test_ip_allowlist() {
+        let ip = |s: &str| Some(s.parse().unwrap());
+        assert!(key_with_allowlist(None).allows_ip(None));
+
+        let key = key_with_allowlist(Some("10.0.0.0/8, 203.0.113.7,2001:db8::/32"));
+        assert!(key.allows_ip(ip("10.1.2.3")));
+        assert!(key.allows_ip(ip("203.0.113.7")));
+        assert!(key.allows_ip(ip("2001:db8::1")));
+        assert!(!key.allows_ip(ip("203.0.113.8")));
+        assert!(!key.allows_ip(ip("11.0.0.1")));
+        assert!(!key.allows_ip(None));
+
+        assert!(is_valid_ip_entry("0.0.0.0/0"));
+        assert!(!is_valid_ip_entry("10.0.0.0/33"));
+        assert!(!is_valid_ip_entry("example.com"));
+    }
+}
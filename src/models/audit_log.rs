@@ -0,0 +1,146 @@
+use std::net::IpAddr;
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use sqlx::FromRow;
+use crate::blockchain::WalletType;
+
+/// 邮箱登录
+pub const EVENT_EMAIL_LOGIN: &str = "auth.email_login";
+/// 双重认证登录
+pub const EVENT_TWO_FACTOR_LOGIN: &str = "auth.2fa_login";
+/// 钱包登录
+pub const EVENT_WALLET_LOGIN: &str = "auth.wallet_login";
+/// 钱包签名校验（不登录）
+pub const EVENT_WALLET_SIGNATURE: &str = "auth.wallet_signature";
+/// 修改密码
+pub const EVENT_PASSWORD_CHANGE: &str = "auth.password_change";
+/// 通过邮箱验证码重置密码
+pub const EVENT_PASSWORD_RESET: &str = "auth.password_reset";
+/// 开启双重认证
+pub const EVENT_TWO_FACTOR_ENABLE: &str = "auth.2fa_enable";
+/// 关闭双重认证
+pub const EVENT_TWO_FACTOR_DISABLE: &str = "auth.2fa_disable";
+/// 连续失败导致账户锁定
+pub const EVENT_ACCOUNT_LOCKED: &str = "auth.lockout";
+
+/// 操作结果
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditOutcome {
+    #[default]
+    Success,
+    Failure,
+    Locked, // 账户锁定期间的请求
+}
+
+impl AuditOutcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Success => "success",
+            Self::Failure => "failure",
+            Self::Locked => "locked",
+        }
+    }
+}
+
+/// 审计日志
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct AuditLog {
+    pub id: u64,
+    pub created_at: DateTime<Utc>,
+    pub user_id: Option<u32>, // 操作人，匿名操作为空
+    pub event: String, // 例如 admin.user.delete
+    pub target: Option<String>,
+    pub wallet: Option<String>,
+    pub detail: Option<String>, // JSON
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub wallet_type: Option<String>, // phantom, solflare, backpack, other
+    pub outcome: String, // success, failure, locked
+}
+
+/// 请求来源信息
+#[derive(Debug, Clone, Default)]
+pub struct ClientContext {
+    pub ip: Option<IpAddr>,
+    pub user_agent: Option<String>,
+}
+
+/// 待写入的审计日志
+#[derive(Debug, Clone, Default)]
+pub struct NewAuditLog {
+    pub user_id: Option<u32>,
+    pub event: String,
+    pub target: Option<String>,
+    pub wallet: Option<String>,
+    pub detail: Option<serde_json::Value>,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub wallet_type: Option<String>,
+    pub outcome: AuditOutcome,
+}
+
+impl NewAuditLog {
+    pub fn new(user_id: Option<u32>, event: impl Into<String>) -> Self {
+        Self {
+            user_id,
+            event: event.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn target(mut self, target: impl Into<String>) -> Self {
+        self.target = Some(target.into());
+        self
+    }
+
+    pub fn wallet(mut self, wallet: impl Into<String>) -> Self {
+        self.wallet = Some(wallet.into());
+        self
+    }
+
+    pub fn detail(mut self, detail: serde_json::Value) -> Self {
+        self.detail = Some(detail);
+        self
+    }
+
+    pub fn outcome(mut self, outcome: AuditOutcome) -> Self {
+        self.outcome = outcome;
+        self
+    }
+
+    /// 记录请求来源，并由User-Agent识别钱包类型
+    pub fn client(mut self, client: &ClientContext) -> Self {
+        self.ip = client.ip.map(|ip| ip.to_string());
+        self.user_agent = client
+            .user_agent
+            .as_deref()
+            .map(|user_agent| user_agent.chars().take(255).collect());
+        self.wallet_type = client
+            .user_agent
+            .as_deref()
+            .map(|user_agent| WalletType::from_user_agent(user_agent).as_str().to_string());
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_client_context() {
+        let client = ClientContext {
+            ip: Some("203.0.113.7".parse().unwrap()),
+            user_agent: Some("Mozilla/5.0 Phantom/24.1".to_string()),
+        };
+        let entry = NewAuditLog::new(Some(1), EVENT_WALLET_LOGIN).client(&client);
+        assert_eq!(entry.ip.as_deref(), Some("203.0.113.7"));
+        assert_eq!(entry.wallet_type.as_deref(), Some("phantom"));
+        assert_eq!(entry.outcome, AuditOutcome::Success);
+
+        let entry = NewAuditLog::new(None, EVENT_EMAIL_LOGIN).client(&ClientContext::default());
+        assert_eq!(entry.user_agent, None);
+        assert_eq!(entry.wallet_type, None);
+    }
+}
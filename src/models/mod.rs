@@ -1,4 +1,8 @@
 pub mod user;
+pub mod user_wallet;
+pub mod user_totp;
+pub mod api_key;
+pub mod audit_log;
 pub mod solana;
 pub mod trade;
 pub mod commission;
@@ -102,14 +102,47 @@ pub struct SolTransaction {
     pub is_buy: bool,
     pub volume_usd: f64,
     pub slot: String,
+    pub commitment: String, // confirmed=已确认 finalized=已最终确认
     pub pnl: f64,
     pub transfer_type: u8, // 1=建仓2=清仓3=加仓4=减仓
 }
 
+/// 交易对持仓的增量，写入交易时累加，撤销时扣回
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct HolderDelta {
+    pub amount: f64,
+    pub bet: f64,
+    pub pnl: f64,
+}
+
 impl SolTransaction {
     pub fn table_name() -> &'static str {
         "cook_wm_sol_transaction"
     }
+
+    /// 按交易前的持仓数量和成本计算仓位类型与已实现盈亏，返回持仓增量
+    ///
+    /// 卖出按平均成本结转，超出已知持仓的部分没有成本。
+    pub fn apply_to_holding(&mut self, amount: f64, bet: f64) -> HolderDelta {
+        if self.is_buy {
+            self.pnl = 0.0;
+            self.transfer_type = if amount <= 0.0 { 1 } else { 3 };
+        } else {
+            let cost = if amount > 0.0 { bet * self.token_amount.min(amount) / amount } else { 0.0 };
+            self.pnl = self.sol_amount - cost;
+            self.transfer_type = if amount - self.token_amount <= 0.0 { 2 } else { 4 };
+        }
+        self.holder_delta()
+    }
+
+    /// 交易对持仓的增量，只依赖交易记录本身，撤销时可以精确扣回
+    pub fn holder_delta(&self) -> HolderDelta {
+        if self.is_buy {
+            HolderDelta { amount: self.token_amount, bet: self.sol_amount, pnl: 0.0 }
+        } else {
+            HolderDelta { amount: -self.token_amount, bet: self.pnl - self.sol_amount, pnl: self.pnl }
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
@@ -2,6 +2,34 @@ use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
 use sqlx::FromRow;
 
+/// 用户角色，按权限从低到高排列
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UserRole {
+    User,
+    Operator,
+    Admin,
+}
+
+impl UserRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::User => "user",
+            Self::Operator => "operator",
+            Self::Admin => "admin",
+        }
+    }
+
+    pub fn parse(role: &str) -> Option<Self> {
+        match role.trim() {
+            "user" => Some(Self::User),
+            "operator" => Some(Self::Operator),
+            "admin" => Some(Self::Admin),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct User {
     pub id: u32,
@@ -10,10 +38,12 @@ pub struct User {
     pub deleted_at: Option<DateTime<Utc>>,
     pub username: String,
     pub email: String,
+    pub wallet: Option<String>,
     #[serde(skip_serializing)]
     pub password: String,
     #[serde(skip_serializing)]
     pub parent: String,
+    pub role: String, // user, operator, admin
     pub sol_commission: f64,
     pub base_commission: f64,
     pub eth_commission: f64,
@@ -36,8 +66,10 @@ impl User {
             deleted_at: None,
             username,
             email,
+            wallet: None,
             password,
             parent: String::new(),
+            role: UserRole::User.as_str().to_string(),
             sol_commission: 0.0,
             base_commission: 0.0,
             eth_commission: 0.0,
@@ -52,6 +84,18 @@ impl User {
         user.parent = parent;
         user
     }
+
+    /// 创建钱包登录用户（无邮箱、无密码）
+    pub fn with_wallet(username: String, wallet: String, parent: String) -> Self {
+        let mut user = Self::with_parent(username, String::new(), String::new(), parent);
+        user.wallet = Some(wallet);
+        user
+    }
+
+    /// 用户角色，无法识别的值按普通用户处理
+    pub fn role(&self) -> UserRole {
+        UserRole::parse(&self.role).unwrap_or(UserRole::User)
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -106,3 +150,22 @@ pub struct LoginResponse {
     pub token: String,
     pub user: UserResponse,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_user_role() {
+        assert!(UserRole::Admin > UserRole::Operator && UserRole::Operator > UserRole::User);
+        assert_eq!(UserRole::parse("operator"), Some(UserRole::Operator));
+        assert_eq!(UserRole::parse("root"), None);
+
+        let mut user = User::new("alice".to_string(), String::new(), String::new());
+        assert_eq!(user.role(), UserRole::User);
+        user.role = "unknown".to_string();
+        assert_eq!(user.role(), UserRole::User);
+        user.role = UserRole::Admin.as_str().to_string();
+        assert_eq!(user.role(), UserRole::Admin);
+    }
+}
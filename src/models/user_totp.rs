@@ -0,0 +1,23 @@
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use sqlx::FromRow;
+
+/// 用户TOTP双重认证设置
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct UserTotp {
+    pub user_id: u32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    #[serde(skip_serializing)]
+    pub secret: String, // 加密后的密钥
+    pub enabled: bool,
+    #[serde(skip_serializing)]
+    pub recovery_codes: String, // 恢复码哈希的JSON数组
+}
+
+impl UserTotp {
+    /// 未使用的恢复码哈希
+    pub fn recovery_code_hashes(&self) -> Vec<String> {
+        serde_json::from_str(&self.recovery_codes).unwrap_or_default()
+    }
+}
@@ -0,0 +1,14 @@
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use sqlx::FromRow;
+
+/// 用户绑定的钱包
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct UserWallet {
+    pub id: u32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub user_id: u32,
+    pub wallet: String,
+    pub network: String, // sol, evm
+}
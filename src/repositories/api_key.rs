@@ -0,0 +1,107 @@
+use sqlx::MySqlPool;
+use crate::models::api_key::ApiKey;
+use crate::utils::AppResult;
+
+pub struct ApiKeyRepository {
+    pool: MySqlPool,
+}
+
+impl ApiKeyRepository {
+    pub fn new(pool: MySqlPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn create_api_key(
+        &self,
+        user_id: u32,
+        name: &str,
+        prefix: &str,
+        key_hash: &str,
+        scopes: &str,
+        ip_allowlist: Option<&str>,
+    ) -> AppResult<ApiKey> {
+        let query = r#"
+            INSERT INTO cook_jcc_user_api_keys (user_id, name, prefix, key_hash, scopes, ip_allowlist, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, NOW(), NOW())
+        "#;
+
+        let result = sqlx::query(query)
+            .bind(user_id)
+            .bind(name)
+            .bind(prefix)
+            .bind(key_hash)
+            .bind(scopes)
+            .bind(ip_allowlist)
+            .execute(&self.pool)
+            .await?;
+
+        let now = chrono::Utc::now();
+        Ok(ApiKey {
+            id: result.last_insert_id() as u32,
+            created_at: now,
+            updated_at: now,
+            user_id,
+            name: name.to_string(),
+            prefix: prefix.to_string(),
+            scopes: scopes.to_string(),
+            ip_allowlist: ip_allowlist.map(str::to_string),
+            last_used_at: None,
+            revoked_at: None,
+        })
+    }
+
+    /// 根据哈希查找未吊销的密钥
+    pub async fn find_active_by_hash(&self, key_hash: &str) -> AppResult<Option<ApiKey>> {
+        let query = r#"
+            SELECT id, created_at, updated_at, user_id, name, prefix, scopes, ip_allowlist, last_used_at, revoked_at
+            FROM cook_jcc_user_api_keys
+            WHERE key_hash = ? AND revoked_at IS NULL
+        "#;
+
+        let key = sqlx::query_as::<_, ApiKey>(query)
+            .bind(key_hash)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(key)
+    }
+
+    /// 查询用户未吊销的密钥
+    pub async fn find_active_by_user_id(&self, user_id: u32) -> AppResult<Vec<ApiKey>> {
+        let query = r#"
+            SELECT id, created_at, updated_at, user_id, name, prefix, scopes, ip_allowlist, last_used_at, revoked_at
+            FROM cook_jcc_user_api_keys
+            WHERE user_id = ? AND revoked_at IS NULL
+            ORDER BY id DESC
+        "#;
+
+        let keys = sqlx::query_as::<_, ApiKey>(query)
+            .bind(user_id)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(keys)
+    }
+
+    /// 吊销密钥，返回是否有记录被更新
+    pub async fn revoke_api_key(&self, user_id: u32, id: u32) -> AppResult<bool> {
+        let result = sqlx::query(
+            "UPDATE cook_jcc_user_api_keys SET revoked_at = NOW(), updated_at = NOW() WHERE id = ? AND user_id = ? AND revoked_at IS NULL",
+        )
+            .bind(id)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn touch_last_used(&self, id: u32) -> AppResult<()> {
+        sqlx::query("UPDATE cook_jcc_user_api_keys SET last_used_at = NOW() WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+}
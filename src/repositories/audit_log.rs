@@ -0,0 +1,76 @@
+use sqlx::MySqlPool;
+use crate::models::audit_log::{AuditLog, NewAuditLog};
+use crate::utils::AppResult;
+
+pub struct AuditLogRepository {
+    pool: MySqlPool,
+}
+
+impl AuditLogRepository {
+    pub fn new(pool: MySqlPool) -> Self {
+        Self { pool }
+    }
+
+    /// 写入一条审计日志
+    pub async fn create(&self, entry: &NewAuditLog) -> AppResult<u64> {
+        let query = r#"
+            INSERT INTO cook_jcc_audit_log (
+                user_id, event, target, wallet, detail, ip, user_agent, wallet_type, outcome, created_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, NOW())
+        "#;
+
+        let detail = entry.detail.as_ref().map(serde_json::to_string).transpose()?;
+        let result = sqlx::query(query)
+            .bind(entry.user_id)
+            .bind(&entry.event)
+            .bind(&entry.target)
+            .bind(&entry.wallet)
+            .bind(detail)
+            .bind(&entry.ip)
+            .bind(&entry.user_agent)
+            .bind(&entry.wallet_type)
+            .bind(entry.outcome.as_str())
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.last_insert_id())
+    }
+
+    /// 按时间倒序查询，可按用户筛选；`event_prefix`用于按事件类别筛选，例如`admin.`
+    pub async fn list(&self, user_id: Option<u32>, event_prefix: &str, limit: u32, offset: u32) -> AppResult<Vec<AuditLog>> {
+        let query = r#"
+            SELECT id, created_at, user_id, event, target, wallet, detail, ip, user_agent, wallet_type, outcome
+            FROM cook_jcc_audit_log
+            WHERE (? IS NULL OR user_id = ?) AND event LIKE CONCAT(?, '%')
+            ORDER BY id DESC
+            LIMIT ? OFFSET ?
+        "#;
+
+        let logs = sqlx::query_as::<_, AuditLog>(query)
+            .bind(user_id)
+            .bind(user_id)
+            .bind(event_prefix)
+            .bind(limit)
+            .bind(offset)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(logs)
+    }
+
+    pub async fn count(&self, user_id: Option<u32>, event_prefix: &str) -> AppResult<i64> {
+        let query = r#"
+            SELECT COUNT(*) FROM cook_jcc_audit_log
+            WHERE (? IS NULL OR user_id = ?) AND event LIKE CONCAT(?, '%')
+        "#;
+
+        let result: (i64,) = sqlx::query_as(query)
+            .bind(user_id)
+            .bind(user_id)
+            .bind(event_prefix)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(result.0)
+    }
+}
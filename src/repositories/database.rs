@@ -1,6 +1,6 @@
 use sqlx::{MySqlPool, MySql, Pool};
 use crate::config::Config;
-use crate::utils::AppResult;
+use crate::utils::{AppResult, AppError};
 
 pub type DatabasePool = Pool<MySql>;
 
@@ -23,8 +23,10 @@ pub async fn create_pool(config: &Config) -> AppResult<DatabasePool> {
 pub async fn run_migrations(pool: &DatabasePool) -> AppResult<()> {
     tracing::info!("Running database migrations...");
     
-    // 这里可以添加数据库迁移逻辑
-    // sqlx::migrate!("./migrations").run(pool).await?;
+    sqlx::migrate!("./migrations")
+        .run(pool)
+        .await
+        .map_err(|e| AppError::internal(format!("Migration failed: {}", e)))?;
     
     tracing::info!("Database migrations completed");
     
@@ -1,6 +1,10 @@
 pub mod database;
 pub mod redis_repo;
 pub mod user;
+pub mod user_wallet;
+pub mod user_totp;
+pub mod api_key;
+pub mod audit_log;
 pub mod solana;
 pub mod trade;
 pub mod commission;
@@ -13,6 +17,10 @@ use crate::utils::AppResult;
 pub use database::*;
 pub use redis_repo::*;
 pub use user::*;
+pub use user_wallet::*;
+pub use user_totp::*;
+pub use api_key::*;
+pub use audit_log::*;
 pub use solana::*;
 pub use trade::*;
 pub use commission::*;
@@ -20,6 +28,10 @@ pub use listen::*;
 
 pub struct RepositoriesImpl {
     pub user: UserRepository,
+    pub user_wallet: UserWalletRepository,
+    pub user_totp: UserTotpRepository,
+    pub api_key: ApiKeyRepository,
+    pub audit_log: AuditLogRepository,
     pub solana: SolanaRepository,
     pub trade: TradeRepository,
     pub commission: CommissionRepository,
@@ -30,10 +42,19 @@ pub struct RepositoriesImpl {
 impl RepositoriesImpl {
     pub async fn new(config: Arc<Config>) -> AppResult<Arc<Self>> {
         let database = create_pool(&config).await?;
+        if config.mysql.auto_migrate {
+            run_migrations(&database).await?;
+        } else {
+            tracing::info!("Automatic migrations disabled, run them with `sqlx migrate run`");
+        }
         let redis = RedisRepository::new(&config).await?;
         
         let repositories = Self {
             user: UserRepository::new(database.clone()),
+            user_wallet: UserWalletRepository::new(database.clone()),
+            user_totp: UserTotpRepository::new(database.clone()),
+            api_key: ApiKeyRepository::new(database.clone()),
+            audit_log: AuditLogRepository::new(database.clone()),
             solana: SolanaRepository::new(database.clone()),
             trade: TradeRepository::new(database.clone()),
             commission: CommissionRepository::new(database.clone()),
@@ -48,6 +69,22 @@ impl RepositoriesImpl {
         &self.user
     }
 
+    pub fn user_wallet_repository(&self) -> &UserWalletRepository {
+        &self.user_wallet
+    }
+
+    pub fn user_totp_repository(&self) -> &UserTotpRepository {
+        &self.user_totp
+    }
+
+    pub fn api_key_repository(&self) -> &ApiKeyRepository {
+        &self.api_key
+    }
+
+    pub fn audit_log_repository(&self) -> &AuditLogRepository {
+        &self.audit_log
+    }
+
     pub fn solana_repository(&self) -> &SolanaRepository {
         &self.solana
     }
@@ -4,6 +4,17 @@ use crate::config::Config;
 use crate::utils::AppResult;
 use serde::{Serialize, Deserialize};
 
+/// 限流检查结果
+#[derive(Debug, Clone, Copy)]
+pub struct RateLimitStatus {
+    pub allowed: bool,
+    pub limit: u32,
+    pub remaining: u32,
+    /// 距离窗口内最早一次请求过期的毫秒数，被拒绝时即为需要等待的时间
+    pub reset_after_ms: u64,
+}
+
+#[derive(Clone)]
 pub struct RedisRepository {
     connection: ConnectionManager,
 }
@@ -23,11 +34,11 @@ impl RedisRepository {
         Ok(Self { connection })
     }
     
-    pub async fn get<T>(&mut self, key: &str) -> AppResult<Option<T>>
+    pub async fn get<T>(&self, key: &str) -> AppResult<Option<T>>
     where
         T: for<'de> Deserialize<'de>,
     {
-        let value: Option<String> = self.connection.get(key).await?;
+        let value: Option<String> = self.connection.clone().get(key).await?;
         match value {
             Some(v) => {
                 let result = serde_json::from_str(&v)?;
@@ -37,7 +48,7 @@ impl RedisRepository {
         }
     }
     
-    pub async fn set<T>(&mut self, key: &str, value: &T, ttl_seconds: Option<usize>) -> AppResult<()>
+    pub async fn set<T>(&self, key: &str, value: &T, ttl_seconds: Option<usize>) -> AppResult<()>
     where
         T: Serialize,
     {
@@ -45,55 +56,169 @@ impl RedisRepository {
         
         match ttl_seconds {
             Some(ttl) => {
-                self.connection.set_ex::<_, _, ()>(key, serialized, ttl.try_into().unwrap()).await?;
+                self.connection.clone().set_ex::<_, _, ()>(key, serialized, ttl.try_into().unwrap()).await?;
             }
             None => {
-                self.connection.set::<_, _, ()>(key, serialized).await?;
+                self.connection.clone().set::<_, _, ()>(key, serialized).await?;
             }
         }
         
         Ok(())
     }
     
-    pub async fn delete(&mut self, key: &str) -> AppResult<()> {
-        self.connection.del::<_, ()>(key).await?;
+    pub async fn delete(&self, key: &str) -> AppResult<()> {
+        self.connection.clone().del::<_, ()>(key).await?;
         Ok(())
     }
     
-    pub async fn exists(&mut self, key: &str) -> AppResult<bool> {
-        let result: bool = self.connection.exists(key).await?;
+    pub async fn exists(&self, key: &str) -> AppResult<bool> {
+        let result: bool = self.connection.clone().exists(key).await?;
         Ok(result)
     }
     
-    pub async fn increment(&mut self, key: &str) -> AppResult<i64> {
-        let result: i64 = self.connection.incr(key, 1).await?;
+    pub async fn increment(&self, key: &str) -> AppResult<i64> {
+        let result: i64 = self.connection.clone().incr(key, 1).await?;
         Ok(result)
     }
     
-    pub async fn set_string(&mut self, key: &str, value: &str, ttl_seconds: Option<usize>) -> AppResult<()> {
+    /// 原子地读取并删除键（GETDEL）
+    pub async fn take<T>(&self, key: &str) -> AppResult<Option<T>>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        let value: Option<String> = redis::cmd("GETDEL")
+            .arg(key)
+            .query_async(&mut self.connection.clone())
+            .await?;
+        match value {
+            Some(v) => Ok(Some(serde_json::from_str(&v)?)),
+            None => Ok(None),
+        }
+    }
+    
+    /// 当键的当前值等于`expected`时原子地替换为`value`，返回是否替换成功
+    pub async fn compare_and_swap(&self, key: &str, expected: &str, value: &str, ttl_seconds: usize) -> AppResult<bool> {
+        let script = redis::Script::new(
+            r"
+            if redis.call('GET', KEYS[1]) == ARGV[1] then
+                redis.call('SET', KEYS[1], ARGV[2], 'EX', ARGV[3])
+                return 1
+            end
+            return 0
+            ",
+        );
+        let swapped: i32 = script
+            .key(key)
+            .arg(expected)
+            .arg(value)
+            .arg(ttl_seconds)
+            .invoke_async(&mut self.connection.clone())
+            .await?;
+        Ok(swapped == 1)
+    }
+    
+    /// 计数加一，首次创建时设置过期时间，返回当前计数
+    ///
+    /// INCR与EXPIRE在同一脚本中执行，避免进程在两者之间退出留下永不过期的计数；
+    /// 计数已存在但没有过期时间时同样补上。
+    pub async fn increment_with_ttl(&self, key: &str, ttl_seconds: usize) -> AppResult<i64> {
+        let script = redis::Script::new(
+            r"
+            local count = redis.call('INCR', KEYS[1])
+            if count == 1 or redis.call('TTL', KEYS[1]) == -1 then
+                redis.call('EXPIRE', KEYS[1], ARGV[1])
+            end
+            return count
+            ",
+        );
+        let count: i64 = script
+            .key(key)
+            .arg(ttl_seconds)
+            .invoke_async(&mut self.connection.clone())
+            .await?;
+        Ok(count)
+    }
+    
+    /// 滑动窗口限流：有序集合中保存窗口内每次请求的时间戳，检查与计数在同一脚本中原子完成
+    pub async fn rate_limit(&self, key: &str, limit: u32, window_seconds: u64) -> AppResult<RateLimitStatus> {
+        let script = redis::Script::new(
+            r"
+            local now = tonumber(ARGV[1])
+            local window = tonumber(ARGV[2])
+            local limit = tonumber(ARGV[3])
+            redis.call('ZREMRANGEBYSCORE', KEYS[1], '-inf', now - window)
+            local count = redis.call('ZCARD', KEYS[1])
+            local allowed = 0
+            if count < limit then
+                redis.call('ZADD', KEYS[1], now, ARGV[4])
+                redis.call('PEXPIRE', KEYS[1], window)
+                count = count + 1
+                allowed = 1
+            end
+            local reset = window
+            local oldest = redis.call('ZRANGE', KEYS[1], 0, 0, 'WITHSCORES')
+            if oldest[2] then
+                reset = tonumber(oldest[2]) + window - now
+            end
+            return {allowed, count, reset}
+            ",
+        );
+        let now = chrono::Utc::now().timestamp_millis();
+        let member = format!("{}-{}", now, uuid::Uuid::new_v4().simple());
+        let (allowed, count, reset): (i64, i64, i64) = script
+            .key(key)
+            .arg(now)
+            .arg(window_seconds * 1000)
+            .arg(limit)
+            .arg(member)
+            .invoke_async(&mut self.connection.clone())
+            .await?;
+
+        Ok(RateLimitStatus {
+            allowed: allowed == 1,
+            limit,
+            remaining: limit.saturating_sub(count as u32),
+            reset_after_ms: reset.max(0) as u64,
+        })
+    }
+    
+    /// 键不存在时写入并设置过期时间，返回是否写入成功
+    pub async fn set_if_absent(&self, key: &str, value: &str, ttl_seconds: usize) -> AppResult<bool> {
+        let result: Option<String> = redis::cmd("SET")
+            .arg(key)
+            .arg(value)
+            .arg("NX")
+            .arg("EX")
+            .arg(ttl_seconds)
+            .query_async(&mut self.connection.clone())
+            .await?;
+        Ok(result.is_some())
+    }
+    
+    pub async fn set_string(&self, key: &str, value: &str, ttl_seconds: Option<usize>) -> AppResult<()> {
         match ttl_seconds {
             Some(ttl) => {
-                self.connection.set_ex::<_, _, ()>(key, value, ttl.try_into().unwrap()).await?;
+                self.connection.clone().set_ex::<_, _, ()>(key, value, ttl.try_into().unwrap()).await?;
             }
             None => {
-                self.connection.set::<_, _, ()>(key, value).await?;
+                self.connection.clone().set::<_, _, ()>(key, value).await?;
             }
         }
         Ok(())
     }
     
-    pub async fn get_string(&mut self, key: &str) -> AppResult<Option<String>> {
-        let result: Option<String> = self.connection.get(key).await?;
+    pub async fn get_string(&self, key: &str) -> AppResult<Option<String>> {
+        let result: Option<String> = self.connection.clone().get(key).await?;
         Ok(result)
     }
     
-    pub async fn health_check(&mut self) -> AppResult<()> {
-        let _: String = redis::cmd("PING").query_async(&mut self.connection).await?;
+    pub async fn health_check(&self) -> AppResult<()> {
+        let _: String = redis::cmd("PING").query_async(&mut self.connection.clone()).await?;
         Ok(())
     }
     
     // 缓存相关的便捷方法
-    pub async fn cache_token_info<T>(&mut self, mint: &str, data: &T, ttl_seconds: usize) -> AppResult<()>
+    pub async fn cache_token_info<T>(&self, mint: &str, data: &T, ttl_seconds: usize) -> AppResult<()>
     where
         T: Serialize,
     {
@@ -101,7 +226,7 @@ impl RedisRepository {
         self.set(&key, data, Some(ttl_seconds)).await
     }
     
-    pub async fn get_cached_token_info<T>(&mut self, mint: &str) -> AppResult<Option<T>>
+    pub async fn get_cached_token_info<T>(&self, mint: &str) -> AppResult<Option<T>>
     where
         T: for<'de> Deserialize<'de>,
     {
@@ -109,24 +234,287 @@ impl RedisRepository {
         self.get(&key).await
     }
     
-    pub async fn cache_user_session(&mut self, user_id: u32, token: &str, ttl_seconds: usize) -> AppResult<()> {
-        let key = format!("user:session:{}", user_id);
-        self.set_string(&key, token, Some(ttl_seconds)).await
+    pub async fn cache_user_session(&self, user_id: u32, session_id: &str, ttl_seconds: usize) -> AppResult<()> {
+        let key = format!("user:session:{}:{}", user_id, session_id);
+        let index_key = format!("user:sessions:{}", user_id);
+        self.set_string(&key, "1", Some(ttl_seconds)).await?;
+        self.connection.clone().sadd::<_, _, ()>(&index_key, session_id).await?;
+        self.connection.clone().expire::<_, ()>(&index_key, ttl_seconds as i64).await?;
+        Ok(())
+    }
+    
+    pub async fn get_user_session(&self, user_id: u32, session_id: &str) -> AppResult<Option<String>> {
+        let key = format!("user:session:{}:{}", user_id, session_id);
+        self.get_string(&key).await
+    }
+    
+    pub async fn invalidate_user_session(&self, user_id: u32, session_id: &str) -> AppResult<()> {
+        let key = format!("user:session:{}:{}", user_id, session_id);
+        let index_key = format!("user:sessions:{}", user_id);
+        self.delete(&key).await?;
+        self.connection.clone().srem::<_, _, ()>(&index_key, session_id).await?;
+        Ok(())
+    }
+    
+    pub async fn list_user_sessions(&self, user_id: u32) -> AppResult<Vec<String>> {
+        let index_key = format!("user:sessions:{}", user_id);
+        let session_ids: Vec<String> = self.connection.clone().smembers(&index_key).await?;
+        Ok(session_ids)
+    }
+    
+    pub async fn invalidate_all_user_sessions(&self, user_id: u32) -> AppResult<Vec<String>> {
+        let index_key = format!("user:sessions:{}", user_id);
+        let session_ids: Vec<String> = self.connection.clone().smembers(&index_key).await?;
+        for session_id in &session_ids {
+            self.delete(&format!("user:session:{}:{}", user_id, session_id)).await?;
+        }
+        self.delete(&index_key).await?;
+        Ok(session_ids)
+    }
+    
+    pub async fn store_refresh_token<T>(&self, token_hash: &str, record: &T, ttl_seconds: usize) -> AppResult<()>
+    where
+        T: Serialize,
+    {
+        let key = format!("user:refresh:{}", token_hash);
+        self.set(&key, record, Some(ttl_seconds)).await
+    }
+    
+    pub async fn get_refresh_token<T>(&self, token_hash: &str) -> AppResult<Option<T>>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        let key = format!("user:refresh:{}", token_hash);
+        self.get(&key).await
+    }
+    
+    pub async fn set_refresh_family(&self, family_id: &str, token_hash: &str, ttl_seconds: usize) -> AppResult<()> {
+        let key = format!("user:refresh_family:{}", family_id);
+        self.set_string(&key, token_hash, Some(ttl_seconds)).await
+    }
+    
+    /// 将令牌族的当前令牌从`old_hash`轮换为`new_hash`，旧令牌已被使用过时返回false
+    pub async fn rotate_refresh_family(&self, family_id: &str, old_hash: &str, new_hash: &str, ttl_seconds: usize) -> AppResult<bool> {
+        let key = format!("user:refresh_family:{}", family_id);
+        self.compare_and_swap(&key, old_hash, new_hash, ttl_seconds).await
+    }
+    
+    pub async fn revoke_refresh_family(&self, family_id: &str) -> AppResult<()> {
+        let key = format!("user:refresh_family:{}", family_id);
+        self.delete(&key).await
+    }
+    
+    pub async fn increment_reset_requests(&self, email: &str, window_seconds: usize) -> AppResult<i64> {
+        let key = format!("user:reset_requests:{}:{}", email, window_seconds);
+        self.increment_with_ttl(&key, window_seconds).await
+    }
+    
+    pub async fn store_reset_code(&self, email: &str, code_hash: &str, ttl_seconds: usize) -> AppResult<()> {
+        let key = format!("user:reset_code:{}", email);
+        self.set_string(&key, code_hash, Some(ttl_seconds)).await?;
+        self.delete(&format!("user:reset_attempts:{}", email)).await
     }
     
-    pub async fn get_user_session(&mut self, user_id: u32) -> AppResult<Option<String>> {
-        let key = format!("user:session:{}", user_id);
+    pub async fn get_reset_code(&self, email: &str) -> AppResult<Option<String>> {
+        let key = format!("user:reset_code:{}", email);
         self.get_string(&key).await
     }
     
-    pub async fn invalidate_user_session(&mut self, user_id: u32) -> AppResult<()> {
-        let key = format!("user:session:{}", user_id);
+    pub async fn increment_reset_attempts(&self, email: &str, ttl_seconds: usize) -> AppResult<i64> {
+        let key = format!("user:reset_attempts:{}", email);
+        self.increment_with_ttl(&key, ttl_seconds).await
+    }
+    
+    pub async fn delete_reset_code(&self, email: &str) -> AppResult<()> {
+        self.delete(&format!("user:reset_code:{}", email)).await?;
+        self.delete(&format!("user:reset_attempts:{}", email)).await
+    }
+    
+    /// 登录挑战按钱包和随机数分别存放，同一钱包的新挑战不会覆盖尚未使用的旧挑战
+    pub fn login_challenge_key(wallet: &str, nonce: &str) -> String {
+        format!("wallet:challenge:{}:{}", wallet, nonce)
+    }
+    
+    pub async fn store_login_challenge<T>(&self, wallet: &str, nonce: &str, challenge: &T, ttl_seconds: usize) -> AppResult<()>
+    where
+        T: Serialize,
+    {
+        let key = Self::login_challenge_key(wallet, nonce);
+        self.set(&key, challenge, Some(ttl_seconds)).await
+    }
+    
+    pub async fn take_login_challenge<T>(&self, wallet: &str, nonce: &str) -> AppResult<Option<T>>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        let key = Self::login_challenge_key(wallet, nonce);
+        self.take(&key).await
+    }
+    
+    pub async fn mark_challenge_used(&self, nonce: &str, ttl_seconds: usize) -> AppResult<()> {
+        let key = format!("wallet:challenge:used:{}", nonce);
+        self.set_string(&key, "1", Some(ttl_seconds)).await
+    }
+    
+    pub async fn is_challenge_used(&self, nonce: &str) -> AppResult<bool> {
+        let key = format!("wallet:challenge:used:{}", nonce);
+        self.exists(&key).await
+    }
+    
+    pub async fn store_two_factor_ticket<T>(&self, ticket_hash: &str, ticket: &T, ttl_seconds: usize) -> AppResult<()>
+    where
+        T: Serialize,
+    {
+        let key = format!("user:2fa:ticket:{}", ticket_hash);
+        self.set(&key, ticket, Some(ttl_seconds)).await
+    }
+    
+    pub async fn get_two_factor_ticket<T>(&self, ticket_hash: &str) -> AppResult<Option<T>>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        let key = format!("user:2fa:ticket:{}", ticket_hash);
+        self.get(&key).await
+    }
+    
+    pub async fn take_two_factor_ticket<T>(&self, ticket_hash: &str) -> AppResult<Option<T>>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        let key = format!("user:2fa:ticket:{}", ticket_hash);
+        self.take(&key).await
+    }
+    
+    pub async fn increment_two_factor_attempts(&self, ticket_hash: &str, ttl_seconds: usize) -> AppResult<i64> {
+        let key = format!("user:2fa:attempts:{}", ticket_hash);
+        self.increment_with_ttl(&key, ttl_seconds).await
+    }
+    
+    /// 标记TOTP时间步已使用，返回false表示该验证码已被使用过
+    pub async fn mark_totp_step_used(&self, user_id: u32, step: u64, ttl_seconds: usize) -> AppResult<bool> {
+        let key = format!("user:2fa:used:{}:{}", user_id, step);
+        self.set_if_absent(&key, "1", ttl_seconds).await
+    }
+    
+    /// 登录失败计数，`subject`为被尝试的账户标识（邮箱或钱包）
+    pub async fn increment_login_failures(&self, subject: &str, window_seconds: usize) -> AppResult<i64> {
+        let key = format!("auth:failures:{}", subject);
+        self.increment_with_ttl(&key, window_seconds).await
+    }
+    
+    pub async fn clear_login_failures(&self, subject: &str) -> AppResult<()> {
+        let key = format!("auth:failures:{}", subject);
         self.delete(&key).await
     }
+    
+    pub async fn lock_account(&self, subject: &str, ttl_seconds: usize) -> AppResult<()> {
+        let key = format!("auth:locked:{}", subject);
+        self.set_string(&key, "1", Some(ttl_seconds)).await
+    }
+    
+    /// 账户锁定的剩余秒数，未锁定时返回`None`
+    pub async fn account_lock_ttl(&self, subject: &str) -> AppResult<Option<i64>> {
+        let key = format!("auth:locked:{}", subject);
+        let ttl: i64 = self.connection.clone().ttl(&key).await?;
+        Ok((ttl > 0).then_some(ttl))
+    }
+    
+    /// 交易监听已处理到的区块
+    pub async fn get_listener_checkpoint(&self) -> AppResult<Option<u64>> {
+        self.get("listener:last_slot").await
+    }
+    
+    pub async fn set_listener_checkpoint(&self, slot: u64) -> AppResult<()> {
+        self.set("listener:last_slot", &slot, None).await
+    }
+    
+    /// 记录交易监听多次重试仍未能处理的区块，等待之后重新处理
+    pub async fn add_skipped_slot(&self, slot: u64) -> AppResult<()> {
+        self.connection.clone().zadd::<_, _, _, ()>("listener:skipped_slots", slot, slot).await?;
+        Ok(())
+    }
+    
+    /// 最早的若干个待重新处理的区块
+    pub async fn skipped_slots(&self, limit: usize) -> AppResult<Vec<u64>> {
+        let slots: Vec<u64> = self
+            .connection
+            .clone()
+            .zrange("listener:skipped_slots", 0, limit as isize - 1)
+            .await?;
+        Ok(slots)
+    }
+    
+    pub async fn remove_skipped_slot(&self, slot: u64) -> AppResult<()> {
+        self.connection.clone().zrem::<_, _, ()>("listener:skipped_slots", slot).await?;
+        Ok(())
+    }
+    
+    pub async fn skipped_slot_count(&self) -> AppResult<u64> {
+        let count: u64 = self.connection.clone().zcard("listener:skipped_slots").await?;
+        Ok(count)
+    }
+    
+    /// 向Stream追加一条`payload`字段的消息，按近似长度裁剪，返回消息ID
+    pub async fn xadd(&self, stream: &str, payload: &str, max_len: usize) -> AppResult<String> {
+        let id: String = redis::cmd("XADD")
+            .arg(stream)
+            .arg("MAXLEN")
+            .arg("~")
+            .arg(max_len)
+            .arg("*")
+            .arg("payload")
+            .arg(payload)
+            .query_async(&mut self.connection.clone())
+            .await?;
+        Ok(id)
+    }
+    
+    /// 写入事件死信，只保留最近的`max_len`条
+    pub async fn push_dead_letter(&self, sink: &str, entry: &str, max_len: usize) -> AppResult<()> {
+        let key = format!("pipeline:dead_letter:{}", sink);
+        redis::pipe()
+            .atomic()
+            .lpush(&key, entry)
+            .ltrim(&key, 0, max_len as isize - 1)
+            .query_async::<_, ()>(&mut self.connection.clone())
+            .await?;
+        Ok(())
+    }
+    
+    /// 读取输出端最早的若干条死信（不删除），按写入顺序返回
+    pub async fn peek_dead_letters(&self, sink: &str, count: usize) -> AppResult<Vec<String>> {
+        if count == 0 {
+            return Ok(Vec::new());
+        }
+        let key = format!("pipeline:dead_letter:{}", sink);
+        let mut entries: Vec<String> = self.connection.clone().lrange(&key, -(count as isize), -1).await?;
+        entries.reverse();
+        Ok(entries)
+    }
+    
+    /// 删除已重放的死信，从最早一端按内容逐条删除，不受并发写入影响
+    pub async fn remove_dead_letters(&self, sink: &str, entries: &[String]) -> AppResult<()> {
+        if entries.is_empty() {
+            return Ok(());
+        }
+        let key = format!("pipeline:dead_letter:{}", sink);
+        let mut pipe = redis::pipe();
+        for entry in entries {
+            pipe.lrem(&key, -1, entry).ignore();
+        }
+        pipe.query_async::<_, ()>(&mut self.connection.clone()).await?;
+        Ok(())
+    }
+    
+    /// 输出端的死信数量
+    pub async fn dead_letter_count(&self, sink: &str) -> AppResult<u64> {
+        let count: u64 = self.connection.clone().llen(format!("pipeline:dead_letter:{}", sink)).await?;
+        Ok(count)
+    }
 }
 
 // Redis健康检查
 pub async fn health_check(config: &Config) -> AppResult<()> {
-    let mut redis = RedisRepository::new(config).await?;
+    let redis = RedisRepository::new(config).await?;
     redis.health_check().await
 }
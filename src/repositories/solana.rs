@@ -1,8 +1,9 @@
-use sqlx::MySqlPool;
-use crate::models::solana::{SolToken, SolTransaction, SolHolder, SolPool, SolStat};
+use sqlx::{MySqlConnection, MySqlPool};
+use crate::models::solana::{HolderDelta, SolToken, SolTransaction, SolHolder, SolPool, SolStat};
 use crate::utils::AppResult;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, DurationRound, Utc};
 
+#[derive(Clone)]
 pub struct SolanaRepository {
     pool: MySqlPool,
 }
@@ -60,6 +61,32 @@ impl SolanaRepository {
         Ok(tokens)
     }
     
+    pub async fn find_tokens_by_mints(&self, mints: &[String]) -> AppResult<Vec<SolToken>> {
+        if mints.is_empty() {
+            return Ok(Vec::new());
+        }
+        
+        let query = format!(
+            r#"
+            SELECT id, created_at, updated_at, deleted_at, create_time, ecosystem, mint, creator,
+                   symbol, name, decimals, supply, description, logo, banner_url, social,
+                   progress, price, price_change, volume, market_cap, holder_count
+            FROM cook_wm_sol_token 
+            WHERE mint IN ({}) AND deleted_at IS NULL
+        "#,
+            vec!["?"; mints.len()].join(", ")
+        );
+        
+        let mut query_builder = sqlx::query_as::<_, SolToken>(&query);
+        for mint in mints {
+            query_builder = query_builder.bind(mint);
+        }
+        
+        let tokens = query_builder.fetch_all(&self.pool).await?;
+        
+        Ok(tokens)
+    }
+    
     pub async fn update_token_price(&self, mint: &str, price: f64, market_cap: f64) -> AppResult<()> {
         let query = r#"
             UPDATE cook_wm_sol_token 
@@ -78,13 +105,15 @@ impl SolanaRepository {
     }
     
     // Transaction相关方法
+    /// 写入交易，按签名幂等：签名已存在时不修改并返回已有记录的ID
     pub async fn create_transaction(&self, transaction: &SolTransaction) -> AppResult<u32> {
         let query = r#"
             INSERT INTO cook_wm_sol_transaction (
                 create_time, mint, curve, signature, signer, token_amount, sol_amount,
-                new_price_usd, new_price_sol, is_buy, volume_usd, slot, pnl, transfer_type,
+                new_price_usd, new_price_sol, is_buy, volume_usd, slot, commitment, pnl, transfer_type,
                 created_at, updated_at
-            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, NOW(), NOW())
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, NOW(), NOW())
+            ON DUPLICATE KEY UPDATE id = LAST_INSERT_ID(id)
         "#;
         
         let result = sqlx::query(query)
@@ -100,6 +129,7 @@ impl SolanaRepository {
             .bind(transaction.is_buy)
             .bind(transaction.volume_usd)
             .bind(&transaction.slot)
+            .bind(&transaction.commitment)
             .bind(transaction.pnl)
             .bind(transaction.transfer_type)
             .execute(&self.pool)
@@ -108,11 +138,243 @@ impl SolanaRepository {
         Ok(result.last_insert_id() as u32)
     }
     
+    /// 写入监听到的交易并在同一事务中更新持仓和统计
+    ///
+    /// 仓位类型和已实现盈亏按交易前的持仓计算。签名已存在时不做任何修改并返回`false`。
+    pub async fn record_trade(&self, transaction: &mut SolTransaction) -> AppResult<bool> {
+        let mut tx = self.pool.begin().await?;
+        
+        let holding: Option<(f64, f64)> = sqlx::query_as(
+            "SELECT amount, bet FROM cook_wm_sol_holder WHERE mint = ? AND holder = ? FOR UPDATE",
+        )
+            .bind(&transaction.mint)
+            .bind(&transaction.signer)
+            .fetch_optional(&mut *tx)
+            .await?;
+        let (amount, bet) = holding.unwrap_or_default();
+        let delta = transaction.apply_to_holding(amount, bet);
+        
+        let query = r#"
+            INSERT IGNORE INTO cook_wm_sol_transaction (
+                create_time, mint, curve, signature, signer, token_amount, sol_amount,
+                new_price_usd, new_price_sol, is_buy, volume_usd, slot, commitment, slot_number, pnl, transfer_type,
+                created_at, updated_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, NOW(), NOW())
+        "#;
+        
+        let result = sqlx::query(query)
+            .bind(transaction.create_time)
+            .bind(&transaction.mint)
+            .bind(&transaction.curve)
+            .bind(&transaction.signature)
+            .bind(&transaction.signer)
+            .bind(transaction.token_amount)
+            .bind(transaction.sol_amount)
+            .bind(transaction.new_price_usd)
+            .bind(transaction.new_price_sol)
+            .bind(transaction.is_buy)
+            .bind(transaction.volume_usd)
+            .bind(&transaction.slot)
+            .bind(&transaction.commitment)
+            .bind(transaction.slot.parse::<u64>().ok())
+            .bind(transaction.pnl)
+            .bind(transaction.transfer_type)
+            .execute(&mut *tx)
+            .await?;
+        if result.rows_affected() == 0 {
+            return Ok(false);
+        }
+        transaction.id = result.last_insert_id() as u32;
+        
+        Self::apply_holder_delta(&mut tx, transaction, delta, transaction.new_price_usd).await?;
+        Self::add_stat(&mut tx, transaction).await?;
+        tx.commit().await?;
+        
+        Ok(true)
+    }
+    
+    /// 尚未最终确认的交易所在的slot，按升序返回不超过`max_slot`的前`limit`个
+    ///
+    /// 按数值列`slot_number`查询，可以使用`(commitment, slot_number)`索引。
+    pub async fn find_unfinalized_slots(&self, max_slot: u64, limit: u32) -> AppResult<Vec<u64>> {
+        let query = r#"
+            SELECT DISTINCT slot_number
+            FROM cook_wm_sol_transaction 
+            WHERE commitment = 'confirmed' AND slot_number <= ?
+            ORDER BY slot_number
+            LIMIT ?
+        "#;
+        
+        let slots: Vec<(u64,)> = sqlx::query_as(query)
+            .bind(max_slot)
+            .bind(limit)
+            .fetch_all(&self.pool)
+            .await?;
+        
+        Ok(slots.into_iter().map(|(slot,)| slot).collect())
+    }
+    
+    /// 将slot内的交易标记为已最终确认
+    pub async fn finalize_slot(&self, slot: u64) -> AppResult<u64> {
+        let result = sqlx::query(
+            "UPDATE cook_wm_sol_transaction SET commitment = 'finalized', updated_at = NOW() WHERE commitment = 'confirmed' AND slot_number = ?",
+        )
+            .bind(slot)
+            .execute(&self.pool)
+            .await?;
+        
+        Ok(result.rows_affected())
+    }
+    
+    /// 撤销被跳过的slot内尚未最终确认的交易，扣回对持仓和统计的影响后删除交易记录
+    ///
+    /// 交易可能被重新打包进其他区块，因此直接删除而不是软删除，避免签名唯一索引阻止重新写入。
+    pub async fn revert_slot(&self, slot: u64) -> AppResult<Vec<SolTransaction>> {
+        let mut tx = self.pool.begin().await?;
+        
+        let query = r#"
+            SELECT id, created_at, updated_at, deleted_at, create_time, mint, curve, signature,
+                   signer, token_amount, sol_amount, new_price_usd, new_price_sol, is_buy,
+                   volume_usd, slot, commitment, pnl, transfer_type
+            FROM cook_wm_sol_transaction 
+            WHERE commitment = 'confirmed' AND slot_number = ?
+            FOR UPDATE
+        "#;
+        
+        let transactions = sqlx::query_as::<_, SolTransaction>(query)
+            .bind(slot)
+            .fetch_all(&mut *tx)
+            .await?;
+        
+        for transaction in &transactions {
+            let delta = transaction.holder_delta();
+            let reverse = HolderDelta { amount: -delta.amount, bet: -delta.bet, pnl: -delta.pnl };
+            Self::apply_holder_delta(&mut tx, transaction, reverse, 0.0).await?;
+            Self::subtract_stat(&mut tx, transaction).await?;
+            
+            sqlx::query("DELETE FROM cook_wm_sol_transaction WHERE id = ?")
+                .bind(transaction.id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+        
+        Ok(transactions)
+    }
+    
+    /// 按增量更新持仓，价格为0时保留原价格
+    async fn apply_holder_delta(
+        conn: &mut MySqlConnection,
+        transaction: &SolTransaction,
+        delta: HolderDelta,
+        price_usd: f64,
+    ) -> AppResult<()> {
+        let query = r#"
+            INSERT INTO cook_wm_sol_holder (mint, holder, amount, price_usd, bet, pnl, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, NOW(), NOW())
+            ON DUPLICATE KEY UPDATE
+            amount = amount + VALUES(amount),
+            price_usd = IF(VALUES(price_usd) > 0, VALUES(price_usd), price_usd),
+            bet = bet + VALUES(bet),
+            pnl = pnl + VALUES(pnl),
+            updated_at = NOW()
+        "#;
+        
+        sqlx::query(query)
+            .bind(&transaction.mint)
+            .bind(&transaction.signer)
+            .bind(delta.amount)
+            .bind(price_usd)
+            .bind(delta.bet)
+            .bind(delta.pnl)
+            .execute(conn)
+            .await?;
+        
+        Ok(())
+    }
+    
+    /// 交易所属的统计时段（按小时）
+    fn stat_time(transaction: &SolTransaction) -> DateTime<Utc> {
+        transaction
+            .create_time
+            .duration_trunc(chrono::Duration::hours(1))
+            .unwrap_or(transaction.create_time)
+    }
+    
+    /// 将交易计入所属时段的统计
+    async fn add_stat(conn: &mut MySqlConnection, transaction: &SolTransaction) -> AppResult<()> {
+        let query = r#"
+            INSERT INTO cook_wm_sol_stat (
+                mint, create_time, volume_token, volume_sol, volume_usd, buy_count, sell_count,
+                buy_usdt, sell_usdt, price_usd, created_at, updated_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, NOW(), NOW())
+            ON DUPLICATE KEY UPDATE
+            volume_token = volume_token + VALUES(volume_token),
+            volume_sol = volume_sol + VALUES(volume_sol),
+            volume_usd = volume_usd + VALUES(volume_usd),
+            buy_count = buy_count + VALUES(buy_count),
+            sell_count = sell_count + VALUES(sell_count),
+            buy_usdt = buy_usdt + VALUES(buy_usdt),
+            sell_usdt = sell_usdt + VALUES(sell_usdt),
+            price_usd = IF(VALUES(price_usd) > 0, VALUES(price_usd), price_usd),
+            updated_at = NOW()
+        "#;
+        
+        let (buy_usdt, sell_usdt) = if transaction.is_buy { (transaction.volume_usd, 0.0) } else { (0.0, transaction.volume_usd) };
+        sqlx::query(query)
+            .bind(&transaction.mint)
+            .bind(Self::stat_time(transaction))
+            .bind(transaction.token_amount)
+            .bind(transaction.sol_amount)
+            .bind(transaction.volume_usd)
+            .bind(transaction.is_buy as u64)
+            .bind(!transaction.is_buy as u64)
+            .bind(buy_usdt)
+            .bind(sell_usdt)
+            .bind(transaction.new_price_usd)
+            .execute(conn)
+            .await?;
+        
+        Ok(())
+    }
+    
+    /// 从所属时段的统计中扣除交易，成交次数为无符号列，不能通过负增量的插入实现
+    async fn subtract_stat(conn: &mut MySqlConnection, transaction: &SolTransaction) -> AppResult<()> {
+        let query = r#"
+            UPDATE cook_wm_sol_stat SET
+            volume_token = volume_token - ?,
+            volume_sol = volume_sol - ?,
+            volume_usd = volume_usd - ?,
+            buy_count = buy_count - ?,
+            sell_count = sell_count - ?,
+            buy_usdt = buy_usdt - ?,
+            sell_usdt = sell_usdt - ?,
+            updated_at = NOW()
+            WHERE mint = ? AND create_time = ?
+        "#;
+        
+        let (buy_usdt, sell_usdt) = if transaction.is_buy { (transaction.volume_usd, 0.0) } else { (0.0, transaction.volume_usd) };
+        sqlx::query(query)
+            .bind(transaction.token_amount)
+            .bind(transaction.sol_amount)
+            .bind(transaction.volume_usd)
+            .bind(transaction.is_buy as u64)
+            .bind(!transaction.is_buy as u64)
+            .bind(buy_usdt)
+            .bind(sell_usdt)
+            .bind(&transaction.mint)
+            .bind(Self::stat_time(transaction))
+            .execute(conn)
+            .await?;
+        
+        Ok(())
+    }
+    
     pub async fn find_transactions_by_mint(&self, mint: &str, limit: u32, offset: u32) -> AppResult<Vec<SolTransaction>> {
         let query = r#"
             SELECT id, created_at, updated_at, deleted_at, create_time, mint, curve, signature,
                    signer, token_amount, sol_amount, new_price_usd, new_price_sol, is_buy,
-                   volume_usd, slot, pnl, transfer_type
+                   volume_usd, slot, commitment, pnl, transfer_type
             FROM cook_wm_sol_transaction 
             WHERE mint = ? AND deleted_at IS NULL
             ORDER BY create_time DESC
@@ -133,7 +395,7 @@ impl SolanaRepository {
         let query = r#"
             SELECT id, created_at, updated_at, deleted_at, create_time, mint, curve, signature,
                    signer, token_amount, sol_amount, new_price_usd, new_price_sol, is_buy,
-                   volume_usd, slot, pnl, transfer_type
+                   volume_usd, slot, commitment, pnl, transfer_type
             FROM cook_wm_sol_transaction 
             WHERE signer = ? AND deleted_at IS NULL
             ORDER BY create_time DESC
@@ -170,6 +432,21 @@ impl SolanaRepository {
         Ok(holders)
     }
     
+    pub async fn find_holders_by_wallet(&self, holder: &str) -> AppResult<Vec<SolHolder>> {
+        let query = r#"
+            SELECT id, created_at, updated_at, deleted_at, mint, holder, amount, price_usd, bet, pnl
+            FROM cook_wm_sol_holder 
+            WHERE holder = ? AND deleted_at IS NULL
+        "#;
+        
+        let holders = sqlx::query_as::<_, SolHolder>(query)
+            .bind(holder)
+            .fetch_all(&self.pool)
+            .await?;
+        
+        Ok(holders)
+    }
+    
     pub async fn update_holder(&self, mint: &str, holder: &str, amount: f64, price_usd: f64, bet: f64, pnl: f64) -> AppResult<()> {
         let query = r#"
             INSERT INTO cook_wm_sol_holder (mint, holder, amount, price_usd, bet, pnl, created_at, updated_at)
@@ -1,7 +1,7 @@
 use sqlx::MySqlPool;
 use crate::models::user::{User, CreateUserRequest};
 use crate::utils::{AppResult, AppError};
-use crate::utils::CryptoUtils;
+use crate::utils::{PasswordCheck, PasswordUtils};
 
 pub struct UserRepository {
     pool: MySqlPool,
@@ -19,7 +19,7 @@ impl UserRepository {
         }
         
         // 加密密码
-        let hashed_password = CryptoUtils::hash_password(&request.password);
+        let hashed_password = PasswordUtils::hash(&request.password)?;
         
         // 创建用户
         let mut user = User::new(request.username, request.email, hashed_password);
@@ -56,7 +56,7 @@ impl UserRepository {
     
     pub async fn find_by_id(&self, id: u32) -> AppResult<Option<User>> {
         let query = r#"
-            SELECT id, created_at, updated_at, deleted_at, username, email, password, parent,
+            SELECT id, created_at, updated_at, deleted_at, username, email, wallet, password, parent, role,
                    sol_commission, base_commission, eth_commission, sol_commission_total,
                    base_commission_total, eth_commission_total
             FROM cook_jcc_user 
@@ -73,7 +73,7 @@ impl UserRepository {
     
     pub async fn find_by_email(&self, email: &str) -> AppResult<Option<User>> {
         let query = r#"
-            SELECT id, created_at, updated_at, deleted_at, username, email, password, parent,
+            SELECT id, created_at, updated_at, deleted_at, username, email, wallet, password, parent, role,
                    sol_commission, base_commission, eth_commission, sol_commission_total,
                    base_commission_total, eth_commission_total
             FROM cook_jcc_user 
@@ -88,23 +88,123 @@ impl UserRepository {
         Ok(user)
     }
     
-    pub async fn update_user(&self, user: &User) -> AppResult<()> {
+    /// 根据任一已绑定钱包查找用户
+    pub async fn find_by_wallet(&self, wallet: &str) -> AppResult<Option<User>> {
         let query = r#"
-            UPDATE cook_jcc_user 
-            SET username = ?, sol_commission = ?, base_commission = ?, eth_commission = ?,
-                sol_commission_total = ?, base_commission_total = ?, eth_commission_total = ?,
-                updated_at = NOW()
-            WHERE id = ?
+            SELECT u.id, u.created_at, u.updated_at, u.deleted_at, u.username, u.email, u.wallet, u.password, u.parent, u.role,
+                   u.sol_commission, u.base_commission, u.eth_commission, u.sol_commission_total,
+                   u.base_commission_total, u.eth_commission_total
+            FROM cook_jcc_user u
+            INNER JOIN cook_jcc_user_wallets w ON w.user_id = u.id
+            WHERE w.wallet = ? AND u.deleted_at IS NULL
         "#;
         
-        sqlx::query(query)
+        let user = sqlx::query_as::<_, User>(query)
+            .bind(wallet)
+            .fetch_optional(&self.pool)
+            .await?;
+        
+        Ok(user)
+    }
+    
+    /// 钱包是否仍绑定在已注销的用户上（绑定记录和主钱包唯一键不会随注销释放）
+    pub async fn is_wallet_held_by_deleted_user(&self, wallet: &str) -> AppResult<bool> {
+        let query = r#"
+            SELECT COUNT(*) as count
+            FROM cook_jcc_user u
+            WHERE u.deleted_at IS NOT NULL
+              AND (u.wallet = ? OR EXISTS (
+                  SELECT 1 FROM cook_jcc_user_wallets w WHERE w.user_id = u.id AND w.wallet = ?
+              ))
+        "#;
+
+        let result: (i64,) = sqlx::query_as(query)
+            .bind(wallet)
+            .bind(wallet)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(result.0 > 0)
+    }
+    
+    /// 创建钱包登录用户，并在同一事务中登记绑定关系
+    pub async fn create_wallet_user(&self, username: String, wallet: String, network: &str, parent: Option<String>) -> AppResult<User> {
+        let mut user = User::with_wallet(username, wallet, parent.unwrap_or_default());
+        let mut tx = self.pool.begin().await?;
+        
+        let query = r#"
+            INSERT INTO cook_jcc_user (
+                username, email, wallet, password, parent, sol_commission, base_commission, 
+                eth_commission, sol_commission_total, base_commission_total, eth_commission_total,
+                created_at, updated_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, NOW(), NOW())
+        "#;
+        
+        let result = sqlx::query(query)
             .bind(&user.username)
+            .bind(&user.email)
+            .bind(&user.wallet)
+            .bind(&user.password)
+            .bind(&user.parent)
             .bind(user.sol_commission)
             .bind(user.base_commission)
             .bind(user.eth_commission)
             .bind(user.sol_commission_total)
             .bind(user.base_commission_total)
             .bind(user.eth_commission_total)
+            .execute(&mut *tx)
+            .await?;
+        
+        user.id = result.last_insert_id() as u32;
+
+        sqlx::query(
+            "INSERT INTO cook_jcc_user_wallets (user_id, wallet, network, created_at, updated_at) VALUES (?, ?, ?, NOW(), NOW())",
+        )
+            .bind(user.id)
+            .bind(&user.wallet)
+            .bind(network)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        
+        Ok(user)
+    }
+
+    /// 绑定邮箱和密码
+    pub async fn update_email(&self, id: u32, email: &str, password_hash: &str) -> AppResult<()> {
+        sqlx::query("UPDATE cook_jcc_user SET email = ?, password = ?, updated_at = NOW() WHERE id = ?")
+            .bind(email)
+            .bind(password_hash)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// 更新用户的主钱包（令牌中携带的钱包）
+    pub async fn update_wallet(&self, id: u32, wallet: Option<&str>) -> AppResult<()> {
+        sqlx::query("UPDATE cook_jcc_user SET wallet = ?, updated_at = NOW() WHERE id = ?")
+            .bind(wallet)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+    
+    /// 更新用户资料（用户名、密码）；佣金余额只通过`update_commission`增量修改
+    pub async fn update_user(&self, user: &User) -> AppResult<()> {
+        let query = r#"
+            UPDATE cook_jcc_user 
+            SET username = ?, password = ?, updated_at = NOW()
+            WHERE id = ?
+        "#;
+        
+        sqlx::query(query)
+            .bind(&user.username)
+            .bind(&user.password)
             .bind(user.id)
             .execute(&self.pool)
             .await?;
@@ -112,6 +212,17 @@ impl UserRepository {
         Ok(())
     }
     
+    /// 设置用户角色
+    pub async fn update_role(&self, id: u32, role: &str) -> AppResult<()> {
+        sqlx::query("UPDATE cook_jcc_user SET role = ?, updated_at = NOW() WHERE id = ?")
+            .bind(role)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+    
     pub async fn update_commission(&self, user_id: u32, sol_commission: f64, base_commission: f64, eth_commission: f64) -> AppResult<()> {
         let query = r#"
             UPDATE cook_jcc_user 
@@ -139,13 +250,51 @@ impl UserRepository {
         Ok(())
     }
     
+    pub async fn find_by_username(&self, username: &str) -> AppResult<Option<User>> {
+        let query = r#"
+            SELECT id, created_at, updated_at, deleted_at, username, email, wallet, password, parent, role,
+                   sol_commission, base_commission, eth_commission, sol_commission_total,
+                   base_commission_total, eth_commission_total
+            FROM cook_jcc_user 
+            WHERE username = ? AND deleted_at IS NULL
+        "#;
+        
+        let user = sqlx::query_as::<_, User>(query)
+            .bind(username)
+            .fetch_optional(&self.pool)
+            .await?;
+        
+        Ok(user)
+    }
+    
+    pub async fn update_password(&self, id: u32, password_hash: &str) -> AppResult<()> {
+        let query = "UPDATE cook_jcc_user SET password = ?, updated_at = NOW() WHERE id = ?";
+        
+        sqlx::query(query)
+            .bind(password_hash)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        
+        Ok(())
+    }
+    
+    /// 校验邮箱密码，遗留哈希校验通过后自动升级为Argon2id
     pub async fn verify_password(&self, email: &str, password: &str) -> AppResult<Option<User>> {
-        if let Some(user) = self.find_by_email(email).await? {
-            if CryptoUtils::verify_password(password, &user.password) {
-                return Ok(Some(user));
+        let Some(mut user) = self.find_by_email(email).await? else {
+            return Ok(None);
+        };
+        
+        match PasswordUtils::verify(password, &user.password) {
+            PasswordCheck::Invalid => Ok(None),
+            PasswordCheck::Valid => Ok(Some(user)),
+            PasswordCheck::ValidLegacy => {
+                user.password = PasswordUtils::hash(password)?;
+                self.update_password(user.id, &user.password).await?;
+                tracing::info!("Upgraded legacy password hash for user {}", user.id);
+                Ok(Some(user))
             }
         }
-        Ok(None)
     }
     
     pub async fn delete_user(&self, id: u32) -> AppResult<()> {
@@ -161,7 +310,7 @@ impl UserRepository {
     
     pub async fn list_users(&self, limit: u32, offset: u32) -> AppResult<Vec<User>> {
         let query = r#"
-            SELECT id, created_at, updated_at, deleted_at, username, email, password, parent,
+            SELECT id, created_at, updated_at, deleted_at, username, email, wallet, password, parent, role,
                    sol_commission, base_commission, eth_commission, sol_commission_total,
                    base_commission_total, eth_commission_total
             FROM cook_jcc_user 
@@ -0,0 +1,94 @@
+use sqlx::MySqlPool;
+use crate::models::user_totp::UserTotp;
+use crate::utils::AppResult;
+
+pub struct UserTotpRepository {
+    pool: MySqlPool,
+}
+
+impl UserTotpRepository {
+    pub fn new(pool: MySqlPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn find_by_user_id(&self, user_id: u32) -> AppResult<Option<UserTotp>> {
+        let query = r#"
+            SELECT user_id, created_at, updated_at, secret, enabled, recovery_codes
+            FROM cook_jcc_user_totp
+            WHERE user_id = ?
+        "#;
+
+        let totp = sqlx::query_as::<_, UserTotp>(query)
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(totp)
+    }
+
+    /// 保存待确认的密钥，覆盖之前未启用的设置
+    pub async fn save_pending(&self, user_id: u32, encrypted_secret: &str) -> AppResult<()> {
+        let query = r#"
+            INSERT INTO cook_jcc_user_totp (user_id, secret, enabled, recovery_codes, created_at, updated_at)
+            VALUES (?, ?, 0, '[]', NOW(), NOW())
+            ON DUPLICATE KEY UPDATE secret = VALUES(secret), enabled = 0, recovery_codes = '[]', updated_at = NOW()
+        "#;
+
+        sqlx::query(query)
+            .bind(user_id)
+            .bind(encrypted_secret)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// 启用双重认证并保存恢复码哈希
+    pub async fn enable(&self, user_id: u32, recovery_code_hashes: &[String]) -> AppResult<()> {
+        sqlx::query("UPDATE cook_jcc_user_totp SET enabled = 1, recovery_codes = ?, updated_at = NOW() WHERE user_id = ?")
+            .bind(serde_json::to_string(recovery_code_hashes)?)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// 消费一个恢复码，返回是否成功；条件更新保证并发下同一恢复码只能使用一次
+    pub async fn consume_recovery_code(&self, totp: &UserTotp, code_hash: &str) -> AppResult<bool> {
+        let mut remaining = totp.recovery_code_hashes();
+        let Some(index) = remaining.iter().position(|hash| hash == code_hash) else {
+            return Ok(false);
+        };
+        remaining.remove(index);
+
+        let result = sqlx::query(
+            "UPDATE cook_jcc_user_totp SET recovery_codes = ?, updated_at = NOW() WHERE user_id = ? AND recovery_codes = ?",
+        )
+            .bind(serde_json::to_string(&remaining)?)
+            .bind(totp.user_id)
+            .bind(&totp.recovery_codes)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// 已保存的双重认证密钥数量（含未启用的）
+    pub async fn count(&self) -> AppResult<i64> {
+        let result: (i64,) = sqlx::query_as("SELECT COUNT(*) as count FROM cook_jcc_user_totp")
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(result.0)
+    }
+
+    pub async fn delete(&self, user_id: u32) -> AppResult<()> {
+        sqlx::query("DELETE FROM cook_jcc_user_totp WHERE user_id = ?")
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,84 @@
+use sqlx::MySqlPool;
+use crate::models::user_wallet::UserWallet;
+use crate::utils::{AppResult, AppError};
+
+pub struct UserWalletRepository {
+    pool: MySqlPool,
+}
+
+impl UserWalletRepository {
+    pub fn new(pool: MySqlPool) -> Self {
+        Self { pool }
+    }
+
+    /// 绑定钱包，钱包已被任意账户绑定时返回错误
+    pub async fn link_wallet(&self, user_id: u32, wallet: &str, network: &str) -> AppResult<UserWallet> {
+        if self.find_by_wallet(wallet).await?.is_some() {
+            return Err(AppError::validation("Wallet already linked"));
+        }
+
+        let query = r#"
+            INSERT INTO cook_jcc_user_wallets (user_id, wallet, network, created_at, updated_at)
+            VALUES (?, ?, ?, NOW(), NOW())
+        "#;
+
+        let result = sqlx::query(query)
+            .bind(user_id)
+            .bind(wallet)
+            .bind(network)
+            .execute(&self.pool)
+            .await?;
+
+        let now = chrono::Utc::now();
+        Ok(UserWallet {
+            id: result.last_insert_id() as u32,
+            created_at: now,
+            updated_at: now,
+            user_id,
+            wallet: wallet.to_string(),
+            network: network.to_string(),
+        })
+    }
+
+    pub async fn find_by_wallet(&self, wallet: &str) -> AppResult<Option<UserWallet>> {
+        let query = r#"
+            SELECT id, created_at, updated_at, user_id, wallet, network
+            FROM cook_jcc_user_wallets
+            WHERE wallet = ?
+        "#;
+
+        let wallet = sqlx::query_as::<_, UserWallet>(query)
+            .bind(wallet)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(wallet)
+    }
+
+    pub async fn find_by_user_id(&self, user_id: u32) -> AppResult<Vec<UserWallet>> {
+        let query = r#"
+            SELECT id, created_at, updated_at, user_id, wallet, network
+            FROM cook_jcc_user_wallets
+            WHERE user_id = ?
+            ORDER BY id
+        "#;
+
+        let wallets = sqlx::query_as::<_, UserWallet>(query)
+            .bind(user_id)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(wallets)
+    }
+
+    /// 解绑钱包，返回是否有记录被删除
+    pub async fn unlink_wallet(&self, user_id: u32, wallet: &str) -> AppResult<bool> {
+        let result = sqlx::query("DELETE FROM cook_jcc_user_wallets WHERE user_id = ? AND wallet = ?")
+            .bind(user_id)
+            .bind(wallet)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
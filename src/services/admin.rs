@@ -0,0 +1,242 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use crate::blockchain::{DeadLetterReplay, EventPipeline, PriceData, PriceService, SolanaClientService};
+use crate::config::{CommissionConfig, Config};
+use crate::models::audit_log::{ClientContext, NewAuditLog};
+use crate::models::user::{User, UserRole};
+use crate::repositories::RepositoriesImpl;
+use crate::services::{AuditLogListResponse, AuditServiceImpl, BackfillRequest, BackfillSummary, SolanaServiceImpl};
+use crate::utils::{AppError, AppResult};
+
+/// 默认分页大小
+const DEFAULT_PAGE_SIZE: u32 = 20;
+/// 最大分页大小
+const MAX_PAGE_SIZE: u32 = 100;
+/// 管理操作的审计事件前缀
+const ADMIN_EVENT_PREFIX: &str = "admin.";
+/// 单次刷新的最大代币数，与Jupiter单次查询上限一致
+const MAX_PRICE_REFRESH_MINTS: usize = 100;
+/// 默认单次重放的死信数
+const DEFAULT_REPLAY_LIMIT: usize = 100;
+/// 单次重放的最大死信数
+const MAX_REPLAY_LIMIT: usize = 1000;
+
+/// 用户列表响应
+#[derive(Debug, Serialize)]
+pub struct UserListResponse {
+    pub users: Vec<User>,
+    pub total: i64,
+    pub page: u32,
+    pub limit: u32,
+}
+
+/// 设置用户角色请求
+#[derive(Debug, Deserialize)]
+pub struct SetUserRoleRequest {
+    pub user_id: u32,
+    pub role: String,
+}
+
+/// 刷新价格请求
+#[derive(Debug, Deserialize)]
+pub struct PriceRefreshRequest {
+    pub mints: Vec<String>,
+}
+
+/// 重放死信请求
+#[derive(Debug, Deserialize)]
+pub struct ReplayDeadLettersRequest {
+    pub sink: String,
+    pub limit: Option<usize>,
+}
+
+/// 管理后台服务实现，所有修改操作先写入审计日志再执行
+pub struct AdminServiceImpl {
+    config: Arc<Config>,
+    repositories: Arc<RepositoriesImpl>,
+    audit: AuditServiceImpl,
+}
+
+impl AdminServiceImpl {
+    /// 创建新的管理后台服务实例
+    pub fn new(config: Arc<Config>, repositories: Arc<RepositoriesImpl>, audit: AuditServiceImpl) -> Self {
+        Self { config, repositories, audit }
+    }
+
+    /// 分页查询用户
+    pub async fn list_users(&self, page: Option<u32>, limit: Option<u32>) -> AppResult<UserListResponse> {
+        let (page, limit) = Self::pagination(page, limit);
+        let repository = self.repositories.user_repository();
+        let users = repository.list_users(limit, (page - 1) * limit).await?;
+        let total = repository.count_users().await?;
+
+        Ok(UserListResponse { users, total, page, limit })
+    }
+
+    /// 软删除用户并注销其全部会话
+    pub async fn delete_user(&self, admin: &User, user_id: u32, client: &ClientContext) -> AppResult<()> {
+        if admin.id == user_id {
+            return Err(AppError::validation("Cannot delete your own account"));
+        }
+
+        let target = self.find_user(user_id).await?;
+        if target.role() == UserRole::Admin {
+            return Err(AppError::validation("Demote the admin before deleting the account"));
+        }
+
+        self.audit(
+            NewAuditLog::new(Some(admin.id), "admin.user.delete")
+                .target(user_id.to_string())
+                .detail(json!({ "username": target.username, "email": target.email, "wallet": target.wallet })),
+            client,
+        )
+        .await?;
+
+        self.repositories.user_repository().delete_user(user_id).await?;
+
+        let redis = self.repositories.redis_repository();
+        for session_id in redis.invalidate_all_user_sessions(user_id).await? {
+            redis.revoke_refresh_family(&session_id).await?;
+        }
+        Ok(())
+    }
+
+    /// 设置用户角色，不能修改自己的角色以免误删最后一个管理员
+    pub async fn set_user_role(&self, admin: &User, req: SetUserRoleRequest, client: &ClientContext) -> AppResult<()> {
+        let role = UserRole::parse(&req.role)
+            .ok_or_else(|| AppError::validation(format!("Unknown role: {}", req.role)))?;
+        if admin.id == req.user_id {
+            return Err(AppError::validation("Cannot change your own role"));
+        }
+
+        let target = self.find_user(req.user_id).await?;
+        if target.role() == role {
+            return Ok(());
+        }
+
+        self.audit(
+            NewAuditLog::new(Some(admin.id), "admin.user.role")
+                .target(req.user_id.to_string())
+                .detail(json!({ "from": target.role(), "to": role })),
+            client,
+        )
+        .await?;
+
+        self.repositories.user_repository().update_role(req.user_id, role.as_str()).await
+    }
+
+    /// 配置文件中的佣金比例
+    pub fn commission_rates(&self) -> CommissionConfig {
+        self.config.commission.clone()
+    }
+
+    /// 强制刷新代币价格，并同步到代币表
+    pub async fn refresh_prices(
+        &self,
+        admin: &User,
+        price_service: &PriceService,
+        req: PriceRefreshRequest,
+        client: &ClientContext,
+    ) -> AppResult<HashMap<String, PriceData>> {
+        let mut mints: Vec<String> = req
+            .mints
+            .iter()
+            .map(|mint| mint.trim().to_string())
+            .filter(|mint| !mint.is_empty())
+            .collect();
+        mints.sort();
+        mints.dedup();
+        if mints.is_empty() || mints.len() > MAX_PRICE_REFRESH_MINTS {
+            return Err(AppError::validation(format!(
+                "Between 1 and {} mints are required",
+                MAX_PRICE_REFRESH_MINTS
+            )));
+        }
+
+        self.audit(
+            NewAuditLog::new(Some(admin.id), "admin.price.refresh").detail(json!({ "mints": mints })),
+            client,
+        )
+        .await?;
+
+        let prices = price_service.refresh_prices(&mints).await?;
+        let repository = self.repositories.solana_repository();
+        for price in prices.values() {
+            repository.update_token_price(&price.mint, price.price_usd, price.market_cap).await?;
+        }
+        Ok(prices)
+    }
+
+    /// 回填历史交易
+    pub async fn backfill_transactions(
+        &self,
+        admin: &User,
+        solana_service: &SolanaServiceImpl,
+        solana_client: &SolanaClientService,
+        req: BackfillRequest,
+        client: &ClientContext,
+    ) -> AppResult<BackfillSummary> {
+        self.audit(
+            NewAuditLog::new(Some(admin.id), "admin.transaction.backfill")
+                .target(req.address.trim())
+                .detail(json!({
+                    "before": req.before,
+                    "until": req.until,
+                    "start_slot": req.start_slot,
+                    "end_slot": req.end_slot,
+                })),
+            client,
+        )
+        .await?;
+
+        solana_service.backfill(solana_client, &req).await
+    }
+
+    /// 重放事件输出端的死信
+    pub async fn replay_dead_letters(
+        &self,
+        admin: &User,
+        event_pipeline: &EventPipeline,
+        req: ReplayDeadLettersRequest,
+        client: &ClientContext,
+    ) -> AppResult<DeadLetterReplay> {
+        let limit = req.limit.unwrap_or(DEFAULT_REPLAY_LIMIT).clamp(1, MAX_REPLAY_LIMIT);
+
+        self.audit(
+            NewAuditLog::new(Some(admin.id), "admin.pipeline.replay")
+                .target(req.sink.trim())
+                .detail(json!({ "limit": limit })),
+            client,
+        )
+        .await?;
+
+        event_pipeline.replay_dead_letters(req.sink.trim(), limit).await
+    }
+
+    /// 分页查询管理操作的审计日志
+    pub async fn audit_logs(&self, page: Option<u32>, limit: Option<u32>) -> AppResult<AuditLogListResponse> {
+        self.audit.list(None, ADMIN_EVENT_PREFIX, page, limit).await
+    }
+
+    async fn find_user(&self, user_id: u32) -> AppResult<User> {
+        self.repositories
+            .user_repository()
+            .find_by_id(user_id)
+            .await?
+            .ok_or_else(|| AppError::not_found("User not found"))
+    }
+
+    /// 在执行管理操作前写入审计日志，写入失败时不执行该操作
+    async fn audit(&self, entry: NewAuditLog, client: &ClientContext) -> AppResult<()> {
+        tracing::info!("Admin action {} by user {:?} on {:?}", entry.event, entry.user_id, entry.target);
+        self.audit.record_required(entry.client(client)).await
+    }
+
+    fn pagination(page: Option<u32>, limit: Option<u32>) -> (u32, u32) {
+        let page = page.unwrap_or(1).max(1);
+        let limit = limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+        (page, limit)
+    }
+}
@@ -0,0 +1,191 @@
+use std::net::IpAddr;
+use std::sync::Arc;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use crate::models::api_key::{is_valid_ip_entry, ApiKey, ApiKeyScope};
+use crate::models::user::User;
+use crate::repositories::RepositoriesImpl;
+use crate::utils::{AppError, AppResult, CryptoUtils};
+
+/// 密钥明文前缀
+const API_KEY_PREFIX: &str = "wm_";
+/// 密钥随机部分长度
+const API_KEY_RANDOM_LENGTH: usize = 40;
+/// 列表中展示的明文长度
+const API_KEY_DISPLAY_LENGTH: usize = 11;
+/// 每个用户最多持有的有效密钥数
+const MAX_API_KEYS_PER_USER: usize = 20;
+/// last_used_at 的最小刷新间隔（秒），避免每个请求都写库
+const LAST_USED_REFRESH_SECONDS: i64 = 60;
+
+/// 创建API密钥请求
+#[derive(Debug, Deserialize)]
+pub struct CreateApiKeyRequest {
+    pub name: String,
+    pub scopes: Vec<String>,
+    pub ip_allowlist: Option<Vec<String>>,
+}
+
+/// API密钥信息
+#[derive(Debug, Serialize)]
+pub struct ApiKeyResponse {
+    pub id: u32,
+    pub name: String,
+    pub prefix: String,
+    pub scopes: Vec<ApiKeyScope>,
+    pub ip_allowlist: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+impl From<ApiKey> for ApiKeyResponse {
+    fn from(key: ApiKey) -> Self {
+        Self {
+            scopes: key.scope_list(),
+            ip_allowlist: key.ip_list(),
+            id: key.id,
+            name: key.name,
+            prefix: key.prefix,
+            created_at: key.created_at,
+            last_used_at: key.last_used_at,
+        }
+    }
+}
+
+/// 创建API密钥响应，明文密钥仅在此时返回一次
+#[derive(Debug, Serialize)]
+pub struct CreateApiKeyResponse {
+    pub key: String,
+    #[serde(flatten)]
+    pub info: ApiKeyResponse,
+}
+
+/// 通过API密钥认证后授予的权限
+#[derive(Debug, Clone)]
+pub struct ApiKeyGrant {
+    pub key_id: u32,
+    pub scopes: Vec<ApiKeyScope>,
+}
+
+impl ApiKeyGrant {
+    pub fn has_scope(&self, scope: ApiKeyScope) -> bool {
+        self.scopes.contains(&scope)
+    }
+}
+
+/// API密钥服务实现
+pub struct ApiKeyServiceImpl {
+    repositories: Arc<RepositoriesImpl>,
+}
+
+impl ApiKeyServiceImpl {
+    /// 创建新的API密钥服务实例
+    pub fn new(repositories: Arc<RepositoriesImpl>) -> Self {
+        Self { repositories }
+    }
+
+    /// 创建API密钥
+    pub async fn create_api_key(&self, user_id: u32, req: CreateApiKeyRequest) -> AppResult<CreateApiKeyResponse> {
+        let name = req.name.trim();
+        if name.is_empty() || name.chars().count() > 64 {
+            return Err(AppError::validation("API key name must be 1 to 64 characters"));
+        }
+
+        let mut scopes = Vec::new();
+        for scope in &req.scopes {
+            let scope = ApiKeyScope::parse(scope)
+                .ok_or_else(|| AppError::validation(format!("Unknown API key scope: {}", scope)))?;
+            if !scopes.contains(&scope) {
+                scopes.push(scope);
+            }
+        }
+        if scopes.is_empty() {
+            return Err(AppError::validation("At least one scope is required"));
+        }
+
+        let ip_allowlist: Vec<String> = req
+            .ip_allowlist
+            .unwrap_or_default()
+            .iter()
+            .map(|entry| entry.trim().to_string())
+            .filter(|entry| !entry.is_empty())
+            .collect();
+        if let Some(entry) = ip_allowlist.iter().find(|entry| !is_valid_ip_entry(entry)) {
+            return Err(AppError::validation(format!("Invalid IP allowlist entry: {}", entry)));
+        }
+
+        let repository = self.repositories.api_key_repository();
+        if repository.find_active_by_user_id(user_id).await?.len() >= MAX_API_KEYS_PER_USER {
+            return Err(AppError::validation("Too many API keys"));
+        }
+
+        let key = format!("{}{}", API_KEY_PREFIX, CryptoUtils::generate_random_string(API_KEY_RANDOM_LENGTH));
+        let scopes = scopes.iter().map(ApiKeyScope::as_str).collect::<Vec<_>>().join(",");
+        let ip_allowlist = (!ip_allowlist.is_empty()).then(|| ip_allowlist.join(","));
+
+        let api_key = repository
+            .create_api_key(
+                user_id,
+                name,
+                &key[..API_KEY_DISPLAY_LENGTH],
+                &CryptoUtils::sha256(&key),
+                &scopes,
+                ip_allowlist.as_deref(),
+            )
+            .await?;
+
+        tracing::info!("User {} created API key {}", user_id, api_key.id);
+        Ok(CreateApiKeyResponse {
+            key,
+            info: api_key.into(),
+        })
+    }
+
+    /// 查询用户的有效API密钥
+    pub async fn list_api_keys(&self, user_id: u32) -> AppResult<Vec<ApiKeyResponse>> {
+        let keys = self.repositories.api_key_repository().find_active_by_user_id(user_id).await?;
+        Ok(keys.into_iter().map(ApiKeyResponse::from).collect())
+    }
+
+    /// 吊销API密钥
+    pub async fn revoke_api_key(&self, user_id: u32, id: u32) -> AppResult<()> {
+        if !self.repositories.api_key_repository().revoke_api_key(user_id, id).await? {
+            return Err(AppError::validation("API key not found"));
+        }
+        tracing::info!("User {} revoked API key {}", user_id, id);
+        Ok(())
+    }
+
+    /// 使用明文密钥认证，校验来源IP后返回所属用户和权限
+    pub async fn authenticate(&self, key: &str, client_ip: Option<IpAddr>) -> AppResult<(User, ApiKeyGrant)> {
+        let repository = self.repositories.api_key_repository();
+        let api_key = repository
+            .find_active_by_hash(&CryptoUtils::sha256(key))
+            .await?
+            .ok_or_else(|| AppError::authentication("Invalid API key"))?;
+
+        if !api_key.allows_ip(client_ip) {
+            tracing::warn!("API key {} used from disallowed address {:?}", api_key.id, client_ip);
+            return Err(AppError::authorization("Request address is not allowed for this API key"));
+        }
+
+        let user = self
+            .repositories
+            .user_repository()
+            .find_by_id(api_key.user_id)
+            .await?
+            .ok_or_else(|| AppError::authentication("Invalid API key"))?;
+
+        let stale = api_key
+            .last_used_at
+            .is_none_or(|last| (Utc::now() - last).num_seconds() >= LAST_USED_REFRESH_SECONDS);
+        if stale {
+            repository.touch_last_used(api_key.id).await?;
+        }
+
+        Ok((user, ApiKeyGrant {
+            key_id: api_key.id,
+            scopes: api_key.scope_list(),
+        }))
+    }
+}
@@ -0,0 +1,113 @@
+use std::sync::Arc;
+use serde::Serialize;
+use serde_json::json;
+use crate::models::audit_log::{AuditLog, AuditOutcome, NewAuditLog, EVENT_ACCOUNT_LOCKED};
+use crate::repositories::RepositoriesImpl;
+use crate::utils::{AppError, AppResult};
+
+/// 统计登录失败的时间窗口（秒）
+const LOGIN_FAILURE_WINDOW_SECONDS: usize = 900;
+/// 窗口内允许的最大失败次数，达到后锁定账户
+const MAX_LOGIN_FAILURES: i64 = 5;
+/// 账户锁定时长（秒）
+const LOCKOUT_SECONDS: usize = 900;
+/// 默认分页大小
+const DEFAULT_PAGE_SIZE: u32 = 20;
+/// 最大分页大小
+const MAX_PAGE_SIZE: u32 = 100;
+
+/// 审计日志列表响应
+#[derive(Debug, Serialize)]
+pub struct AuditLogListResponse {
+    pub logs: Vec<AuditLog>,
+    pub total: i64,
+    pub page: u32,
+    pub limit: u32,
+}
+
+/// 审计日志和登录失败锁定
+///
+/// 邮箱登录以被尝试的账户为单位（`email:<邮箱>`）锁定，与请求来源无关；钱包登录按`wallet:<地址>:<客户端IP>`锁定，
+/// 窗口内连续失败达到上限后该主体在锁定期内无法登录。
+#[derive(Clone)]
+pub struct AuditServiceImpl {
+    repositories: Arc<RepositoriesImpl>,
+}
+
+impl AuditServiceImpl {
+    /// 创建新的审计服务实例
+    pub fn new(repositories: Arc<RepositoriesImpl>) -> Self {
+        Self { repositories }
+    }
+
+    /// 写入审计日志；所记录的操作已经发生，写入失败只记录错误
+    pub async fn record(&self, entry: NewAuditLog) {
+        if let Err(err) = self.repositories.audit_log_repository().create(&entry).await {
+            tracing::error!("Failed to write audit log {:?}: {:?}", entry, err);
+        }
+    }
+
+    /// 写入必须留痕的审计日志，写入失败时返回错误，调用方应放弃对应操作
+    pub async fn record_required(&self, entry: NewAuditLog) -> AppResult<()> {
+        self.repositories.audit_log_repository().create(&entry).await?;
+        Ok(())
+    }
+
+    /// 账户处于锁定期时记录本次尝试并返回错误
+    pub async fn ensure_not_locked(&self, subject: &str, entry: NewAuditLog) -> AppResult<()> {
+        let Some(ttl) = self.repositories.redis_repository().account_lock_ttl(subject).await? else {
+            return Ok(());
+        };
+
+        self.record(entry.outcome(AuditOutcome::Locked)).await;
+        Err(AppError::authentication(format!(
+            "Too many failed attempts, try again in {} minutes",
+            (ttl + 59) / 60
+        )))
+    }
+
+    /// 记录一次成功的认证并清除失败计数
+    pub async fn record_success(&self, subject: &str, entry: NewAuditLog) -> AppResult<()> {
+        self.record(entry.outcome(AuditOutcome::Success)).await;
+        self.repositories.redis_repository().clear_login_failures(subject).await
+    }
+
+    /// 记录一次失败的认证，窗口内失败次数达到上限时锁定账户
+    pub async fn record_failure(&self, subject: &str, entry: NewAuditLog) -> AppResult<()> {
+        let redis = self.repositories.redis_repository();
+        let failures = redis.increment_login_failures(subject, LOGIN_FAILURE_WINDOW_SECONDS).await?;
+
+        let lockout = (failures >= MAX_LOGIN_FAILURES).then(|| {
+            let mut lockout = entry.clone();
+            lockout.event = EVENT_ACCOUNT_LOCKED.to_string();
+            lockout.detail(json!({ "failures": failures, "lockout_seconds": LOCKOUT_SECONDS }))
+        });
+        self.record(entry.outcome(AuditOutcome::Failure)).await;
+
+        if let Some(lockout) = lockout {
+            tracing::warn!("Locking {} after {} failed attempts", subject, failures);
+            redis.lock_account(subject, LOCKOUT_SECONDS).await?;
+            redis.clear_login_failures(subject).await?;
+            self.record(lockout.outcome(AuditOutcome::Locked)).await;
+        }
+        Ok(())
+    }
+
+    /// 分页查询审计日志，`user_id`为空时查询全部用户
+    pub async fn list(
+        &self,
+        user_id: Option<u32>,
+        event_prefix: &str,
+        page: Option<u32>,
+        limit: Option<u32>,
+    ) -> AppResult<AuditLogListResponse> {
+        let page = page.unwrap_or(1).max(1);
+        let limit = limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+
+        let repository = self.repositories.audit_log_repository();
+        let logs = repository.list(user_id, event_prefix, limit, (page - 1) * limit).await?;
+        let total = repository.count(user_id, event_prefix).await?;
+
+        Ok(AuditLogListResponse { logs, total, page, limit })
+    }
+}
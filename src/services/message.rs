@@ -0,0 +1,105 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+use async_trait::async_trait;
+use reqwest::Client;
+use crate::config::{Config, MailgunConfig};
+use crate::utils::{AppResult, AppError};
+
+/// 邮件发送服务
+#[async_trait]
+pub trait MailService: Send + Sync {
+    async fn send_mail(&self, to: &str, subject: &str, body: &str) -> AppResult<()>;
+}
+
+/// 根据配置创建邮件服务
+///
+/// `mail_transport`为`mailgun`或`file`；未配置时，有Mailgun密钥则使用Mailgun，否则写入本地文件。
+pub fn create_mail_service(config: &Config) -> Arc<dyn MailService> {
+    let transport = config.mail_transport.as_deref().unwrap_or(
+        if config.mailgun.apikey.is_empty() { "file" } else { "mailgun" },
+    );
+
+    match transport {
+        "mailgun" => Arc::new(MailgunMailService::new(config.mailgun.clone())),
+        other => {
+            if other != "file" {
+                tracing::warn!("Unknown mail transport {}, falling back to file", other);
+            }
+            Arc::new(FileMailService::new("./logs/mail"))
+        }
+    }
+}
+
+/// Mailgun HTTP API实现
+pub struct MailgunMailService {
+    http_client: Client,
+    config: MailgunConfig,
+}
+
+impl MailgunMailService {
+    pub fn new(config: MailgunConfig) -> Self {
+        Self {
+            http_client: Client::new(),
+            config,
+        }
+    }
+}
+
+#[async_trait]
+impl MailService for MailgunMailService {
+    async fn send_mail(&self, to: &str, subject: &str, body: &str) -> AppResult<()> {
+        let url = format!("https://api.mailgun.net/v3/{}/messages", self.config.domain);
+        let params = [
+            ("from", self.config.sender.as_str()),
+            ("to", to),
+            ("subject", subject),
+            ("text", body),
+        ];
+
+        let response = self.http_client
+            .post(&url)
+            .basic_auth("api", Some(&self.config.apikey))
+            .form(&params)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            return Err(AppError::external_service("mailgun", format!("{}: {}", status, text)));
+        }
+
+        tracing::info!("Mail sent via Mailgun to {}", to);
+        Ok(())
+    }
+}
+
+/// 本地文件实现，用于开发和测试环境
+pub struct FileMailService {
+    dir: PathBuf,
+}
+
+impl FileMailService {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+}
+
+#[async_trait]
+impl MailService for FileMailService {
+    async fn send_mail(&self, to: &str, subject: &str, body: &str) -> AppResult<()> {
+        tokio::fs::create_dir_all(&self.dir).await?;
+
+        let file_name = format!(
+            "{}_{}.txt",
+            chrono::Utc::now().format("%Y%m%d%H%M%S%3f"),
+            to.replace(|c: char| !c.is_ascii_alphanumeric() && c != '@' && c != '.', "_")
+        );
+        let path = self.dir.join(file_name);
+        let content = format!("To: {}\nSubject: {}\n\n{}\n", to, subject, body);
+        tokio::fs::write(&path, content).await?;
+
+        tracing::info!("Mail to {} written to {}", to, path.display());
+        Ok(())
+    }
+}
@@ -5,14 +5,28 @@ use crate::utils::AppResult;
 
 pub mod user;
 pub mod solana;
+pub mod message;
+pub mod api_key;
+pub mod admin;
+pub mod audit;
+pub mod rate_limit;
 
 pub use user::*;
 pub use solana::*;
+pub use message::*;
+pub use api_key::*;
+pub use admin::*;
+pub use audit::*;
+pub use rate_limit::*;
 
 /// 服务层实现
 pub struct ServicesImpl {
     user_service: UserServiceImpl,
     solana_service: SolanaServiceImpl,
+    api_key_service: ApiKeyServiceImpl,
+    admin_service: AdminServiceImpl,
+    audit_service: AuditServiceImpl,
+    rate_limit_service: RateLimitServiceImpl,
 }
 
 impl ServicesImpl {
@@ -21,10 +35,14 @@ impl ServicesImpl {
         config: Arc<Config>,
         repositories: Arc<RepositoriesImpl>,
     ) -> AppResult<Arc<Self>> {
+        // 创建审计服务
+        let audit_service = AuditServiceImpl::new(repositories.clone());
+
         // 创建用户服务
         let user_service = UserServiceImpl::new(
             config.clone(),
             repositories.clone(),
+            audit_service.clone(),
         ).await?;
 
         // 创建Solana服务
@@ -33,9 +51,22 @@ impl ServicesImpl {
             repositories.clone(),
         ).await?;
 
+        // 创建API密钥服务
+        let api_key_service = ApiKeyServiceImpl::new(repositories.clone());
+
+        // 创建管理后台服务
+        let admin_service = AdminServiceImpl::new(config.clone(), repositories.clone(), audit_service.clone());
+
+        // 创建限流服务
+        let rate_limit_service = RateLimitServiceImpl::new(config.clone(), repositories.clone());
+
         let services = Self {
             user_service,
             solana_service,
+            api_key_service,
+            admin_service,
+            audit_service,
+            rate_limit_service,
         };
 
         Ok(Arc::new(services))
@@ -48,4 +79,20 @@ impl ServicesImpl {
     pub fn solana_service(&self) -> &SolanaServiceImpl {
         &self.solana_service
     }
+
+    pub fn api_key_service(&self) -> &ApiKeyServiceImpl {
+        &self.api_key_service
+    }
+
+    pub fn admin_service(&self) -> &AdminServiceImpl {
+        &self.admin_service
+    }
+
+    pub fn audit_service(&self) -> &AuditServiceImpl {
+        &self.audit_service
+    }
+
+    pub fn rate_limit_service(&self) -> &RateLimitServiceImpl {
+        &self.rate_limit_service
+    }
 }
@@ -0,0 +1,37 @@
+use std::sync::Arc;
+use crate::config::Config;
+use crate::repositories::{RateLimitStatus, RepositoriesImpl};
+use crate::utils::AppResult;
+
+/// 限流服务实现
+pub struct RateLimitServiceImpl {
+    config: Arc<Config>,
+    repositories: Arc<RepositoriesImpl>,
+}
+
+impl RateLimitServiceImpl {
+    /// 创建新的限流服务实例
+    pub fn new(config: Arc<Config>, repositories: Arc<RepositoriesImpl>) -> Self {
+        Self { config, repositories }
+    }
+
+    /// 按路由组策略记录一次请求，限流关闭或路由组未配置策略时返回`None`
+    ///
+    /// `identity`为请求方标识，例如`key:12`、`user:34`或`ip:1.2.3.4`。
+    pub async fn check(&self, policy: &str, identity: &str) -> AppResult<Option<RateLimitStatus>> {
+        if !self.config.rate_limit.enabled {
+            return Ok(None);
+        }
+        let Some(rule) = self.config.rate_limit.policies.get(policy) else {
+            return Ok(None);
+        };
+
+        let key = format!("ratelimit:{}:{}", policy, identity);
+        let status = self
+            .repositories
+            .redis_repository()
+            .rate_limit(&key, rule.limit, rule.window_seconds)
+            .await?;
+        Ok(Some(status))
+    }
+}
@@ -1,10 +1,22 @@
+use std::collections::HashMap;
 use std::sync::Arc;
+use chrono::Utc;
+use crate::blockchain::{Commitment, Mint, SolanaClientService, TradeRecorder, TransactionParser};
 use crate::config::Config;
 use crate::repositories::RepositoriesImpl;
 use crate::models::solana::*;
-use crate::utils::AppResult;
+use crate::utils::{AppError, AppResult};
 use serde::{Deserialize, Serialize};
 
+/// 单次批量查询的最大代币数
+const MAX_MULTI_TOKEN_MINTS: usize = 500;
+/// 单次回填默认处理的签名数
+const DEFAULT_BACKFILL_SIGNATURES: usize = 1000;
+/// 单次回填最多处理的签名数，更多的历史通过`next_before`分批继续
+const MAX_BACKFILL_SIGNATURES: usize = 5000;
+/// `getSignaturesForAddress`每页数量
+const SIGNATURE_PAGE_SIZE: usize = 1000;
+
 /// 代币查询请求
 #[derive(Debug, Deserialize)]
 pub struct TokenQueryRequest {
@@ -23,6 +35,41 @@ pub struct TokenListResponse {
     pub limit: u32,
 }
 
+/// 历史交易回填请求
+///
+/// 从`before`签名（不含，为空时从最新交易开始）向前遍历`address`（代币或程序）的交易，
+/// 到`until`签名（不含）或`start_slot`为止；`end_slot`和尚未最终确认的交易跳过。
+#[derive(Debug, Deserialize)]
+pub struct BackfillRequest {
+    pub address: String,
+    pub before: Option<String>,
+    pub until: Option<String>,
+    pub start_slot: Option<u64>,
+    pub end_slot: Option<u64>,
+    pub limit: Option<usize>,
+}
+
+/// 回填结果
+#[derive(Debug, Default, Serialize)]
+pub struct BackfillSummary {
+    /// 处理的签名数
+    pub scanned: usize,
+    /// 执行失败而跳过的交易数
+    pub failed: usize,
+    /// 写入的交易数，已存在的交易也计入
+    pub saved: usize,
+    /// 获取、解析或写入出错的交易数，这些交易可以单独重新回填
+    pub errors: usize,
+    /// 出错的交易签名
+    pub error_signatures: Vec<String>,
+    /// 中断回填的错误，例如签名列表获取失败
+    pub last_error: Option<String>,
+    /// 继续回填使用的`before`，即最后处理的签名
+    pub next_before: Option<String>,
+    /// 已遍历到范围末尾且没有出错
+    pub complete: bool,
+}
+
 /// Solana服务实现
 pub struct SolanaServiceImpl {
     config: Arc<Config>,
@@ -61,6 +108,237 @@ impl SolanaServiceImpl {
         }
     }
 
+    /// 批量获取代币信息
+    ///
+    /// 数据库中的代币使用链上的精度和供应量覆盖；只存在于链上的代币返回基础信息。
+    /// 链上数据通过`getMultipleAccounts`批量获取。
+    pub async fn multi_token_info(&self, client: &SolanaClientService, mints: &[String]) -> AppResult<Vec<SolToken>> {
+        let mut mints: Vec<String> = mints
+            .iter()
+            .map(|mint| mint.trim().to_string())
+            .filter(|mint| !mint.is_empty())
+            .collect();
+        mints.sort();
+        mints.dedup();
+        if mints.len() > MAX_MULTI_TOKEN_MINTS {
+            return Err(AppError::validation(format!("At most {} mints are allowed", MAX_MULTI_TOKEN_MINTS)));
+        }
+
+        let mut tokens: HashMap<String, SolToken> = self.repositories.solana_repository()
+            .find_tokens_by_mints(&mints).await?
+            .into_iter()
+            .map(|token| (token.mint.clone(), token))
+            .collect();
+        let onchain = client.get_mints(&mints).await?;
+
+        Ok(mints
+            .into_iter()
+            .filter_map(|mint| {
+                let chain_mint = onchain.get(&mint);
+                match (tokens.remove(&mint), chain_mint) {
+                    (Some(mut token), Some(chain_mint)) => {
+                        token.decimals = chain_mint.decimals;
+                        token.supply = ui_amount(chain_mint.supply, chain_mint.decimals);
+                        Some(token)
+                    }
+                    (Some(token), None) => Some(token),
+                    (None, Some(chain_mint)) => Some(Self::token_from_mint(&mint, chain_mint)),
+                    (None, None) => None,
+                }
+            })
+            .collect())
+    }
+
+    /// 钱包的全部代币持仓
+    ///
+    /// 余额来自链上代币账户，精度通过一次批量请求获取，价格和盈亏取自数据库。
+    pub async fn wallet_positions(&self, client: &SolanaClientService, wallet: &str) -> AppResult<Vec<Position>> {
+        let mut balances: HashMap<String, u64> = HashMap::new();
+        for (_, account) in client.get_token_accounts_by_owner(wallet).await? {
+            if account.amount > 0 {
+                *balances.entry(account.mint).or_default() += account.amount;
+            }
+        }
+        if balances.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mints: Vec<String> = balances.keys().cloned().collect();
+        let onchain = client.get_mints(&mints).await?;
+        let repository = self.repositories.solana_repository();
+        let prices: HashMap<String, f64> = repository.find_tokens_by_mints(&mints).await?
+            .into_iter()
+            .map(|token| (token.mint, token.price))
+            .collect();
+        let holders: HashMap<String, SolHolder> = repository.find_holders_by_wallet(wallet).await?
+            .into_iter()
+            .map(|holder| (holder.mint.clone(), holder))
+            .collect();
+
+        let mut positions: Vec<Position> = balances
+            .into_iter()
+            .filter_map(|(mint, raw_amount)| {
+                // 查不到精度时无法换算数量，跳过该持仓而不是按0位小数计算
+                let Some(decimals) = onchain.get(&mint).map(|chain_mint| chain_mint.decimals) else {
+                    tracing::warn!("钱包 {} 的代币 {} 未查到Mint信息，跳过持仓", wallet, mint);
+                    return None;
+                };
+                let amount = ui_amount(raw_amount, decimals);
+                let price = prices.get(&mint).copied().unwrap_or(0.0);
+                let (pnl, bet) = holders.get(&mint).map_or((0.0, 0.0), |holder| (holder.pnl, holder.bet));
+                Some(Position {
+                    mint,
+                    wallet: wallet.to_string(),
+                    amount,
+                    value_usd: amount * price,
+                    pnl,
+                    pnl_percentage: if bet > 0.0 { pnl / bet * 100.0 } else { 0.0 },
+                })
+            })
+            .collect();
+        positions.sort_by(|a, b| b.value_usd.total_cmp(&a.value_usd));
+        Ok(positions)
+    }
+
+    /// 钱包在指定代币上的持仓
+    pub async fn token_position(&self, client: &SolanaClientService, wallet: &str, mint: &str) -> AppResult<Option<Position>> {
+        let positions = self.wallet_positions(client, wallet).await?;
+        Ok(positions.into_iter().find(|position| position.mint == mint))
+    }
+
+    /// 回填历史交易，按签名幂等写入交易表，可重复执行
+    pub async fn backfill(&self, client: &SolanaClientService, req: &BackfillRequest) -> AppResult<BackfillSummary> {
+        let address = req.address.trim();
+        if address.is_empty() {
+            return Err(AppError::validation("Address is required"));
+        }
+        let limit = req.limit.unwrap_or(DEFAULT_BACKFILL_SIGNATURES).clamp(1, MAX_BACKFILL_SIGNATURES);
+        // 尚未最终确认的交易由监听写入并结算
+        let finalized_slot = client.get_slot_with_commitment(Commitment::Finalized).await?;
+        let end_slot = req.end_slot.map_or(finalized_slot, |end_slot| end_slot.min(finalized_slot));
+
+        let mut summary = BackfillSummary::default();
+        let mut decimals: HashMap<String, u8> = HashMap::new();
+        let mut before = req.before.clone();
+
+        // 出错时不中断，已处理的进度通过`next_before`返回
+        let mut reached_end = false;
+        'pages: loop {
+            let page = match client
+                .get_signatures_for_address(address, before.as_deref(), req.until.as_deref(), SIGNATURE_PAGE_SIZE)
+                .await
+            {
+                Ok(page) => page,
+                Err(e) => {
+                    tracing::warn!("回填 {} 获取签名失败: {}", address, e);
+                    summary.last_error = Some(e.to_string());
+                    break;
+                }
+            };
+            let last_page = page.len() < SIGNATURE_PAGE_SIZE;
+
+            for info in page {
+                if req.start_slot.is_some_and(|start_slot| info.slot < start_slot) {
+                    reached_end = true;
+                    break 'pages;
+                }
+                before = Some(info.signature.clone());
+                if info.slot > end_slot {
+                    continue;
+                }
+
+                summary.scanned += 1;
+                if info.err.is_some() {
+                    summary.failed += 1;
+                } else {
+                    match self.backfill_signature(client, &info.signature, &mut decimals).await {
+                        Ok(true) => summary.saved += 1,
+                        Ok(false) => {}
+                        Err(e) => {
+                            tracing::warn!("回填交易 {} 失败: {}", info.signature, e);
+                            summary.errors += 1;
+                            summary.error_signatures.push(info.signature.clone());
+                        }
+                    }
+                }
+                if summary.scanned >= limit {
+                    break 'pages;
+                }
+            }
+
+            if last_page {
+                reached_end = true;
+                break;
+            }
+        }
+
+        summary.complete = reached_end && summary.errors == 0;
+        summary.next_before = before;
+        tracing::info!("回填 {} 完成: {:?}", address, summary);
+        Ok(summary)
+    }
+
+    /// 回填单笔交易，只记录以SOL计价的代币交换
+    async fn backfill_signature(
+        &self,
+        client: &SolanaClientService,
+        signature: &str,
+        decimals: &mut HashMap<String, u8>,
+    ) -> AppResult<bool> {
+        let Some(transaction) = client.get_transaction(signature).await? else {
+            return Err(AppError::blockchain_error(format!("交易不存在: {}", signature)));
+        };
+        // 回填只处理已最终确认的区块，不参与结算，也不更新持仓和统计
+        let Some(event) = TransactionParser::parse_token_swap(signature, &transaction, Commitment::Finalized)? else {
+            return Ok(false);
+        };
+        let Some(mint) = TradeRecorder::traded_mint(&event).map(str::to_string) else {
+            return Ok(false);
+        };
+
+        if !decimals.contains_key(&mint) {
+            let mint_decimals = client.get_mints(std::slice::from_ref(&mint)).await?
+                .get(&mint)
+                .map(|chain_mint| chain_mint.decimals)
+                .ok_or_else(|| AppError::blockchain_error(format!("未获取到代币铸造信息: {}", mint)))?;
+            decimals.insert(mint.clone(), mint_decimals);
+        }
+        let Some(record) = TradeRecorder::build_trade(&event, decimals[&mint]) else {
+            return Ok(false);
+        };
+        self.repositories.solana_repository().create_transaction(&record).await?;
+        Ok(true)
+    }
+
+    /// 由链上铸造信息构建仅包含基础字段的代币
+    fn token_from_mint(mint: &str, chain_mint: &Mint) -> SolToken {
+        let now = Utc::now();
+        SolToken {
+            id: 0,
+            created_at: now,
+            updated_at: now,
+            deleted_at: None,
+            create_time: 0,
+            ecosystem: "solana".to_string(),
+            mint: mint.to_string(),
+            creator: chain_mint.mint_authority.clone().unwrap_or_default(),
+            symbol: String::new(),
+            name: String::new(),
+            decimals: chain_mint.decimals,
+            supply: ui_amount(chain_mint.supply, chain_mint.decimals),
+            description: String::new(),
+            logo: String::new(),
+            banner_url: String::new(),
+            social: String::new(),
+            progress: 0.0,
+            price: 0.0,
+            price_change: String::new(),
+            volume: String::new(),
+            market_cap: 0.0,
+            holder_count: 0,
+        }
+    }
+
     /// 更新代币价格 - 简化版本
     pub async fn update_token_price(&self, mint: &str, price: f64, market_cap: f64) -> AppResult<()> {
         self.repositories.solana_repository()
@@ -68,3 +346,8 @@ impl SolanaServiceImpl {
         Ok(())
     }
 }
+
+/// 按精度把链上原始数量换算为显示数量
+fn ui_amount(raw: u64, decimals: u8) -> f64 {
+    raw as f64 / 10f64.powi(decimals as i32)
+}
@@ -1,28 +1,88 @@
 use std::sync::Arc;
 use crate::config::Config;
 use crate::repositories::RepositoriesImpl;
+use crate::blockchain::{EvmChain, EvmVerifier, TypedData, WalletVerifier};
+use crate::services::{create_mail_service, AuditLogListResponse, AuditServiceImpl, MailService};
+use crate::models::audit_log::*;
 use crate::models::user::*;
-use crate::utils::{AppResult, AppError, CryptoUtils};
+use crate::models::user_wallet::UserWallet;
+use crate::utils::{AppResult, AppError, CryptoUtils, PasswordCheck, PasswordUtils, Validator};
+use crate::utils::{TwoFactorUtils, TOTP_DIGITS, TOTP_STEP_SECONDS};
 use serde::{Deserialize, Serialize};
+use serde_json::json;
 
 /// 钱包登录请求
 #[derive(Debug, Deserialize)]
 pub struct WalletLoginRequest {
     pub wallet_address: String,
     pub network: String,
+    pub challenge: String,
     pub signature: String,
     pub invite_code: Option<String>,
 }
 
+/// 钱包登录挑战请求
+#[derive(Debug, Deserialize)]
+pub struct WalletChallengeRequest {
+    pub wallet_address: String,
+    pub network: String,
+}
+
+/// 钱包登录挑战响应
+///
+/// `challenge`为待签名文本；EVM钱包也可改用`typed_data`进行EIP-712签名，并将其JSON作为挑战提交。
+#[derive(Debug, Serialize)]
+pub struct WalletChallengeResponse {
+    pub wallet: String,
+    pub network: String,
+    pub challenge: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub typed_data: Option<TypedData>,
+}
+
+/// 登录网络对应的签名方案
+enum WalletNetwork {
+    Solana,
+    Evm(EvmChain),
+}
+
+impl WalletNetwork {
+    /// 绑定记录中的网络类别，同一EVM地址在各EVM链上共用
+    fn family(&self) -> &'static str {
+        match self {
+            Self::Solana => "sol",
+            Self::Evm(_) => "evm",
+        }
+    }
+
+    fn parse(network: &str) -> AppResult<Self> {
+        if matches!(network.to_lowercase().as_str(), "sol" | "solana") {
+            return Ok(Self::Solana);
+        }
+        EvmChain::from_network(network)
+            .map(Self::Evm)
+            .ok_or_else(|| AppError::validation(format!("Unsupported network: {}", network)))
+    }
+}
+
 /// 钱包登录响应
 #[derive(Debug, Serialize)]
 pub struct WalletLoginResponse {
     pub username: String,
     pub wallet: String,
     pub token: String,
+    pub refresh_token: String,
     pub invite_code: String,
 }
 
+/// 钱包登录结果：账户开启双重认证时返回中间令牌
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum WalletLoginResult {
+    Token(WalletLoginResponse),
+    TwoFactorRequired(TwoFactorRequiredResponse),
+}
+
 /// 邮箱登录请求
 #[derive(Debug, Deserialize)]
 pub struct EmailLoginRequest {
@@ -30,19 +90,189 @@ pub struct EmailLoginRequest {
     pub password: String,
 }
 
+/// 用户注册请求
+#[derive(Debug, Deserialize)]
+pub struct UserRegisterRequest {
+    pub email: String,
+    pub password: String,
+    pub username: Option<String>,
+    pub invite_code: Option<String>,
+}
+
+/// 找回密码请求
+#[derive(Debug, Deserialize)]
+pub struct FindPasswordRequest {
+    pub email: String,
+}
+
+/// 重置密码请求
+#[derive(Debug, Deserialize)]
+pub struct ResetPasswordRequest {
+    pub email: String,
+    pub code: String,
+    pub new_password: String,
+}
+
+/// 修改密码请求
+#[derive(Debug, Deserialize)]
+pub struct UpdatePasswordRequest {
+    pub old_password: String,
+    pub new_password: String,
+}
+
+/// 绑定钱包请求，需使用`wallet_challenge`签发的挑战证明钱包所有权
+#[derive(Debug, Deserialize)]
+pub struct LinkWalletRequest {
+    pub wallet_address: String,
+    pub network: String,
+    pub challenge: String,
+    pub signature: String,
+}
+
+/// 绑定邮箱请求
+#[derive(Debug, Deserialize)]
+pub struct BindEmailRequest {
+    pub email: String,
+    pub password: String,
+}
+
 /// 用户Token响应
 #[derive(Debug, Serialize)]
 pub struct UserTokenResponse {
     pub username: String,
     pub email: String,
     pub token: String,
+    pub refresh_token: String,
     pub invite_code: Option<String>,
+    /// 通过钱包登录发起的双重认证，会话绑定该钱包
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wallet: Option<String>,
+}
+
+/// 双重认证中间令牌在Redis中保存的内容
+#[derive(Debug, Serialize, Deserialize)]
+struct TwoFactorTicket {
+    user_id: u32,
+    /// 钱包登录发起时的钱包地址，邮箱登录为空
+    #[serde(default)]
+    wallet: Option<String>,
+}
+
+/// 邮箱登录响应：未开启双重认证时直接签发令牌，否则返回中间令牌
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum EmailLoginResponse {
+    Token(UserTokenResponse),
+    TwoFactorRequired(TwoFactorRequiredResponse),
+}
+
+/// 需要双重认证的中间响应
+#[derive(Debug, Serialize)]
+pub struct TwoFactorRequiredResponse {
+    pub two_factor_required: bool,
+    pub two_factor_token: String,
+    pub expires_in: i64,
+}
+
+/// 双重认证登录请求，`code`可以是TOTP验证码或恢复码
+#[derive(Debug, Deserialize)]
+pub struct TwoFactorLoginRequest {
+    pub two_factor_token: String,
+    pub code: String,
+}
+
+/// 双重认证设置响应
+#[derive(Debug, Serialize)]
+pub struct TwoFactorSetupResponse {
+    pub secret: String,
+    pub otpauth_uri: String,
+}
+
+/// 启用双重认证响应，恢复码仅在此时返回一次
+#[derive(Debug, Serialize)]
+pub struct TwoFactorEnableResponse {
+    pub recovery_codes: Vec<String>,
+}
+
+/// 刷新令牌请求
+#[derive(Debug, Deserialize)]
+pub struct RefreshTokenRequest {
+    pub refresh_token: String,
+}
+
+/// 刷新令牌响应
+#[derive(Debug, Serialize)]
+pub struct RefreshTokenResponse {
+    pub token: String,
+    pub refresh_token: String,
+    pub expires_in: i64,
+}
+
+/// 刷新令牌默认有效期（秒）
+const DEFAULT_REFRESH_EXPIRE: i64 = 86400 * 30;
+
+/// 刷新令牌在Redis中的记录，令牌族ID即登录会话ID
+#[derive(Debug, Serialize, Deserialize)]
+struct RefreshTokenRecord {
+    user_id: u32,
+    session_id: String,
+    wallet: Option<String>,
+}
+
+/// 重置密码验证码有效期（秒）
+const RESET_CODE_TTL_SECONDS: usize = 900;
+/// 单个验证码允许的最大尝试次数
+const RESET_CODE_MAX_ATTEMPTS: i64 = 5;
+/// 找回密码的发送频率限制：(时间窗口秒数, 窗口内最多次数)
+const RESET_REQUEST_LIMITS: [(usize, i64); 2] = [(60, 1), (3600, 5)];
+
+/// 双重认证中间令牌有效期（秒）
+const TWO_FACTOR_TICKET_TTL_SECONDS: usize = 300;
+/// 单个中间令牌允许的最大尝试次数
+const TWO_FACTOR_MAX_ATTEMPTS: i64 = 5;
+/// 恢复码数量
+const RECOVERY_CODE_COUNT: usize = 10;
+
+/// 一次登录签发的令牌对
+struct SessionTokens {
+    token: String,
+    refresh_token: String,
 }
 
 /// 用户服务实现
 pub struct UserServiceImpl {
     config: Arc<Config>,
     repositories: Arc<RepositoriesImpl>,
+    wallet_verifier: WalletVerifier,
+    evm_verifier: EvmVerifier,
+    mail_service: Arc<dyn MailService>,
+    audit: AuditServiceImpl,
+}
+
+/// 钱包登录的锁定主体
+///
+/// 只有EVM地址不区分大小写，Solana地址原样保留；按客户端IP分别计数，他人的失败签名不会锁住钱包主人。
+fn wallet_lock_subject(wallet: &str, client: &ClientContext) -> String {
+    let wallet = wallet.trim();
+    let wallet = if wallet.starts_with("0x") || wallet.starts_with("0X") {
+        wallet.to_lowercase()
+    } else {
+        wallet.to_string()
+    };
+    let ip = client.ip.map_or_else(|| "unknown".to_string(), |ip| ip.to_string());
+    format!("wallet:{}:{}", wallet, ip)
+}
+
+/// 是否为凭据错误（密码、签名、验证码等），数据库等基础设施故障不计入登录失败
+fn is_credential_error(err: &AppError) -> bool {
+    matches!(
+        err,
+        AppError::Authentication { .. }
+            | AppError::Validation { .. }
+            | AppError::WalletVerificationError(_)
+            | AppError::BadRequest(_)
+            | AppError::Unauthorized(_)
+    )
 }
 
 impl UserServiceImpl {
@@ -50,31 +280,149 @@ impl UserServiceImpl {
     pub async fn new(
         config: Arc<Config>,
         repositories: Arc<RepositoriesImpl>,
+        audit: AuditServiceImpl,
     ) -> AppResult<Self> {
+        let wallet_verifier = WalletVerifier::new(
+            config.clone(),
+            repositories.redis_repository().clone(),
+        )?;
+        let evm_verifier = EvmVerifier::new(
+            config.clone(),
+            repositories.redis_repository().clone(),
+        )?;
+        let mail_service = create_mail_service(&config);
+
+        // 已有用户开启双重认证时必须配置独立的加密密钥，否则这些密钥都无法解密
+        if config.two_factor.encryption_key.is_empty() && repositories.user_totp_repository().count().await? > 0 {
+            return Err(AppError::internal(
+                "two_factor.encryption_key must be configured while users have 2FA secrets stored",
+            ));
+        }
+
         Ok(Self {
             config,
             repositories,
+            wallet_verifier,
+            evm_verifier,
+            mail_service,
+            audit,
         })
     }
 
-    /// 加密密码
-    fn encrypt_password(&self, password: &str) -> String {
-        // 使用SHA256双重加密，与Go版本保持一致
-        let sha256_hash = CryptoUtils::sha256(password);
-        CryptoUtils::sha256(&sha256_hash)
-    }
-
     /// 生成用户Token
-    async fn create_user_token(&self, user: &User) -> AppResult<String> {
+    async fn create_user_token(&self, user: &User, wallet: Option<String>, session_id: &str) -> AppResult<String> {
+        let email = (!user.email.is_empty()).then(|| user.email.clone());
         CryptoUtils::generate_jwt(
             user.id,
-            Some(user.email.clone()),
-            None, // wallet
+            email,
+            wallet,
+            session_id,
             &self.config.jwt_token.sign,
             self.config.jwt_token.expire,
         )
     }
 
+    /// 刷新令牌有效期（秒）
+    fn refresh_expire(&self) -> usize {
+        self.config.jwt_token.refresh_expire.unwrap_or(DEFAULT_REFRESH_EXPIRE) as usize
+    }
+
+    /// 开启新的登录会话，签发访问令牌和该会话的第一个刷新令牌
+    async fn start_session(&self, user: &User, wallet: Option<String>) -> AppResult<SessionTokens> {
+        let session_id = uuid::Uuid::new_v4().to_string();
+        let refresh_token = CryptoUtils::generate_random_string(48);
+        let token_hash = CryptoUtils::sha256(&refresh_token);
+        let record = RefreshTokenRecord {
+            user_id: user.id,
+            session_id: session_id.clone(),
+            wallet: wallet.clone(),
+        };
+
+        let redis = self.repositories.redis_repository();
+        redis.cache_user_session(user.id, &session_id, self.refresh_expire()).await?;
+        redis.set_refresh_family(&session_id, &token_hash, self.refresh_expire()).await?;
+        redis.store_refresh_token(&token_hash, &record, self.refresh_expire()).await?;
+
+        let token = self.create_user_token(user, wallet, &session_id).await?;
+
+        Ok(SessionTokens { token, refresh_token })
+    }
+
+    /// 检查会话是否仍然有效
+    pub async fn is_session_active(&self, user_id: u32, session_id: &str) -> AppResult<bool> {
+        let session = self.repositories.redis_repository().get_user_session(user_id, session_id).await?;
+        Ok(session.is_some())
+    }
+
+    /// 使用刷新令牌换取新的令牌对
+    ///
+    /// 每个刷新令牌只能使用一次；已轮换过的令牌再次出现时视为泄露，整个会话被吊销。
+    pub async fn refresh_token(&self, req: RefreshTokenRequest) -> AppResult<RefreshTokenResponse> {
+        let redis = self.repositories.redis_repository();
+        let token_hash = CryptoUtils::sha256(&req.refresh_token);
+
+        let record: RefreshTokenRecord = redis
+            .get_refresh_token(&token_hash)
+            .await?
+            .ok_or_else(|| AppError::authentication("Refresh token is invalid or expired"))?;
+
+        if !self.is_session_active(record.user_id, &record.session_id).await? {
+            return Err(AppError::authentication("Session has been revoked"));
+        }
+
+        let refresh_token = CryptoUtils::generate_random_string(48);
+        let new_hash = CryptoUtils::sha256(&refresh_token);
+        if !redis
+            .rotate_refresh_family(&record.session_id, &token_hash, &new_hash, self.refresh_expire())
+            .await?
+        {
+            tracing::warn!(
+                "Refresh token reuse detected for user {}, revoking session {}",
+                record.user_id,
+                record.session_id
+            );
+            self.logout(record.user_id, &record.session_id).await?;
+            return Err(AppError::authentication("Refresh token has been revoked"));
+        }
+        redis.store_refresh_token(&new_hash, &record, self.refresh_expire()).await?;
+
+        let user = self.user_info(record.user_id).await?;
+        let token = self.create_user_token(&user, record.wallet, &record.session_id).await?;
+
+        Ok(RefreshTokenResponse {
+            token,
+            refresh_token,
+            expires_in: self.config.jwt_token.expire,
+        })
+    }
+
+    /// 注销单个会话
+    pub async fn logout(&self, user_id: u32, session_id: &str) -> AppResult<()> {
+        let redis = self.repositories.redis_repository();
+        redis.invalidate_user_session(user_id, session_id).await?;
+        redis.revoke_refresh_family(session_id).await
+    }
+
+    /// 注销用户除当前会话以外的全部会话
+    pub async fn logout_others(&self, user_id: u32, current_session_id: &str) -> AppResult<()> {
+        let redis = self.repositories.redis_repository();
+        for session_id in redis.list_user_sessions(user_id).await? {
+            if session_id != current_session_id {
+                self.logout(user_id, &session_id).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// 注销用户的全部会话
+    pub async fn logout_all(&self, user_id: u32) -> AppResult<()> {
+        let redis = self.repositories.redis_repository();
+        for session_id in redis.invalidate_all_user_sessions(user_id).await? {
+            redis.revoke_refresh_family(&session_id).await?;
+        }
+        Ok(())
+    }
+
     /// 生成随机用户名
     fn generate_username(&self) -> String {
         CryptoUtils::generate_random_string(8)
@@ -98,31 +446,606 @@ impl UserServiceImpl {
         }
     }
 
-    /// 邮箱登录 - 简化版本
-    pub async fn email_login(&self, req: EmailLoginRequest) -> AppResult<UserTokenResponse> {
-        // 查找用户
-        let user = match self.repositories.user_repository().find_by_email(&req.email).await? {
+    /// 邮箱登录，连续失败过多时锁定该邮箱
+    pub async fn email_login(&self, req: EmailLoginRequest, client: &ClientContext) -> AppResult<EmailLoginResponse> {
+        let subject = format!("email:{}", req.email.trim().to_lowercase());
+        let audit = NewAuditLog::new(None, EVENT_EMAIL_LOGIN).target(req.email.clone()).client(client);
+        self.audit.ensure_not_locked(&subject, audit.clone()).await?;
+
+        // 查找用户并验证密码
+        let user_repository = self.repositories.user_repository();
+        let user = match user_repository.verify_password(&req.email, &req.password).await? {
             Some(user) => user,
-            None => return Err(AppError::validation("Wrong email or password")),
+            None => {
+                let user_id = user_repository.find_by_email(&req.email).await?.map(|user| user.id);
+                self.audit.record_failure(&subject, NewAuditLog { user_id, ..audit }).await?;
+                return Err(AppError::validation("Wrong email or password"));
+            }
         };
+        let audit = NewAuditLog { user_id: Some(user.id), ..audit };
 
-        // 验证密码
-        let encrypted_password = self.encrypt_password(&req.password);
-        if user.password != encrypted_password {
-            return Err(AppError::validation("Wrong email or password"));
+        // 开启双重认证时先签发中间令牌
+        let two_factor = self.repositories.user_totp_repository().find_by_user_id(user.id).await?;
+        if two_factor.is_some_and(|totp| totp.enabled) {
+            // 密码正确但尚未完成登录，失败计数在双重认证通过后清除
+            self.audit.record(audit.detail(json!({ "two_factor_required": true }))).await;
+            let required = self.issue_two_factor_ticket(user.id, None).await?;
+            return Ok(EmailLoginResponse::TwoFactorRequired(required));
         }
 
-        // 生成token
-        let token = self.create_user_token(&user).await?;
+        self.audit.record_success(&subject, audit).await?;
+        Ok(EmailLoginResponse::Token(self.email_session(user).await?))
+    }
+
+    /// 签发双重认证中间令牌，Redis中只保存其哈希
+    async fn issue_two_factor_ticket(&self, user_id: u32, wallet: Option<String>) -> AppResult<TwoFactorRequiredResponse> {
+        let ticket = CryptoUtils::generate_random_string(32);
+        self.repositories
+            .redis_repository()
+            .store_two_factor_ticket(
+                &CryptoUtils::sha256(&ticket),
+                &TwoFactorTicket { user_id, wallet },
+                TWO_FACTOR_TICKET_TTL_SECONDS,
+            )
+            .await?;
+
+        Ok(TwoFactorRequiredResponse {
+            two_factor_required: true,
+            two_factor_token: ticket,
+            expires_in: TWO_FACTOR_TICKET_TTL_SECONDS as i64,
+        })
+    }
+
+    /// 双重认证登录：校验中间令牌和验证码后签发令牌
+    pub async fn two_factor_login(&self, req: TwoFactorLoginRequest, client: &ClientContext) -> AppResult<UserTokenResponse> {
+        let redis = self.repositories.redis_repository();
+        let ticket_hash = CryptoUtils::sha256(&req.two_factor_token);
+        let invalid = || AppError::authentication("Invalid or expired 2FA token");
+
+        let ticket: TwoFactorTicket = redis.get_two_factor_ticket(&ticket_hash).await?.ok_or_else(invalid)?;
+        let attempts = redis
+            .increment_two_factor_attempts(&ticket_hash, TWO_FACTOR_TICKET_TTL_SECONDS)
+            .await?;
+        if attempts > TWO_FACTOR_MAX_ATTEMPTS {
+            redis.take_two_factor_ticket::<TwoFactorTicket>(&ticket_hash).await?;
+            return Err(AppError::authentication("Too many attempts, please login again"));
+        }
+
+        let user = self.user_info(ticket.user_id).await?;
+        let subject = format!("email:{}", user.email.to_lowercase());
+        let audit = NewAuditLog::new(Some(user.id), EVENT_TWO_FACTOR_LOGIN).target(user.email.clone()).client(client);
+        self.audit.ensure_not_locked(&subject, audit.clone()).await?;
+
+        if let Err(err) = self.verify_two_factor_code(&user, &req.code).await {
+            if is_credential_error(&err) {
+                self.audit.record_failure(&subject, audit).await?;
+            }
+            return Err(err);
+        }
+
+        // 中间令牌只能使用一次
+        if redis.take_two_factor_ticket::<TwoFactorTicket>(&ticket_hash).await?.is_none() {
+            return Err(invalid());
+        }
+
+        self.audit.record_success(&subject, audit).await?;
+        match ticket.wallet {
+            Some(wallet) => {
+                redis.clear_login_failures(&wallet_lock_subject(&wallet, client)).await?;
+                self.wallet_session(user, wallet).await
+            }
+            None => self.email_session(user).await,
+        }
+    }
+
+    /// 为邮箱账户开始新会话
+    async fn email_session(&self, user: User) -> AppResult<UserTokenResponse> {
+        let tokens = self.start_session(&user, None).await?;
 
         Ok(UserTokenResponse {
             username: user.username,
             email: user.email,
-            token,
+            token: tokens.token,
+            refresh_token: tokens.refresh_token,
+            invite_code: Some(self.get_invite_code(user.id)),
+            wallet: None,
+        })
+    }
+
+    /// 为通过钱包登录并完成双重认证的账户开始新会话
+    async fn wallet_session(&self, user: User, wallet: String) -> AppResult<UserTokenResponse> {
+        let tokens = self.start_session(&user, Some(wallet.clone())).await?;
+
+        Ok(UserTokenResponse {
+            username: user.username,
+            email: user.email,
+            token: tokens.token,
+            refresh_token: tokens.refresh_token,
             invite_code: Some(self.get_invite_code(user.id)),
+            wallet: Some(wallet),
         })
     }
 
+    /// 生成钱包登录挑战
+    pub async fn wallet_challenge(&self, req: WalletChallengeRequest) -> AppResult<WalletChallengeResponse> {
+        match WalletNetwork::parse(&req.network)? {
+            WalletNetwork::Solana => {
+                let message = self.wallet_verifier.generate_login_challenge(&req.wallet_address).await?;
+                Ok(WalletChallengeResponse {
+                    wallet: req.wallet_address,
+                    network: req.network,
+                    challenge: message.to_string(),
+                    typed_data: None,
+                })
+            }
+            WalletNetwork::Evm(chain) => {
+                let wallet = EvmVerifier::checksum_address(&req.wallet_address)?;
+                let challenge = self.evm_verifier.generate_login_challenge(&wallet, chain).await?;
+                Ok(WalletChallengeResponse {
+                    wallet,
+                    network: req.network,
+                    challenge: challenge.message,
+                    typed_data: Some(challenge.typed_data),
+                })
+            }
+        }
+    }
+
+    /// 按网络选择验证器验证挑战签名，返回网络和规范化后的钱包地址（EVM地址统一为EIP-55格式）
+    async fn verify_wallet_ownership(
+        &self,
+        network: &str,
+        wallet_address: &str,
+        challenge: &str,
+        signature: &str,
+    ) -> AppResult<(WalletNetwork, String)> {
+        let network = WalletNetwork::parse(network)?;
+        let (wallet, result) = match network {
+            WalletNetwork::Solana => {
+                let result = self.wallet_verifier.verify_login_challenge(
+                    wallet_address,
+                    challenge,
+                    signature,
+                ).await?;
+                (wallet_address.to_string(), result)
+            }
+            WalletNetwork::Evm(chain) => {
+                let wallet = EvmVerifier::checksum_address(wallet_address)?;
+                let result = self.evm_verifier.verify_login_challenge(
+                    &wallet,
+                    chain,
+                    challenge,
+                    signature,
+                ).await?;
+                (wallet, result)
+            }
+        };
+        if !result.is_valid {
+            return Err(AppError::authentication(
+                result.error.unwrap_or_else(|| "Invalid wallet signature".to_string()),
+            ));
+        }
+        Ok((network, wallet))
+    }
+
+    /// 钱包所属用户，用于将失败记录归属到账户
+    async fn wallet_owner(&self, wallet_address: &str) -> AppResult<Option<u32>> {
+        let wallet = EvmVerifier::checksum_address(wallet_address).unwrap_or_else(|_| wallet_address.to_string());
+        let user = self.repositories.user_repository().find_by_wallet(&wallet).await?;
+        Ok(user.map(|user| user.id))
+    }
+
+    /// 钱包登录，任一已绑定钱包都登录到同一用户；签名连续失败过多时锁定该钱包
+    ///
+    /// 绑定的邮箱账户开启双重认证时与邮箱登录一样先签发中间令牌。
+    pub async fn wallet_login(&self, req: WalletLoginRequest, client: &ClientContext) -> AppResult<WalletLoginResult> {
+        let subject = wallet_lock_subject(&req.wallet_address, client);
+        let audit = NewAuditLog::new(None, EVENT_WALLET_LOGIN)
+            .wallet(req.wallet_address.clone())
+            .detail(json!({ "network": req.network }))
+            .client(client);
+        self.audit.ensure_not_locked(&subject, audit.clone()).await?;
+
+        let verified = self
+            .verify_wallet_ownership(&req.network, &req.wallet_address, &req.challenge, &req.signature)
+            .await;
+        let (network, wallet) = match verified {
+            Ok(verified) => verified,
+            Err(err) => {
+                if is_credential_error(&err) {
+                    let user_id = self.wallet_owner(&req.wallet_address).await?;
+                    self.audit.record_failure(&subject, NewAuditLog { user_id, ..audit }).await?;
+                }
+                return Err(err);
+            }
+        };
+
+        // 查找或创建钱包用户
+        let user_repository = self.repositories.user_repository();
+        let user = match user_repository.find_by_wallet(&wallet).await? {
+            Some(user) => user,
+            None if user_repository.is_wallet_held_by_deleted_user(&wallet).await? => {
+                return Err(AppError::authorization("The account bound to this wallet has been deleted"));
+            }
+            None => {
+                let parent = self.resolve_parent(req.invite_code.as_deref()).await?;
+                user_repository
+                    .create_wallet_user(self.generate_username(), wallet.clone(), network.family(), parent)
+                    .await?
+            }
+        };
+
+        let audit = NewAuditLog { user_id: Some(user.id), ..audit.wallet(wallet.clone()) };
+
+        // 开启双重认证时先签发中间令牌，会话在双重认证通过后绑定该钱包
+        let two_factor = self.repositories.user_totp_repository().find_by_user_id(user.id).await?;
+        if two_factor.is_some_and(|totp| totp.enabled) {
+            self.audit.record(audit.detail(json!({ "network": req.network, "two_factor_required": true }))).await;
+            let required = self.issue_two_factor_ticket(user.id, Some(wallet)).await?;
+            return Ok(WalletLoginResult::TwoFactorRequired(required));
+        }
+
+        self.audit.record_success(&subject, audit).await?;
+
+        // 生成token
+        let tokens = self.start_session(&user, Some(wallet.clone())).await?;
+
+        Ok(WalletLoginResult::Token(WalletLoginResponse {
+            username: user.username,
+            wallet,
+            token: tokens.token,
+            refresh_token: tokens.refresh_token,
+            invite_code: self.get_invite_code(user.id),
+        }))
+    }
+
+    /// 为当前用户绑定新钱包
+    pub async fn link_wallet(&self, user: &User, req: LinkWalletRequest, client: &ClientContext) -> AppResult<UserWallet> {
+        let verified = self
+            .verify_wallet_ownership(&req.network, &req.wallet_address, &req.challenge, &req.signature)
+            .await;
+        let (network, wallet) = match verified {
+            Ok(verified) => verified,
+            Err(err) => {
+                if is_credential_error(&err) {
+                    let audit = NewAuditLog::new(Some(user.id), EVENT_WALLET_SIGNATURE)
+                        .wallet(req.wallet_address.clone())
+                        .detail(json!({ "network": req.network, "action": "link_wallet" }))
+                        .outcome(AuditOutcome::Failure)
+                        .client(client);
+                    self.audit.record(audit).await;
+                }
+                return Err(err);
+            }
+        };
+
+        let linked = self
+            .repositories
+            .user_wallet_repository()
+            .link_wallet(user.id, &wallet, network.family())
+            .await?;
+
+        // 邮箱账户首次绑定的钱包作为主钱包
+        if user.wallet.is_none() {
+            self.repositories.user_repository().update_wallet(user.id, Some(&wallet)).await?;
+        }
+
+        Ok(linked)
+    }
+
+    /// 解绑钱包，账户至少保留一种登录方式
+    pub async fn unlink_wallet(&self, user: &User, wallet_address: &str) -> AppResult<()> {
+        let wallet_repository = self.repositories.user_wallet_repository();
+        let wallets = wallet_repository.find_by_user_id(user.id).await?;
+        let Some(target) = wallets
+            .iter()
+            .find(|w| w.wallet == wallet_address || (w.network == "evm" && w.wallet.eq_ignore_ascii_case(wallet_address)))
+        else {
+            return Err(AppError::validation("Wallet not linked"));
+        };
+
+        if wallets.len() == 1 && user.email.is_empty() {
+            return Err(AppError::validation("Cannot unlink the only login method"));
+        }
+
+        wallet_repository.unlink_wallet(user.id, &target.wallet).await?;
+
+        // 解绑的是主钱包时，改用剩余的第一个钱包
+        if user.wallet.as_deref() == Some(target.wallet.as_str()) {
+            let next = wallets.iter().find(|w| w.id != target.id).map(|w| w.wallet.as_str());
+            self.repositories.user_repository().update_wallet(user.id, next).await?;
+        }
+
+        Ok(())
+    }
+
+    /// 查询用户绑定的全部钱包
+    pub async fn user_wallets(&self, user_id: u32) -> AppResult<Vec<UserWallet>> {
+        self.repositories.user_wallet_repository().find_by_user_id(user_id).await
+    }
+
+    /// 为钱包账户绑定邮箱和密码，之后可使用邮箱登录
+    pub async fn bind_email(&self, user: &User, req: BindEmailRequest) -> AppResult<()> {
+        if !user.email.is_empty() {
+            return Err(AppError::validation("Email already bound"));
+        }
+
+        let email = req.email.trim().to_lowercase();
+        if !Validator::is_valid_email(&email) {
+            return Err(AppError::validation("Invalid email format"));
+        }
+        Validator::check_password_strength(&req.password)?;
+
+        let user_repository = self.repositories.user_repository();
+        if user_repository.find_by_email(&email).await?.is_some() {
+            return Err(AppError::validation("Email already exists"));
+        }
+
+        let hash = PasswordUtils::hash(&req.password)?;
+        user_repository.update_email(user.id, &email, &hash).await
+    }
+
+    /// 将邀请码解析为上级用户ID，上级不存在时忽略
+    async fn resolve_parent(&self, invite_code: Option<&str>) -> AppResult<Option<String>> {
+        let Some(parent_id) = invite_code.and_then(|code| self.parse_invite_code(code)) else {
+            return Ok(None);
+        };
+
+        let parent = self.repositories.user_repository().find_by_id(parent_id).await?;
+        Ok(parent.map(|user| user.id.to_string()))
+    }
+
+    /// 邮箱注册
+    pub async fn register(&self, req: UserRegisterRequest) -> AppResult<UserTokenResponse> {
+        let email = req.email.trim().to_lowercase();
+        if !Validator::is_valid_email(&email) {
+            return Err(AppError::validation("Invalid email format"));
+        }
+        Validator::check_password_strength(&req.password)?;
+
+        let username = match req.username.as_deref().map(str::trim) {
+            Some(username) if !username.is_empty() => {
+                self.check_username_available(username).await?;
+                username.to_string()
+            }
+            _ => self.generate_username(),
+        };
+        let parent = self.resolve_parent(req.invite_code.as_deref()).await?;
+
+        // create_user会拒绝已存在的邮箱
+        let user = self.repositories.user_repository().create_user(CreateUserRequest {
+            username,
+            email,
+            password: req.password,
+            parent,
+        }).await?;
+
+        let tokens = self.start_session(&user, None).await?;
+
+        Ok(UserTokenResponse {
+            invite_code: Some(self.get_invite_code(user.id)),
+            username: user.username,
+            email: user.email,
+            token: tokens.token,
+            refresh_token: tokens.refresh_token,
+            wallet: None,
+        })
+    }
+
+    /// 找回密码：向邮箱发送一次性验证码
+    ///
+    /// 无论邮箱是否注册都返回成功，避免暴露账号是否存在。
+    pub async fn find_password(&self, req: FindPasswordRequest) -> AppResult<()> {
+        let email = req.email.trim().to_lowercase();
+        if !Validator::is_valid_email(&email) {
+            return Err(AppError::validation("Invalid email format"));
+        }
+
+        let redis = self.repositories.redis_repository();
+        for (window_seconds, max_requests) in RESET_REQUEST_LIMITS {
+            if redis.increment_reset_requests(&email, window_seconds).await? > max_requests {
+                return Err(AppError::business("Too many password reset requests, please try again later"));
+            }
+        }
+
+        if self.repositories.user_repository().find_by_email(&email).await?.is_none() {
+            tracing::info!("Password reset requested for unknown email {}", email);
+            return Ok(());
+        }
+
+        let code = {
+            use rand::Rng;
+            format!("{:06}", rand::thread_rng().gen_range(0..1_000_000))
+        };
+        redis.store_reset_code(&email, &CryptoUtils::sha256(&code), RESET_CODE_TTL_SECONDS).await?;
+
+        let body = format!(
+            "Your password reset code is {}. It expires in {} minutes.\n\nIf you did not request a password reset, please ignore this email.",
+            code,
+            RESET_CODE_TTL_SECONDS / 60
+        );
+        self.mail_service.send_mail(&email, "Password reset code", &body).await
+    }
+
+    /// 使用邮箱验证码重置密码，成功后注销该用户的全部会话
+    pub async fn reset_password(&self, req: ResetPasswordRequest, client: &ClientContext) -> AppResult<()> {
+        let email = req.email.trim().to_lowercase();
+        Validator::check_password_strength(&req.new_password)?;
+        let audit = NewAuditLog::new(None, EVENT_PASSWORD_RESET).target(email.clone()).client(client);
+
+        let redis = self.repositories.redis_repository();
+        let code_hash = redis
+            .get_reset_code(&email)
+            .await?
+            .ok_or_else(|| AppError::validation("Reset code is invalid or expired"))?;
+
+        if redis.increment_reset_attempts(&email, RESET_CODE_TTL_SECONDS).await? > RESET_CODE_MAX_ATTEMPTS {
+            redis.delete_reset_code(&email).await?;
+            return Err(AppError::validation("Too many attempts, please request a new code"));
+        }
+        if CryptoUtils::sha256(req.code.trim()) != code_hash {
+            self.audit.record(audit.outcome(AuditOutcome::Failure)).await;
+            return Err(AppError::validation("Reset code is invalid or expired"));
+        }
+
+        let user_repository = self.repositories.user_repository();
+        let user = user_repository
+            .find_by_email(&email)
+            .await?
+            .ok_or_else(|| AppError::validation("Reset code is invalid or expired"))?;
+
+        redis.delete_reset_code(&email).await?;
+        user_repository.update_password(user.id, &PasswordUtils::hash(&req.new_password)?).await?;
+        self.audit
+            .record_success(&format!("email:{}", email), NewAuditLog { user_id: Some(user.id), ..audit })
+            .await?;
+        self.logout_all(user.id).await
+    }
+
+    /// 修改密码，成功后注销该用户的其他会话
+    pub async fn update_password(
+        &self,
+        mut user: User,
+        session_id: &str,
+        req: UpdatePasswordRequest,
+        client: &ClientContext,
+    ) -> AppResult<()> {
+        let audit = NewAuditLog::new(Some(user.id), EVENT_PASSWORD_CHANGE).client(client);
+        if PasswordUtils::verify(&req.old_password, &user.password) == PasswordCheck::Invalid {
+            self.audit.record(audit.outcome(AuditOutcome::Failure)).await;
+            return Err(AppError::validation("Wrong old password"));
+        }
+        Validator::check_password_strength(&req.new_password)?;
+
+        user.password = PasswordUtils::hash(&req.new_password)?;
+        self.repositories.user_repository().update_user(&user).await?;
+        self.audit.record(audit).await;
+
+        self.logout_others(user.id, session_id).await
+    }
+
+    /// 开始设置双重认证：生成新密钥，确认前不生效
+    pub async fn two_factor_setup(&self, user: &User) -> AppResult<TwoFactorSetupResponse> {
+        if user.email.is_empty() {
+            return Err(AppError::validation("2FA requires an email account"));
+        }
+        let totp_repository = self.repositories.user_totp_repository();
+        if totp_repository.find_by_user_id(user.id).await?.is_some_and(|totp| totp.enabled) {
+            return Err(AppError::validation("2FA already enabled"));
+        }
+
+        let secret = TwoFactorUtils::generate_secret();
+        let encrypted = TwoFactorUtils::encrypt_secret(self.two_factor_key()?, &secret)?;
+        totp_repository.save_pending(user.id, &encrypted).await?;
+
+        let totp = TwoFactorUtils::totp(secret.clone(), &self.config.two_factor.issuer, &user.email)?;
+        Ok(TwoFactorSetupResponse {
+            secret: TwoFactorUtils::secret_base32(&secret),
+            otpauth_uri: totp.get_url(),
+        })
+    }
+
+    /// 使用验证码确认并启用双重认证，返回一次性恢复码
+    pub async fn two_factor_enable(&self, user: &User, code: &str, client: &ClientContext) -> AppResult<TwoFactorEnableResponse> {
+        let totp_repository = self.repositories.user_totp_repository();
+        let pending = match totp_repository.find_by_user_id(user.id).await? {
+            Some(totp) if !totp.enabled => totp,
+            Some(_) => return Err(AppError::validation("2FA already enabled")),
+            None => return Err(AppError::validation("2FA setup not started")),
+        };
+
+        self.check_totp_code(user, &pending.secret, code).await?;
+
+        let recovery_codes = TwoFactorUtils::generate_recovery_codes(RECOVERY_CODE_COUNT);
+        let hashes: Vec<String> = recovery_codes.iter().map(|code| TwoFactorUtils::hash_recovery_code(code)).collect();
+        totp_repository.enable(user.id, &hashes).await?;
+        self.audit.record(NewAuditLog::new(Some(user.id), EVENT_TWO_FACTOR_ENABLE).client(client)).await;
+
+        Ok(TwoFactorEnableResponse { recovery_codes })
+    }
+
+    /// 使用验证码或恢复码关闭双重认证
+    pub async fn two_factor_disable(&self, user: &User, code: &str, client: &ClientContext) -> AppResult<()> {
+        self.verify_two_factor_code(user, code).await?;
+        self.repositories.user_totp_repository().delete(user.id).await?;
+        self.audit.record(NewAuditLog::new(Some(user.id), EVENT_TWO_FACTOR_DISABLE).client(client)).await;
+        Ok(())
+    }
+
+    /// 查询当前用户的认证事件记录
+    pub async fn security_log(&self, user_id: u32, page: Option<u32>, limit: Option<u32>) -> AppResult<AuditLogListResponse> {
+        self.audit.list(Some(user_id), "auth.", page, limit).await
+    }
+
+    /// 校验已启用的双重认证：6位数字按TOTP校验，否则按恢复码校验
+    async fn verify_two_factor_code(&self, user: &User, code: &str) -> AppResult<()> {
+        let totp_repository = self.repositories.user_totp_repository();
+        let totp = match totp_repository.find_by_user_id(user.id).await? {
+            Some(totp) if totp.enabled => totp,
+            _ => return Err(AppError::validation("2FA not enabled")),
+        };
+
+        let code = code.trim();
+        if code.len() == TOTP_DIGITS && code.chars().all(|c| c.is_ascii_digit()) {
+            return self.check_totp_code(user, &totp.secret, code).await;
+        }
+
+        if totp_repository.consume_recovery_code(&totp, &TwoFactorUtils::hash_recovery_code(code)).await? {
+            tracing::info!("User {} used a 2FA recovery code", user.id);
+            Ok(())
+        } else {
+            Err(AppError::authentication("Invalid 2FA code"))
+        }
+    }
+
+    /// 校验TOTP验证码，同一时间步的验证码只能使用一次
+    async fn check_totp_code(&self, user: &User, encrypted_secret: &str, code: &str) -> AppResult<()> {
+        let secret = TwoFactorUtils::decrypt_secret(self.two_factor_key()?, encrypted_secret)?;
+        let totp = TwoFactorUtils::totp(secret, &self.config.two_factor.issuer, &user.email)?;
+        let now = chrono::Utc::now().timestamp() as u64;
+
+        let step = TwoFactorUtils::verify_code(&totp, code, now)
+            .ok_or_else(|| AppError::authentication("Invalid 2FA code"))?;
+        let fresh = self
+            .repositories
+            .redis_repository()
+            .mark_totp_step_used(user.id, step, (TOTP_STEP_SECONDS * 3) as usize)
+            .await?;
+        if !fresh {
+            return Err(AppError::authentication("2FA code already used"));
+        }
+        Ok(())
+    }
+
+    /// TOTP密钥的加密密钥，未配置时不能使用双重认证
+    fn two_factor_key(&self) -> AppResult<&str> {
+        match self.config.two_factor.encryption_key.as_str() {
+            "" => Err(AppError::internal("two_factor.encryption_key is not configured")),
+            key => Ok(key),
+        }
+    }
+
+    /// 修改用户名
+    pub async fn edit_username(&self, mut user: User, username: &str) -> AppResult<()> {
+        let username = username.trim();
+        if username == user.username {
+            return Ok(());
+        }
+        self.check_username_available(username).await?;
+
+        user.username = username.to_string();
+        self.repositories.user_repository().update_user(&user).await
+    }
+
+    /// 校验用户名格式并确认未被占用
+    async fn check_username_available(&self, username: &str) -> AppResult<()> {
+        Validator::check_username(username)?;
+        if self.repositories.user_repository().find_by_username(username).await?.is_some() {
+            return Err(AppError::validation("Username already exists"));
+        }
+        Ok(())
+    }
+
     /// 根据邮箱查找用户
     pub async fn find_by_email(&self, email: &str) -> AppResult<Option<User>> {
         self.repositories.user_repository().find_by_email(email).await
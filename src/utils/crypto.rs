@@ -11,6 +11,7 @@ pub struct Claims {
     pub iat: i64,     // 签发时间
     pub email: Option<String>,
     pub wallet: Option<String>,
+    pub jti: String,  // 会话ID
 }
 
 pub struct CryptoUtils;
@@ -23,27 +24,17 @@ impl CryptoUtils {
         format!("{:x}", hasher.finalize())
     }
     
-    /// 密码加密（使用SHA256 + 盐）
-    pub fn hash_password(password: &str) -> String {
-        let salt = "watermelo_salt_2024"; // 在实际项目中应该使用随机盐
-        Self::sha256(&format!("{}{}", password, salt))
-    }
-    
-    /// 验证密码
-    pub fn verify_password(password: &str, hash: &str) -> bool {
-        Self::hash_password(password) == hash
-    }
-    
     /// 生成JWT Token
     pub fn generate_jwt(
         user_id: u32,
         email: Option<String>,
         wallet: Option<String>,
+        session_id: &str,
         secret: &str,
-        expire_hours: i64,
+        expire_seconds: i64,
     ) -> AppResult<String> {
         let now = Utc::now();
-        let exp = now + Duration::hours(expire_hours);
+        let exp = now + Duration::seconds(expire_seconds);
         
         let claims = Claims {
             sub: user_id.to_string(),
@@ -51,6 +42,7 @@ impl CryptoUtils {
             iat: now.timestamp(),
             email,
             wallet,
+            jti: session_id.to_string(),
         };
         
         let token = encode(
@@ -104,3 +96,17 @@ impl CryptoUtils {
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_jwt_expire_in_seconds() {
+        let token = CryptoUtils::generate_jwt(1, None, None, "sid", "secret", 7200).unwrap();
+        let claims = CryptoUtils::verify_jwt(&token, "secret").unwrap();
+        assert_eq!(claims.exp - claims.iat, 7200);
+        assert_eq!(claims.jti, "sid");
+        assert!(CryptoUtils::verify_jwt(&token, "other").is_err());
+    }
+}
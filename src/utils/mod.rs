@@ -1,6 +1,12 @@
 pub mod error;
 pub mod crypto;
+pub mod password;
+pub mod validation;
+pub mod two_factor;
 pub mod time;
 
 pub use error::*;
 pub use crypto::*;
+pub use password::*;
+pub use validation::*;
+pub use two_factor::*;
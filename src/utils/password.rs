@@ -0,0 +1,84 @@
+//! 密码哈希
+//!
+//! 新密码统一使用Argon2id（每个用户随机盐，PHC格式存储）。
+//! 兼容Go版本遗留的`sha256(sha256(password))`无盐哈希，校验通过后应重新哈希。
+
+use argon2::password_hash::rand_core::OsRng;
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::Argon2;
+use crate::utils::{AppError, AppResult, CryptoUtils};
+
+/// 密码校验结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PasswordCheck {
+    /// 密码错误
+    Invalid,
+    /// 密码正确
+    Valid,
+    /// 密码正确，但存储的是遗留哈希，需要重新哈希
+    ValidLegacy,
+}
+
+pub struct PasswordUtils;
+
+impl PasswordUtils {
+    /// 使用Argon2id哈希密码，返回PHC格式字符串
+    pub fn hash(password: &str) -> AppResult<String> {
+        let salt = SaltString::generate(&mut OsRng);
+        Argon2::default()
+            .hash_password(password.as_bytes(), &salt)
+            .map(|hash| hash.to_string())
+            .map_err(|e| AppError::crypto(format!("Password hashing failed: {}", e)))
+    }
+
+    /// 校验密码，同时支持Argon2 PHC字符串和遗留SHA256哈希
+    pub fn verify(password: &str, stored: &str) -> PasswordCheck {
+        if stored.starts_with('$') {
+            let Ok(parsed) = PasswordHash::new(stored) else {
+                return PasswordCheck::Invalid;
+            };
+            return match Argon2::default().verify_password(password.as_bytes(), &parsed) {
+                Ok(()) => PasswordCheck::Valid,
+                Err(_) => PasswordCheck::Invalid,
+            };
+        }
+
+        if !stored.is_empty() && constant_time_eq(Self::legacy_hash(password).as_bytes(), stored.as_bytes()) {
+            PasswordCheck::ValidLegacy
+        } else {
+            PasswordCheck::Invalid
+        }
+    }
+
+    /// Go版本使用的双重SHA256哈希
+    fn legacy_hash(password: &str) -> String {
+        CryptoUtils::sha256(&CryptoUtils::sha256(password))
+    }
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_argon2_roundtrip() {
+        let hash = PasswordUtils::hash("secret123").unwrap();
+        assert!(hash.starts_with("$argon2id$"));
+        assert_eq!(PasswordUtils::verify("secret123", &hash), PasswordCheck::Valid);
+        assert_eq!(PasswordUtils::verify("wrong", &hash), PasswordCheck::Invalid);
+        // 每次哈希使用不同的盐
+        assert_ne!(hash, PasswordUtils::hash("secret123").unwrap());
+    }
+
+    #[test]
+    fn test_legacy_hash_accepted() {
+        let legacy = CryptoUtils::sha256(&CryptoUtils::sha256("secret123"));
+        assert_eq!(PasswordUtils::verify("secret123", &legacy), PasswordCheck::ValidLegacy);
+        assert_eq!(PasswordUtils::verify("wrong", &legacy), PasswordCheck::Invalid);
+        assert_eq!(PasswordUtils::verify("", ""), PasswordCheck::Invalid);
+    }
+}
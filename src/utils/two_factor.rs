@@ -0,0 +1,161 @@
+//! 双重认证（RFC 6238 TOTP）
+//!
+//! TOTP密钥使用AES-256-GCM加密后入库，恢复码只保存SHA256哈希。
+
+use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::{Aes256Gcm, Nonce};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use rand::{Rng, RngCore};
+use sha2::{Digest, Sha256};
+use totp_rs::{Algorithm, Secret, TOTP};
+use crate::utils::{AppError, AppResult, CryptoUtils};
+
+/// 验证码位数
+pub const TOTP_DIGITS: usize = 6;
+/// 时间步长（秒）
+pub const TOTP_STEP_SECONDS: u64 = 30;
+/// 允许前后偏移的时间步数
+const TOTP_SKEW: u64 = 1;
+/// 密钥长度（字节），与RFC 4226推荐的160位一致
+const SECRET_LENGTH: usize = 20;
+/// AES-GCM随机数长度
+const NONCE_LENGTH: usize = 12;
+
+pub struct TwoFactorUtils;
+
+impl TwoFactorUtils {
+    /// 生成随机TOTP密钥
+    pub fn generate_secret() -> Vec<u8> {
+        let mut secret = vec![0u8; SECRET_LENGTH];
+        rand::thread_rng().fill_bytes(&mut secret);
+        secret
+    }
+
+    /// 构造TOTP实例，`account`用于验证器App中的显示名
+    pub fn totp(secret: Vec<u8>, issuer: &str, account: &str) -> AppResult<TOTP> {
+        TOTP::new(
+            Algorithm::SHA1,
+            TOTP_DIGITS,
+            TOTP_SKEW as u8,
+            TOTP_STEP_SECONDS,
+            secret,
+            Some(issuer.replace(':', "")),
+            account.replace(':', ""),
+        )
+        .map_err(|e| AppError::crypto(format!("Invalid TOTP parameters: {:?}", e)))
+    }
+
+    /// 密钥的Base32编码，供无法扫码时手动输入
+    pub fn secret_base32(secret: &[u8]) -> String {
+        Secret::Raw(secret.to_vec()).to_encoded().to_string()
+    }
+
+    /// 校验验证码，成功时返回匹配的时间步，用于防止同一验证码被重放
+    pub fn verify_code(totp: &TOTP, code: &str, time: u64) -> Option<u64> {
+        let code = code.trim();
+        if code.len() != TOTP_DIGITS || !code.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+
+        let current = time / TOTP_STEP_SECONDS;
+        (current.saturating_sub(TOTP_SKEW)..=current + TOTP_SKEW)
+            .find(|step| totp.generate(step * TOTP_STEP_SECONDS) == code)
+    }
+
+    /// 生成一组一次性恢复码，格式为`xxxxx-xxxxx`
+    pub fn generate_recovery_codes(count: usize) -> Vec<String> {
+        const CHARSET: &[u8] = b"abcdefghjkmnpqrstuvwxyz23456789";
+        let mut rng = rand::thread_rng();
+        (0..count)
+            .map(|_| {
+                let code: String = (0..10)
+                    .map(|_| CHARSET[rng.gen_range(0..CHARSET.len())] as char)
+                    .collect();
+                format!("{}-{}", &code[..5], &code[5..])
+            })
+            .collect()
+    }
+
+    /// 恢复码哈希，忽略大小写、空格和连字符
+    pub fn hash_recovery_code(code: &str) -> String {
+        let normalized: String = code
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric())
+            .map(|c| c.to_ascii_lowercase())
+            .collect();
+        CryptoUtils::sha256(&normalized)
+    }
+
+    /// 加密TOTP密钥，返回`base64(nonce ‖ ciphertext)`
+    pub fn encrypt_secret(key: &str, secret: &[u8]) -> AppResult<String> {
+        let mut nonce = [0u8; NONCE_LENGTH];
+        rand::thread_rng().fill_bytes(&mut nonce);
+
+        let ciphertext = Self::cipher(key)
+            .encrypt(Nonce::from_slice(&nonce), secret)
+            .map_err(|_| AppError::crypto("TOTP secret encryption failed"))?;
+
+        let mut data = nonce.to_vec();
+        data.extend_from_slice(&ciphertext);
+        Ok(STANDARD.encode(data))
+    }
+
+    /// 解密TOTP密钥
+    pub fn decrypt_secret(key: &str, encrypted: &str) -> AppResult<Vec<u8>> {
+        let data = STANDARD
+            .decode(encrypted)
+            .map_err(|e| AppError::crypto(format!("Invalid encrypted TOTP secret: {}", e)))?;
+        if data.len() <= NONCE_LENGTH {
+            return Err(AppError::crypto("Invalid encrypted TOTP secret"));
+        }
+
+        let (nonce, ciphertext) = data.split_at(NONCE_LENGTH);
+        Self::cipher(key)
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| AppError::crypto("TOTP secret decryption failed"))
+    }
+
+    /// 由配置的密钥字符串派生256位AES密钥
+    fn cipher(key: &str) -> Aes256Gcm {
+        let key = Sha256::digest(key.as_bytes());
+        Aes256Gcm::new(&key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rfc6238_vector() {
+        // RFC 6238 附录B，SHA1，T=59 时8位验证码为94287082
+        let totp = TwoFactorUtils::totp(b"12345678901234567890".to_vec(), "watermelo.io", "alice@example.com").unwrap();
+        assert_eq!(totp.generate(59), "287082");
+        assert_eq!(TwoFactorUtils::verify_code(&totp, "287082", 59), Some(1));
+        // 允许一个时间步的偏移
+        assert_eq!(TwoFactorUtils::verify_code(&totp, "287082", 59 + 30), Some(1));
+        assert_eq!(TwoFactorUtils::verify_code(&totp, "287082", 59 + 90), None);
+        assert_eq!(TwoFactorUtils::verify_code(&totp, "28708a", 59), None);
+        assert!(totp.get_url().starts_with("otpauth://totp/watermelo.io:alice%40example.com?secret="));
+    }
+
+    #[test]
+    fn test_secret_encryption_roundtrip() {
+        let secret = TwoFactorUtils::generate_secret();
+        let encrypted = TwoFactorUtils::encrypt_secret("key", &secret).unwrap();
+        assert_eq!(TwoFactorUtils::decrypt_secret("key", &encrypted).unwrap(), secret);
+        assert!(TwoFactorUtils::decrypt_secret("other", &encrypted).is_err());
+        assert_ne!(encrypted, TwoFactorUtils::encrypt_secret("key", &secret).unwrap());
+    }
+
+    #[test]
+    fn test_recovery_codes() {
+        let codes = TwoFactorUtils::generate_recovery_codes(10);
+        assert_eq!(codes.len(), 10);
+        assert!(codes.iter().all(|code| code.len() == 11 && code.as_bytes()[5] == b'-'));
+        assert_eq!(
+            TwoFactorUtils::hash_recovery_code(&codes[0]),
+            TwoFactorUtils::hash_recovery_code(&format!(" {} ", codes[0].to_uppercase().replace('-', "")))
+        );
+    }
+}
@@ -0,0 +1,115 @@
+use crate::utils::{AppError, AppResult};
+
+/// 密码最小长度
+pub const PASSWORD_MIN_LENGTH: usize = 8;
+/// 密码最大长度
+pub const PASSWORD_MAX_LENGTH: usize = 128;
+/// 用户名最小长度
+pub const USERNAME_MIN_LENGTH: usize = 3;
+/// 用户名最大长度
+pub const USERNAME_MAX_LENGTH: usize = 20;
+
+pub struct Validator;
+
+impl Validator {
+    /// 校验邮箱格式
+    pub fn is_valid_email(email: &str) -> bool {
+        if email.len() > 254 || email.chars().any(char::is_whitespace) {
+            return false;
+        }
+
+        let Some((local, domain)) = email.split_once('@') else {
+            return false;
+        };
+
+        !local.is_empty()
+            && !domain.contains('@')
+            && domain.contains('.')
+            && !domain.starts_with('.')
+            && !domain.ends_with('.')
+            && !domain.contains("..")
+    }
+
+    /// 校验Solana地址：Base58编码的32字节公钥
+    pub fn is_valid_solana_address(address: &str) -> bool {
+        bs58::decode(address).into_vec().is_ok_and(|bytes| bytes.len() == 32)
+    }
+
+    /// 校验密码强度：长度8-128，且同时包含字母和数字
+    pub fn check_password_strength(password: &str) -> AppResult<()> {
+        let length = password.chars().count();
+        if !(PASSWORD_MIN_LENGTH..=PASSWORD_MAX_LENGTH).contains(&length) {
+            return Err(AppError::validation(format!(
+                "Password must be {} to {} characters",
+                PASSWORD_MIN_LENGTH, PASSWORD_MAX_LENGTH
+            )));
+        }
+
+        let has_letter = password.chars().any(|c| c.is_ascii_alphabetic());
+        let has_digit = password.chars().any(|c| c.is_ascii_digit());
+        if !has_letter || !has_digit {
+            return Err(AppError::validation("Password must contain both letters and digits"));
+        }
+
+        Ok(())
+    }
+
+    /// 校验用户名：长度3-20，仅允许字母、数字和下划线
+    pub fn check_username(username: &str) -> AppResult<()> {
+        let length = username.chars().count();
+        if !(USERNAME_MIN_LENGTH..=USERNAME_MAX_LENGTH).contains(&length) {
+            return Err(AppError::validation(format!(
+                "Username must be {} to {} characters",
+                USERNAME_MIN_LENGTH, USERNAME_MAX_LENGTH
+            )));
+        }
+
+        if !username.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Err(AppError::validation("Username may only contain letters, digits and underscores"));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_email_format() {
+        assert!(Validator::is_valid_email("alice@example.com"));
+        assert!(Validator::is_valid_email("a.b+c@mail.example.io"));
+        assert!(!Validator::is_valid_email("alice"));
+        assert!(!Validator::is_valid_email("@example.com"));
+        assert!(!Validator::is_valid_email("alice@example"));
+        assert!(!Validator::is_valid_email("alice@@example.com"));
+        assert!(!Validator::is_valid_email("alice @example.com"));
+        assert!(!Validator::is_valid_email("alice@example..com"));
+    }
+
+    #[test]
+    fn test_password_strength() {
+        assert!(Validator::check_password_strength("abcd1234").is_ok());
+        assert!(Validator::check_password_strength("abc123").is_err());
+        assert!(Validator::check_password_strength("abcdefgh").is_err());
+        assert!(Validator::check_password_strength("12345678").is_err());
+    }
+
+    #[test]
+    fn test_username_whitelist() {
+        assert!(Validator::check_username("trader_01").is_ok());
+        assert!(Validator::check_username("ab").is_err());
+        assert!(Validator::check_username("a".repeat(21).as_str()).is_err());
+        assert!(Validator::check_username("bad name").is_err());
+        assert!(Validator::check_username("名字abc").is_err());
+        assert!(Validator::check_username("<script>").is_err());
+    }
+
+    #[test]
+    fn test_solana_address() {
+        assert!(Validator::is_valid_solana_address("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"));
+        assert!(!Validator::is_valid_solana_address("test_program"));
+        assert!(!Validator::is_valid_solana_address("3yZe7d"));
+    }
+}